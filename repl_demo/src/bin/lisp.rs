@@ -0,0 +1,466 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! A tiny Lisp built on `repl_lib`, exercising completion, highlighting, hooks, and
+//! persistent history.
+
+use std::{cell::RefCell, collections::HashMap, fmt, iter::Peekable, rc::Rc};
+
+use repl_lib::{
+    Attribute, Color, CommandAction, CompleterFunc, Completions, Error, HighlightFunc, Repl,
+    ReplPlugin, Style, StyledText, UserError, WordChars, completion, delimiters_balanced, paths,
+};
+
+type Builtin = fn(&[Value]) -> Result<Value, String>;
+
+#[derive(Clone)]
+enum Value {
+    Number(f64),
+    Bool(bool),
+    Symbol(String),
+    List(Vec<Value>),
+    Lambda(Rc<Lambda>),
+    Builtin(&'static str, Builtin),
+}
+
+struct Lambda {
+    params: Vec<String>,
+    body: Vec<Value>,
+    env: Rc<RefCell<Env>>,
+}
+
+/// Variable bindings, chained to the enclosing scope.
+#[derive(Default)]
+struct Env {
+    vars: HashMap<String, Value>,
+    parent: Option<Rc<RefCell<Env>>>,
+}
+
+impl Env {
+    fn global() -> Rc<RefCell<Self>> {
+        let builtins: [(&'static str, Builtin); 13] = [
+            ("+", |args| fold_numbers(args, 0.0, |a, b| a + b)),
+            ("*", |args| fold_numbers(args, 1.0, |a, b| a * b)),
+            ("-", |args| match numbers(args)?.as_slice() {
+                [only] => Ok(Value::Number(-only)),
+                [first, rest @ ..] => Ok(Value::Number(rest.iter().fold(*first, |a, b| a - b))),
+                [] => Err("- expects at least 1 argument".to_string()),
+            }),
+            ("/", |args| match numbers(args)?.as_slice() {
+                [_, rest @ ..] if rest.contains(&0.0) => Err("division by zero".to_string()),
+                [first, rest @ ..] => Ok(Value::Number(rest.iter().fold(*first, |a, b| a / b))),
+                [] => Err("/ expects at least 1 argument".to_string()),
+            }),
+            ("=", |args| compare(args, |a, b| a == b)),
+            ("<", |args| compare(args, |a, b| a < b)),
+            (">", |args| compare(args, |a, b| a > b)),
+            ("<=", |args| compare(args, |a, b| a <= b)),
+            (">=", |args| compare(args, |a, b| a >= b)),
+            ("list", |args| Ok(Value::List(args.to_vec()))),
+            ("car", |args| match args {
+                [Value::List(items)] if !items.is_empty() => Ok(items[0].clone()),
+                _ => Err("car expects a non-empty list".to_string()),
+            }),
+            ("cdr", |args| match args {
+                [Value::List(items)] if !items.is_empty() => Ok(Value::List(items[1..].to_vec())),
+                _ => Err("cdr expects a non-empty list".to_string()),
+            }),
+            ("cons", |args| match args {
+                [head, Value::List(tail)] => {
+                    let mut items = vec![head.clone()];
+                    items.extend(tail.iter().cloned());
+                    Ok(Value::List(items))
+                }
+                _ => Err("cons expects a value and a list".to_string()),
+            }),
+        ];
+        let vars = builtins
+            .into_iter()
+            .map(|(name, f)| (name.to_string(), Value::Builtin(name, f)))
+            .collect();
+        Rc::new(RefCell::new(Self { vars, parent: None }))
+    }
+
+    fn get(&self, name: &str) -> Option<Value> {
+        match self.vars.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.parent.as_ref()?.borrow().get(name),
+        }
+    }
+
+    /// Returns every name bound in this scope and the enclosing ones.
+    fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.vars.keys().cloned().collect();
+        if let Some(parent) = self.parent.as_ref() {
+            names.extend(parent.borrow().names());
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::Bool(b) => write!(f, "{}", if *b { "#t" } else { "#f" }),
+            Value::Symbol(name) => write!(f, "{name}"),
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(Value::to_string).collect();
+                write!(f, "({})", items.join(" "))
+            }
+            Value::Lambda(_) => write!(f, "<lambda>"),
+            Value::Builtin(name, _) => write!(f, "<builtin {name}>"),
+        }
+    }
+}
+
+fn numbers(args: &[Value]) -> Result<Vec<f64>, String> {
+    args.iter()
+        .map(|arg| match arg {
+            Value::Number(n) => Ok(*n),
+            other => Err(format!("expected a number, got {other}")),
+        })
+        .collect()
+}
+
+fn fold_numbers(args: &[Value], init: f64, f: fn(f64, f64) -> f64) -> Result<Value, String> {
+    Ok(Value::Number(numbers(args)?.into_iter().fold(init, f)))
+}
+
+fn compare(args: &[Value], f: fn(f64, f64) -> bool) -> Result<Value, String> {
+    let numbers = numbers(args)?;
+    Ok(Value::Bool(numbers.windows(2).all(|w| f(w[0], w[1]))))
+}
+
+/// Parse every expression in `input`.
+fn parse(input: &str) -> Result<Vec<Value>, String> {
+    let spaced = input
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('\'', " ' ");
+    let mut tokens = spaced.split_whitespace().peekable();
+    let mut exprs = Vec::new();
+    while tokens.peek().is_some() {
+        exprs.push(parse_expr(&mut tokens)?);
+    }
+    Ok(exprs)
+}
+
+fn parse_expr<'a, I>(tokens: &mut Peekable<I>) -> Result<Value, String>
+where
+    I: Iterator<Item = &'a str>,
+{
+    match tokens.next() {
+        None => Err("unexpected end of input".to_string()),
+        Some(")") => Err("unexpected ')'".to_string()),
+        Some("'") => Ok(Value::List(vec![
+            Value::Symbol("quote".to_string()),
+            parse_expr(tokens)?,
+        ])),
+        Some("(") => {
+            let mut items = Vec::new();
+            loop {
+                match tokens.peek() {
+                    Some(&")") => {
+                        tokens.next();
+                        return Ok(Value::List(items));
+                    }
+                    Some(_) => items.push(parse_expr(tokens)?),
+                    None => return Err("expected ')'".to_string()),
+                }
+            }
+        }
+        Some("#t") => Ok(Value::Bool(true)),
+        Some("#f") => Ok(Value::Bool(false)),
+        Some(atom) => Ok(atom
+            .parse()
+            .map(Value::Number)
+            .unwrap_or_else(|_| Value::Symbol(atom.to_string()))),
+    }
+}
+
+fn eval(expr: &Value, env: &Rc<RefCell<Env>>) -> Result<Value, Error> {
+    match expr {
+        Value::Symbol(name) => env
+            .borrow()
+            .get(name)
+            .ok_or_else(|| user_error(format!("unbound symbol '{name}'"))),
+        Value::List(items) => match items.as_slice() {
+            [] => Ok(Value::List(Vec::new())),
+            [Value::Symbol(form), args @ ..] if is_special_form(form) => {
+                eval_special_form(form, args, env)
+            }
+            [head, args @ ..] => {
+                let f = eval(head, env)?;
+                let args = args
+                    .iter()
+                    .map(|arg| eval(arg, env))
+                    .collect::<Result<Vec<_>, _>>()?;
+                apply(&f, &args)
+            }
+        },
+        other => Ok(other.clone()),
+    }
+}
+
+fn is_special_form(name: &str) -> bool {
+    matches!(name, "quote" | "if" | "define" | "lambda" | "begin")
+}
+
+fn eval_special_form(form: &str, args: &[Value], env: &Rc<RefCell<Env>>) -> Result<Value, Error> {
+    match (form, args) {
+        ("quote", [value]) => Ok(value.clone()),
+        ("if", [cond, then, rest @ ..]) if rest.len() <= 1 => {
+            if !matches!(eval(cond, env)?, Value::Bool(false)) {
+                eval(then, env)
+            } else if let [otherwise] = rest {
+                eval(otherwise, env)
+            } else {
+                Ok(Value::List(Vec::new()))
+            }
+        }
+        ("define", [Value::Symbol(name), value]) => {
+            let value = eval(value, env)?;
+            env.borrow_mut().vars.insert(name.clone(), value);
+            Ok(Value::Symbol(name.clone()))
+        }
+        ("define", [Value::List(signature), body @ ..]) if !body.is_empty() => {
+            let [Value::Symbol(name), params @ ..] = signature.as_slice() else {
+                return Err(user_error("define expects a name"));
+            };
+            let lambda = make_lambda(params, body, env)?;
+            env.borrow_mut().vars.insert(name.clone(), lambda);
+            Ok(Value::Symbol(name.clone()))
+        }
+        ("lambda", [Value::List(params), body @ ..]) if !body.is_empty() => {
+            make_lambda(params, body, env)
+        }
+        ("begin", body) => eval_body(body, env),
+        _ => Err(user_error(format!("malformed {form}"))),
+    }
+}
+
+fn make_lambda(params: &[Value], body: &[Value], env: &Rc<RefCell<Env>>) -> Result<Value, Error> {
+    let params = params
+        .iter()
+        .map(|param| match param {
+            Value::Symbol(name) => Ok(name.clone()),
+            other => Err(user_error(format!("parameter {other} is not a symbol"))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Value::Lambda(Rc::new(Lambda {
+        params,
+        body: body.to_vec(),
+        env: Rc::clone(env),
+    })))
+}
+
+fn apply(f: &Value, args: &[Value]) -> Result<Value, Error> {
+    match f {
+        Value::Builtin(_, builtin) => builtin(args).map_err(user_error),
+        Value::Lambda(lambda) => {
+            if lambda.params.len() != args.len() {
+                return Err(user_error(format!(
+                    "expected {} arguments, got {}",
+                    lambda.params.len(),
+                    args.len()
+                )));
+            }
+            let vars = lambda.params.iter().cloned().zip(args.iter().cloned());
+            let env = Rc::new(RefCell::new(Env {
+                vars: vars.collect(),
+                parent: Some(Rc::clone(&lambda.env)),
+            }));
+            eval_body(&lambda.body, &env)
+        }
+        other => Err(user_error(format!("{other} is not a function"))),
+    }
+}
+
+/// Evaluate each expression in turn, returning the last value.
+fn eval_body(body: &[Value], env: &Rc<RefCell<Env>>) -> Result<Value, Error> {
+    let mut value = Value::List(Vec::new());
+    for expr in body {
+        value = eval(expr, env)?;
+    }
+    Ok(value)
+}
+
+fn user_error(message: impl Into<String>) -> Error {
+    Error::User(UserError::new(message))
+}
+
+/// Complete the symbol before the cursor with names bound in `env`.
+fn complete_symbols(env: Rc<RefCell<Env>>) -> CompleterFunc {
+    Box::new(move |ctx| {
+        let before = &ctx.line[..ctx.cursor];
+        let start = before
+            .rfind(|c: char| !ctx.word_chars.contains(c))
+            .map_or(0, |i| {
+                i + before[i..].chars().next().map_or(1, char::len_utf8)
+            });
+        let prefix = &before[start..];
+        if prefix.is_empty() {
+            return Completions::default();
+        }
+        let candidates = env
+            .borrow()
+            .names()
+            .into_iter()
+            .filter(|name| name.starts_with(prefix) && name != prefix)
+            .collect();
+        Completions {
+            start,
+            candidates,
+            docs: Vec::new(),
+        }
+    })
+}
+
+/// Highlight the paren at or just before the cursor along with its match, and unmatched
+/// closing parens in red.
+fn highlight_parens() -> HighlightFunc {
+    Box::new(|line, cursor| {
+        let mut open = Vec::new();
+        let mut pairs = HashMap::new();
+        let mut unmatched = Vec::new();
+        for (i, c) in line.char_indices() {
+            match c {
+                '(' => open.push(i),
+                ')' => match open.pop() {
+                    Some(start) => {
+                        pairs.insert(start, i);
+                        pairs.insert(i, start);
+                    }
+                    None => unmatched.push(i),
+                },
+                _ => {}
+            }
+        }
+        let at_cursor = [Some(cursor), cursor.checked_sub(1)]
+            .into_iter()
+            .flatten()
+            .find(|i| pairs.contains_key(i));
+        let matched = at_cursor.map(|i| (i, pairs[&i]));
+
+        let bold = Style::new().fg(Color::Cyan).attr(Attribute::Bold);
+        let red = Style::new().fg(Color::Red);
+        let mut styled = StyledText::new();
+        let mut plain = String::new();
+        for (i, c) in line.char_indices() {
+            let style = if matched.is_some_and(|(a, b)| i == a || i == b) {
+                bold
+            } else if unmatched.contains(&i) {
+                red
+            } else {
+                plain.push(c);
+                continue;
+            };
+            styled.push(std::mem::take(&mut plain), Style::new());
+            styled.push(c.to_string(), style);
+        }
+        styled.plain(plain)
+    })
+}
+
+/// Adds `:env`, listing the names defined in the session.
+struct EnvPlugin {
+    env: Rc<RefCell<Env>>,
+}
+
+impl ReplPlugin for EnvPlugin {
+    fn register(self, repl: &mut Repl) -> repl_lib::Result<()> {
+        let env = self.env;
+        repl.register_command(
+            "env",
+            "List definitions.",
+            Box::new(move |_| {
+                let env = env.borrow();
+                let mut definitions: Vec<String> = env
+                    .vars
+                    .iter()
+                    .filter(|(_, value)| !matches!(value, Value::Builtin(..)))
+                    .map(|(name, value)| format!("{name} = {value}"))
+                    .collect();
+                definitions.sort();
+                Ok(CommandAction::Output(definitions.join("\n")))
+            }),
+        );
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), ()> {
+    let env = Env::global();
+    let process_env = Rc::clone(&env);
+    let mut builder = Repl::builder()
+        .welcome_msg("Tiny Lisp. Try (define (square x) (* x x)), then (square 4).".to_string())
+        .prompt_func(Box::new(|ctx| {
+            let color = if ctx.last_failed {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            StyledText::new().span("lisp> ", Style::new().fg(color))
+        }))
+        .process_line(Box::new(move |line: String| {
+            let exprs = parse(&line).map_err(user_error)?;
+            eval_body(&exprs, &process_env).map(|value| value.to_string())
+        }))
+        .validator(Box::new(|line: &str| {
+            delimiters_balanced(line, &[('(', ')')])
+        }))
+        .indent_func(Box::new(|input: &str| {
+            let depth = input.chars().fold(0usize, |depth, c| match c {
+                '(' => depth + 1,
+                ')' => depth.saturating_sub(1),
+                _ => depth,
+            });
+            "  ".repeat(depth)
+        }))
+        .completer(completion::merge(vec![
+            complete_symbols(Rc::clone(&env)),
+            completion::history_words(),
+        ]))
+        .highlighter(highlight_parens())
+        .word_chars(WordChars::new().with("-+*/<>=!?"))
+        .abbreviation("def", "(define")
+        .abbreviation("fn", "(lambda")
+        .auto_pairs(&[('(', ')')])
+        .line_numbers(true)
+        .wrap_indicator(StyledText::new().span("↩", Style::new().attr(Attribute::Dim)))
+        .exit_condition(Box::new(|line: &str| line.trim() == "(exit)"));
+    if let Some(path) = paths::history_file("repl_lisp") {
+        builder = builder.history_file(path);
+    }
+    let mut repl = builder
+        .build()
+        .map_err(|e| eprintln!("unable to init REPL: {}", e))?;
+
+    repl.register_command(
+        "quit",
+        "Exit the REPL.",
+        Box::new(|_| Ok(CommandAction::Quit)),
+    );
+    repl.register_plugin(EnvPlugin {
+        env: Rc::clone(&env),
+    })
+    .map_err(|e| eprintln!("unable to register plugin: {}", e))?;
+    // Keep the window title in sync with the number of definitions.
+    let title_env = Rc::clone(&env);
+    repl.register_pre_input_hook(Box::new(move |repl| {
+        let count = title_env
+            .borrow()
+            .vars
+            .values()
+            .filter(|value| !matches!(value, Value::Builtin(..)))
+            .count();
+        repl.set_title(&format!("lisp ({count} defined)"))
+    }));
+
+    repl.run().map_err(|e| eprintln!("repl_lib error: {}", e))
+}
@@ -0,0 +1,244 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::collections::HashMap;
+
+use repl_lib::{Error, Result, UserError};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    /// A variable name and its byte range in the input.
+    Name(String, usize, usize),
+    Op(char),
+}
+
+/// Arithmetic evaluator with variables.
+///
+/// Supports `+ - * / % ^` with the usual precedence, parentheses, unary minus, and
+/// assignment with `name = expr`. The last result is stored in `ans`.
+pub struct Calculator {
+    vars: HashMap<String, f64>,
+}
+
+impl Calculator {
+    pub fn new() -> Self {
+        let vars = HashMap::from([
+            ("pi".to_string(), std::f64::consts::PI),
+            ("e".to_string(), std::f64::consts::E),
+        ]);
+        Self { vars }
+    }
+
+    /// Evaluate `input`, returning the result formatted for display.
+    pub fn eval(&mut self, input: &str) -> Result<String> {
+        let tokens = tokenize(input)?;
+        let (target, expr) = match tokens.as_slice() {
+            [Token::Name(name, _, _), Token::Op('='), rest @ ..] => (Some(name.clone()), rest),
+            _ => (None, tokens.as_slice()),
+        };
+        let mut parser = Parser {
+            input,
+            tokens: expr,
+            pos: 0,
+            vars: &self.vars,
+        };
+        let value = parser.expr()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(user_error(format!("unexpected {}", describe(token))));
+        }
+        if !value.is_finite() {
+            return Err(user_error("result is not a finite number"));
+        }
+
+        self.vars.insert("ans".to_string(), value);
+        if let Some(name) = target {
+            self.vars.insert(name, value);
+        }
+        Ok(format_number(value))
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: &'a [Token],
+    pos: usize,
+    vars: &'a HashMap<String, f64>,
+}
+
+impl Parser<'_> {
+    /// expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek_op() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    /// term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.unary()?;
+        while let Some(op @ ('*' | '/' | '%')) = self.peek_op() {
+            self.pos += 1;
+            let rhs = self.unary()?;
+            if op != '*' && rhs == 0.0 {
+                return Err(user_error("division by zero"));
+            }
+            value = match op {
+                '*' => value * rhs,
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    /// unary := '-' unary | power
+    fn unary(&mut self) -> Result<f64> {
+        if self.peek_op() == Some('-') {
+            self.pos += 1;
+            return Ok(-self.unary()?);
+        }
+        self.power()
+    }
+
+    /// power := atom ('^' unary)?, so `2^3^2` is `2^(3^2)` and `-2^2` is `-(2^2)`.
+    fn power(&mut self) -> Result<f64> {
+        let base = self.atom()?;
+        if self.peek_op() == Some('^') {
+            self.pos += 1;
+            return Ok(base.powf(self.unary()?));
+        }
+        Ok(base)
+    }
+
+    /// atom := number | name | '(' expr ')'
+    fn atom(&mut self) -> Result<f64> {
+        let Some(token) = self.tokens.get(self.pos) else {
+            return Err(user_error("unexpected end of input"));
+        };
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(*n),
+            Token::Name(name, start, end) => self.lookup(name, *start, *end),
+            Token::Op('(') => {
+                let value = self.expr()?;
+                if self.peek_op() != Some(')') {
+                    return Err(user_error("expected ')'"));
+                }
+                self.pos += 1;
+                Ok(value)
+            }
+            token => Err(user_error(format!("unexpected {}", describe(token)))),
+        }
+    }
+
+    /// Returns the value of a variable, or an error suggesting the input with similarly
+    /// named variables in its place.
+    fn lookup(&self, name: &str, start: usize, end: usize) -> Result<f64> {
+        if let Some(value) = self.vars.get(name) {
+            return Ok(*value);
+        }
+        let mut similar: Vec<(usize, &String)> = self
+            .vars
+            .keys()
+            .map(|known| (edit_distance(name, known), known))
+            .filter(|&(distance, _)| distance <= 2)
+            .collect();
+        similar.sort();
+        let suggestions = similar
+            .into_iter()
+            .map(|(_, known)| format!("{}{}{}", &self.input[..start], known, &self.input[end..]));
+        Err(Error::User(
+            UserError::new(format!("unknown variable '{name}'")).with_suggestions(suggestions),
+        ))
+    }
+
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(*op),
+            _ => None,
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek()
+                && (c.is_ascii_digit() || c == '.')
+            {
+                end = i + 1;
+                chars.next();
+            }
+            let text = &input[start..end];
+            let n = text
+                .parse()
+                .map_err(|_| user_error(format!("invalid number '{text}'")))?;
+            tokens.push(Token::Number(n));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek()
+                && (c.is_alphanumeric() || c == '_')
+            {
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Name(input[start..end].to_string(), start, end));
+        } else if "+-*/%^()=".contains(c) {
+            tokens.push(Token::Op(c));
+            chars.next();
+        } else {
+            return Err(user_error(format!("unexpected character '{c}'")));
+        }
+    }
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(n) => format!("number '{}'", format_number(*n)),
+        Token::Name(name, _, _) => format!("name '{name}'"),
+        Token::Op(op) => format!("'{op}'"),
+    }
+}
+
+/// Format whole numbers without a fractional part.
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        format!("{n}")
+    }
+}
+
+/// Returns the Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != *cb))
+                .min(above + 1)
+                .min(row[j] + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+fn user_error(message: impl Into<String>) -> Error {
+    Error::User(UserError::new(message))
+}
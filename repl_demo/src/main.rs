@@ -2,40 +2,135 @@
 // Author: Sebastian Ibanez
 // Created: 2025-08-29
 
-use repl_lib::{LineCompletionFunc, ProcessLineFunc, Repl};
+use std::{env, path::PathBuf};
 
-/// Return line.
+mod calc;
+
+use calc::Calculator;
+use repl_lib::{
+    CommandAction, CompletionContext, Completions, EditMode, IndentFunc, KeyEvent, Line,
+    ProcessLineFunc, Repl, ReplPlugin, ValidatorFunc, completion, delimiters_balanced,
+};
+
+/// Evaluate the line as an arithmetic expression.
 fn process_line() -> ProcessLineFunc {
-    Box::new(|line: String| Ok(line))
+    let mut calc = Calculator::new();
+    Box::new(move |line: String| calc.eval(&line))
+}
+
+/// Continue input with unclosed parens on a new line, and reject unmatched closing parens.
+fn validate() -> ValidatorFunc {
+    Box::new(|line: &str| delimiters_balanced(line, &[('(', ')')]))
 }
 
-/// Return `true` if line is larger than 0.
-fn line_is_finished() -> LineCompletionFunc {
-    Box::new(|line: String| {
-        let expression = line.trim();
-        let mut open_paren = 0;
-        let mut close_paren = 0;
+/// Indent continuation lines by two spaces per unclosed paren.
+fn indent() -> IndentFunc {
+    Box::new(|input: &str| {
+        let depth = input.chars().fold(0usize, |depth, c| match c {
+            '(' => depth + 1,
+            ')' => depth.saturating_sub(1),
+            _ => depth,
+        });
+        "  ".repeat(depth)
+    })
+}
 
-        for e in expression.chars() {
-            match e {
-                '(' => open_paren += 1,
-                ')' => close_paren += 1,
-                _ => {}
+/// Adds the `:history` meta-command with completion of its name, and Alt-( to wrap the input
+/// in parens.
+struct DemoPlugin;
+
+impl ReplPlugin for DemoPlugin {
+    fn register(self, repl: &mut Repl) -> repl_lib::Result<()> {
+        repl.register_command(
+            "history",
+            "List previously entered lines.",
+            Box::new(|ctx| {
+                let entries: Vec<String> = ctx
+                    .history
+                    .iter()
+                    .enumerate()
+                    .map(|(i, line)| format!("{:>4}  {}", i + 1, line))
+                    .collect();
+                Ok(CommandAction::Output(entries.join("\n")))
+            }),
+        );
+        repl.register_completer(Box::new(|ctx: &CompletionContext| {
+            let typed = &ctx.line[..ctx.cursor];
+            let candidates = if typed.starts_with(':') && ":history".starts_with(typed) {
+                vec![":history".to_string()]
+            } else {
+                Vec::new()
+            };
+            Completions {
+                start: 0,
+                candidates,
+                docs: Vec::new(),
             }
-        }
+        }));
+        repl.bind_key(
+            KeyEvent::alt('('),
+            Box::new(|line: &mut Line| {
+                let wrapped = format!("({})", line.text());
+                *line = Line::with_cursor(wrapped, line.text().len() + 2);
+                Ok(())
+            }),
+        );
+        Ok(())
+    }
+}
 
-        let _ = expression.chars().map(|e| match e {
-            '(' => open_paren += 1,
-            ')' => close_paren += 1,
-            _ => {}
-        });
+/// Command-line options.
+struct Args {
+    banner: bool,
+    history_file: Option<PathBuf>,
+    prompt: String,
+    screen_reader: bool,
+    edit_mode: EditMode,
+}
 
-        (open_paren == close_paren) || (!expression.starts_with('(') && !expression.ends_with(')'))
-    })
+const USAGE: &str =
+    "usage: repl_demo [--no-banner] [--history-file PATH] [--vi] [--prompt STR] [--screen-reader]";
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            banner: true,
+            history_file: None,
+            prompt: String::from("> "),
+            screen_reader: false,
+            edit_mode: EditMode::Emacs,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--no-banner" => parsed.banner = false,
+                "--history-file" => {
+                    let path = args.next().ok_or("--history-file expects a path")?;
+                    parsed.history_file = Some(PathBuf::from(path));
+                }
+                "--prompt" => parsed.prompt = args.next().ok_or("--prompt expects a string")?,
+                "--screen-reader" => parsed.screen_reader = true,
+                "--vi" => parsed.edit_mode = EditMode::Vi,
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                other => return Err(format!("unknown option '{}'\n{}", other, USAGE)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Print `e` after `what`, followed by each error that caused it.
+fn report(what: &str, e: &dyn std::error::Error) {
+    let mut message = format!("{}: {}", what, e);
+    let mut source = e.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    eprintln!("{}", message);
 }
 
 fn main() -> Result<(), ()> {
-    let prompt = String::from("> ");
+    let args = Args::parse(env::args().skip(1)).map_err(|e| eprintln!("{}", e))?;
     let banner = String::from(
         r#"
     ____  __________  __       ____  ________  _______ 
@@ -45,35 +140,54 @@ fn main() -> Result<(), ()> {
 /_/ |_/_____/_/   /_____/  /_____/_____/_/  /_/\____/  
     "#,
     );
-    let welcome_msg = String::from("Welcome to the REPL demo!");
-    let mut repl = match Repl::new(
-        prompt,
-        banner,
-        welcome_msg,
-        process_line(),
-        line_is_finished(),
-    ) {
+    let welcome_msg =
+        String::from("Welcome to the REPL demo! Try an expression like `x = (1 + 2) * 3`.");
+    let mut builder = Repl::builder()
+        .prompt(args.prompt)
+        .welcome_msg(welcome_msg)
+        .process_line(process_line())
+        .validator(validate())
+        .indent_func(indent())
+        .completer(completion::merge(vec![
+            completion::history_words(),
+            completion::filenames(),
+        ]))
+        .exit_condition(Box::new(|line: &str| line.trim() == "exit"))
+        .history_expansion(true)
+        .echo_history_expansion(true)
+        .history_ignore_space(true)
+        .screen_reader(args.screen_reader)
+        .edit_mode(args.edit_mode);
+    if args.banner {
+        builder = builder.banner(banner);
+    }
+    if let Some(path) = args.history_file {
+        builder = builder.history_file(path);
+    }
+    let mut repl = match builder.build() {
         Ok(r) => r,
         Err(e) => {
-            eprintln!("unable to init REPL: {}", e);
+            report("unable to init REPL", &e);
             return Err(());
         }
     };
 
-    repl.print_welcome();
-
-    loop {
-        repl.print_prompt();
-        repl.process_input()
-            .inspect(|l| println!("{}", l))
-            .map_err(|e| match e {
-                // repl_lib errors are either:
-                // Internal - This means that the error was created by repl_lib code.
-                // User - This means that the error was created by your process line function.
-                //
-                // This gives you the flexibility to handle user and library errors differently.
-                repl_lib::Error::Internal(e) => eprintln!("repl_lib error: {}", e),
-                repl_lib::Error::User(e) => eprintln!("error: {}", e.error),
-            })?;
+    repl.register_command(
+        "quit",
+        "Exit the REPL.",
+        Box::new(|_| Ok(CommandAction::Quit)),
+    );
+    if let Err(e) = repl.register_plugin(DemoPlugin) {
+        report("unable to register plugin", &e);
+        return Err(());
     }
+
+    // repl_lib errors are either:
+    // Internal - This means that the error was created by repl_lib code.
+    // User - This means that the error was created by your process line function.
+    //
+    // `run` prints user errors and keeps going, internal errors end the loop.
+    repl.run()
+        .and_then(|_| repl.close())
+        .map_err(|e| report("repl_lib error", &e))
 }
@@ -0,0 +1,78 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::process::Command;
+
+use repl_expect::{KeyCode, KeyEvent, Session};
+
+/// Start the demo without its banner, in a clean environment so settings from `REPL_*`
+/// variables don't leak in, and wait for the prompt.
+fn start() -> Session {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_repl_demo"));
+    command.env_clear().arg("--no-banner");
+    let mut session = Session::spawn(command).unwrap();
+    session.expect("> ").unwrap();
+    session
+}
+
+/// Returns the row the cursor is on.
+fn cursor_row(session: &Session) -> String {
+    session.screen().row(session.screen().cursor().0)
+}
+
+#[test]
+fn evaluates_lines() {
+    let mut session = start();
+    session.send_line("x = (1 + 2) * 3").unwrap();
+    session.expect("9").unwrap();
+    session.send_line("x + 1").unwrap();
+    session.expect("10").unwrap();
+    session.send_line("exit").unwrap();
+    assert!(session.wait().unwrap().success());
+}
+
+#[test]
+fn redraws_edits_in_the_middle_of_the_line() {
+    let mut session = start();
+    session.send("1 + 3").unwrap();
+    session.send_key(KeyEvent::new(KeyCode::Left)).unwrap();
+    session.send("2 * ").unwrap();
+    session
+        .expect_row(session.screen().cursor().0, "> 1 + 2 * 3")
+        .unwrap();
+    session.send_key(KeyEvent::ctrl('a')).unwrap();
+    session.send_key(KeyEvent::ctrl('k')).unwrap();
+    session.send("4").unwrap();
+    session.settle().unwrap();
+    assert_eq!(cursor_row(&session), "> 4");
+    session.send("\r").unwrap();
+    session.expect("\n4").unwrap();
+}
+
+#[test]
+fn continues_unbalanced_input_on_indented_lines() {
+    let mut session = start();
+    session.send_line("(1 +").unwrap();
+    session.send("(2 *").unwrap();
+    session.expect("> (1 +\n    (2 *").unwrap();
+    session.send("\r3))").unwrap();
+    session.expect("> (1 +\n    (2 *\n      3))").unwrap();
+    session.send("\r").unwrap();
+    session.expect("\n7\n").unwrap();
+    session.settle().unwrap();
+    assert_eq!(cursor_row(&session), ">");
+}
+
+#[test]
+fn ctrl_c_discards_the_line_and_keeps_running() {
+    let mut session = start();
+    session.send("1 + 2").unwrap();
+    session.expect("> 1 + 2").unwrap();
+    session.send_key(KeyEvent::ctrl('c')).unwrap();
+    session.settle().unwrap();
+    assert_eq!(cursor_row(&session), ">");
+    session.send_line("2 * 3").unwrap();
+    session.expect("6").unwrap();
+    assert!(!session.screen().contains("\n3\n"));
+}
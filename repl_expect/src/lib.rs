@@ -0,0 +1,272 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! Expect-style testing of REPLs through a pseudo-terminal.
+//!
+//! A `Session` runs a program, such as a REPL built with `repl_lib`, on a pseudo-terminal,
+//! sends it keys, and waits for text to appear on a `Screen` kept up to date from its
+//! output. Tests can then check what a user would see, after redraws and cursor movement
+//! rather than in the raw bytes written.
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, Read, Write},
+    os::unix::process::CommandExt,
+    process::{Child, Command, ExitStatus, Stdio},
+    time::{Duration, Instant},
+};
+
+mod pty;
+mod screen;
+
+pub use screen::Screen;
+pub use term_manager::input::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Default size of the terminal.
+pub const DEFAULT_SIZE: (u16, u16) = (24, 80);
+/// Default time to wait for text to appear.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time without output after which `Session::settle` returns.
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+/// Most bytes of output read at once.
+const READ_CHUNK: usize = 4096;
+
+/// Error type for sessions.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Io(io::Error),
+    /// What was waited for didn't appear in time. Holds the screen contents at the time.
+    Timeout {
+        expected: String,
+        screen: String,
+    },
+    /// The program exited before what was waited for appeared. Holds the screen contents.
+    Exited {
+        expected: String,
+        screen: String,
+    },
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Timeout { expected, screen } => {
+                write!(
+                    f,
+                    "timed out waiting for {:?}, screen:\n{}",
+                    expected, screen
+                )
+            }
+            Error::Exited { expected, screen } => write!(
+                f,
+                "program exited while waiting for {:?}, screen:\n{}",
+                expected, screen
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A program running on a pseudo-terminal.
+///
+/// The program is killed when the session is dropped.
+pub struct Session {
+    child: Child,
+    master: File,
+    screen: Screen,
+    timeout: Duration,
+    /// The program closed the terminal, so no more output will come.
+    closed: bool,
+}
+
+impl Session {
+    /// Run `command` on a terminal of `DEFAULT_SIZE`.
+    pub fn spawn(command: Command) -> Result<Self> {
+        Self::spawn_with_size(command, DEFAULT_SIZE.0, DEFAULT_SIZE.1)
+    }
+
+    /// Run `command` on a terminal of `rows` by `cols` cells.
+    ///
+    /// Its standard input, output and error are the terminal, which is its controlling
+    /// terminal. `TERM` is set to `xterm-256color` unless `command` sets it.
+    pub fn spawn_with_size(mut command: Command, rows: u16, cols: u16) -> Result<Self> {
+        let (master, slave) = pty::open(rows, cols)?;
+        if !command.get_envs().any(|(key, _)| key == "TERM") {
+            command.env("TERM", "xterm-256color");
+        }
+        command
+            .stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave));
+        unsafe {
+            command.pre_exec(pty::make_controlling);
+        }
+        let child = command.spawn()?;
+        Ok(Self {
+            child,
+            master,
+            screen: Screen::new(rows, cols),
+            timeout: DEFAULT_TIMEOUT,
+            closed: false,
+        })
+    }
+
+    /// Set how long `expect` and `wait_until` wait, 5 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the screen as of the output read so far.
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    /// Returns the id of the program's process, e.g. to send it signals.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Send raw bytes to the program, as if typed.
+    pub fn send(&mut self, bytes: impl AsRef<[u8]>) -> Result<()> {
+        self.master.write_all(bytes.as_ref())?;
+        self.master.flush()?;
+        Ok(())
+    }
+
+    /// Send the bytes a terminal sends for `key`.
+    pub fn send_key(&mut self, key: KeyEvent) -> Result<()> {
+        self.send(term_manager::input::encode(key))
+    }
+
+    /// Send each of `keys` in turn.
+    pub fn send_keys(&mut self, keys: &[KeyEvent]) -> Result<()> {
+        keys.iter().try_for_each(|&key| self.send_key(key))
+    }
+
+    /// Type `line` followed by Enter.
+    pub fn send_line(&mut self, line: &str) -> Result<()> {
+        self.send(format!("{}\r", line))
+    }
+
+    /// Bracket `text` as a paste, as terminals do when bracketed paste is on.
+    pub fn paste(&mut self, text: &str) -> Result<()> {
+        self.send(format!("\x1b[200~{}\x1b[201~", text))
+    }
+
+    /// Wait until `text` appears on the screen. Text running over several rows must contain
+    /// the line breaks between them.
+    pub fn expect(&mut self, text: &str) -> Result<()> {
+        self.wait_until(text, |screen| screen.contains(text))
+    }
+
+    /// Wait until row `row` of the screen reads exactly `text`, ignoring trailing spaces.
+    pub fn expect_row(&mut self, row: u16, text: &str) -> Result<()> {
+        let expected = format!("row {} to be {:?}", row, text);
+        self.wait_until(&expected, |screen| screen.row(row) == text.trim_end())
+    }
+
+    /// Wait until `condition` holds for the screen, reading output as it comes. `expected`
+    /// describes the condition in the error if it doesn't hold in time.
+    pub fn wait_until(
+        &mut self,
+        expected: &str,
+        mut condition: impl FnMut(&Screen) -> bool,
+    ) -> Result<()> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if condition(&self.screen) {
+                return Ok(());
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if self.closed || remaining.is_zero() {
+                let (expected, screen) = (expected.to_string(), self.screen.contents());
+                return Err(if self.closed {
+                    Error::Exited { expected, screen }
+                } else {
+                    Error::Timeout { expected, screen }
+                });
+            }
+            self.read_output(remaining)?;
+        }
+    }
+
+    /// Read output until there's none for a moment, e.g. before checking that something
+    /// didn't appear. Returns the screen.
+    pub fn settle(&mut self) -> Result<&Screen> {
+        while !self.closed && self.read_output(SETTLE_TIME)? {}
+        Ok(&self.screen)
+    }
+
+    /// Change the size of the terminal, which sends the program `SIGWINCH`.
+    pub fn resize(&mut self, rows: u16, cols: u16) -> Result<()> {
+        pty::resize(&self.master, rows, cols)?;
+        self.screen.resize(rows, cols);
+        Ok(())
+    }
+
+    /// Read output until the program closes the terminal, then wait for it to exit.
+    pub fn wait(&mut self) -> Result<ExitStatus> {
+        let deadline = Instant::now() + self.timeout;
+        while !self.closed {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(Error::Timeout {
+                    expected: "exit".to_string(),
+                    screen: self.screen.contents(),
+                });
+            }
+            self.read_output(remaining)?;
+        }
+        Ok(self.child.wait()?)
+    }
+
+    /// Wait up to `timeout` for output and apply it to the screen, answering any requests
+    /// in it. Returns whether there was any.
+    fn read_output(&mut self, timeout: Duration) -> Result<bool> {
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+        if !pty::wait_readable(&self.master, timeout_ms)? {
+            return Ok(false);
+        }
+        let mut buf = [0u8; READ_CHUNK];
+        let n = match self.master.read(&mut buf) {
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return Ok(false),
+            // Linux reports the other side closing as EIO.
+            Err(e) if e.raw_os_error() == Some(libc::EIO) => 0,
+            Err(e) => return Err(e.into()),
+        };
+        if n == 0 {
+            self.closed = true;
+            return Ok(false);
+        }
+        self.screen.process(&buf[..n]);
+        let replies = self.screen.take_replies();
+        if !replies.is_empty() {
+            self.send(replies)?;
+        }
+        Ok(true)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    fs::File,
+    io,
+    os::fd::{AsRawFd, FromRawFd},
+    ptr,
+};
+
+/// Open a pseudo-terminal of `rows` by `cols` cells, returning its controlling (master) and
+/// terminal (slave) sides.
+pub(crate) fn open(rows: u16, cols: u16) -> io::Result<(File, File)> {
+    let (mut master, mut slave) = (-1, -1);
+    let size = window_size(rows, cols);
+    let res = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &size as *const libc::winsize as *mut libc::winsize,
+        )
+    };
+    if res != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+    // Keep the controlling side out of the program, so it sees the terminal close.
+    if unsafe { libc::fcntl(master.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok((master, slave))
+}
+
+/// Set the size of the pseudo-terminal controlled by `master`, which sends `SIGWINCH` to its
+/// program.
+pub(crate) fn resize(master: &File, rows: u16, cols: u16) -> io::Result<()> {
+    let size = window_size(rows, cols);
+    if unsafe { libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &size) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Wait up to `timeout_ms` for `file` to be readable, returning whether it is.
+pub(crate) fn wait_readable(file: &File, timeout_ms: i32) -> io::Result<bool> {
+    let mut pollfd = libc::pollfd {
+        fd: file.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    match unsafe { libc::poll(&mut pollfd, 1, timeout_ms) } {
+        -1 => {
+            let e = io::Error::last_os_error();
+            if e.kind() == io::ErrorKind::Interrupted {
+                Ok(false)
+            } else {
+                Err(e)
+            }
+        }
+        n => Ok(n > 0),
+    }
+}
+
+/// Make the terminal on standard input the controlling terminal of a new session, in the
+/// child between `fork` and `exec`.
+pub(crate) fn make_controlling() -> io::Result<()> {
+    if unsafe { libc::setsid() } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    if unsafe { libc::ioctl(0, libc::TIOCSCTTY as _, 0) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn window_size(rows: u16, cols: u16) -> libc::winsize {
+    libc::winsize {
+        ws_row: rows,
+        ws_col: cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
@@ -0,0 +1,578 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::mem;
+
+use repl_lib::char_width;
+
+/// Marks the cell covered by the right half of a wide character.
+const WIDE_TAIL: char = '\0';
+/// Columns between tab stops.
+const TAB_WIDTH: usize = 8;
+
+/// Characters of each row, one per cell.
+type Cells = Vec<Vec<char>>;
+
+/// Text on a terminal screen, kept up to date from a program's output.
+///
+/// It understands the cursor movement, erasing, scrolling and alternate screen sequences a
+/// line editor uses, and answers cursor position requests. Colors and other styles are
+/// dropped, so only the text can be checked.
+#[derive(Clone, Debug)]
+pub struct Screen {
+    rows: usize,
+    cols: usize,
+    cells: Cells,
+    cursor: (usize, usize),
+    saved_cursor: (usize, usize),
+    /// The cursor is past the last column, and the next character goes on a new line.
+    wrap_pending: bool,
+    scroll_top: usize,
+    scroll_bottom: usize,
+    /// Cells and cursor of the main screen while the alternate screen is shown.
+    main_screen: Option<(Cells, (usize, usize))>,
+    /// Bytes of an incomplete character or escape sequence.
+    pending: Vec<u8>,
+    /// Replies to requests in the output, to be written back to the program.
+    replies: Vec<u8>,
+}
+
+impl Screen {
+    /// Create a blank screen of `rows` by `cols` cells.
+    pub fn new(rows: u16, cols: u16) -> Self {
+        let (rows, cols) = (rows.max(1) as usize, cols.max(1) as usize);
+        Self {
+            rows,
+            cols,
+            cells: vec![vec![' '; cols]; rows],
+            cursor: (0, 0),
+            saved_cursor: (0, 0),
+            wrap_pending: false,
+            scroll_top: 0,
+            scroll_bottom: rows - 1,
+            main_screen: None,
+            pending: Vec::new(),
+            replies: Vec::new(),
+        }
+    }
+
+    /// Returns the number of rows and columns.
+    pub fn size(&self) -> (u16, u16) {
+        (self.rows as u16, self.cols as u16)
+    }
+
+    /// Returns the zero-based row and column of the cursor.
+    pub fn cursor(&self) -> (u16, u16) {
+        (self.cursor.0 as u16, self.cursor.1 as u16)
+    }
+
+    /// Returns the text of row `row` without trailing spaces, or an empty string past the
+    /// last row.
+    pub fn row(&self, row: u16) -> String {
+        self.cells
+            .get(row as usize)
+            .map_or_else(String::new, |cells| {
+                let text: String = cells.iter().filter(|&&c| c != WIDE_TAIL).collect();
+                text.trim_end().to_string()
+            })
+    }
+
+    /// Returns the text of every row, one per line, without trailing spaces or blank rows.
+    pub fn contents(&self) -> String {
+        let rows: Vec<String> = (0..self.rows as u16).map(|row| self.row(row)).collect();
+        rows.join("\n").trim_end().to_string()
+    }
+
+    /// Returns whether `text` appears on the screen. Text running over several rows must
+    /// contain the line breaks between them. Spaces at the end of rows are ignored, as
+    /// they can't be told apart from blank cells.
+    pub fn contains(&self, text: &str) -> bool {
+        let text: Vec<&str> = text.split('\n').map(str::trim_end).collect();
+        self.contents().contains(&text.join("\n"))
+    }
+
+    /// Returns whether the alternate screen, used by full-screen views like pagers, is
+    /// shown.
+    pub fn is_alternate(&self) -> bool {
+        self.main_screen.is_some()
+    }
+
+    /// Change the size, keeping the text in the top left corner.
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        let (rows, cols) = (rows.max(1) as usize, cols.max(1) as usize);
+        for line in &mut self.cells {
+            line.resize(cols, ' ');
+        }
+        self.cells.resize(rows, vec![' '; cols]);
+        (self.rows, self.cols) = (rows, cols);
+        self.cursor = (self.cursor.0.min(rows - 1), self.cursor.1.min(cols - 1));
+        self.wrap_pending = false;
+        (self.scroll_top, self.scroll_bottom) = (0, rows - 1);
+    }
+
+    /// Apply a chunk of output. A character or sequence cut short at the end is kept until
+    /// the rest arrives.
+    pub fn process(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        let input = mem::take(&mut self.pending);
+        let mut pos = 0;
+        while pos < input.len() {
+            match self.step(&input[pos..]) {
+                Some(len) => pos += len,
+                None => break,
+            }
+        }
+        self.pending = input[pos..].to_vec();
+    }
+
+    /// Returns the replies to requests in the output since the last call.
+    pub(crate) fn take_replies(&mut self) -> Vec<u8> {
+        mem::take(&mut self.replies)
+    }
+
+    /// Apply the character or sequence at the start of `input`, returning its length, or
+    /// `None` if it's incomplete.
+    fn step(&mut self, input: &[u8]) -> Option<usize> {
+        match input[0] {
+            0x1B => return self.escape(input),
+            b'\r' => self.move_to(self.cursor.0, 0),
+            b'\n' | 0x0B | 0x0C => self.line_feed(),
+            0x08 => self.move_to(self.cursor.0, self.cursor.1.saturating_sub(1)),
+            b'\t' => {
+                let stop = (self.cursor.1 / TAB_WIDTH + 1) * TAB_WIDTH;
+                self.move_to(self.cursor.0, stop.min(self.cols - 1));
+            }
+            0x00..=0x1F | 0x7F => {}
+            first => {
+                let len = match first {
+                    0xC0..=0xDF => 2,
+                    0xE0..=0xEF => 3,
+                    0xF0..=0xF7 => 4,
+                    _ => 1,
+                };
+                let bytes = input.get(..len)?;
+                let c = std::str::from_utf8(bytes)
+                    .ok()
+                    .and_then(|s| s.chars().next())
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                self.put(c);
+                return Some(len);
+            }
+        }
+        Some(1)
+    }
+
+    /// Apply the escape sequence at the start of `input`.
+    fn escape(&mut self, input: &[u8]) -> Option<usize> {
+        match *input.get(1)? {
+            b'[' => {
+                let end = input[2..].iter().position(|b| (0x40..=0x7E).contains(b))? + 2;
+                self.csi(&input[2..end], input[end]);
+                Some(end + 1)
+            }
+            // OSC, DCS and APC strings end with BEL or ST (`ESC \`).
+            b']' | b'P' | b'_' => {
+                let end = input[2..]
+                    .windows(2)
+                    .position(|w| w[0] == 0x07 || w == b"\x1b\\")?
+                    + 2;
+                Some(if input[end] == 0x07 { end + 1 } else { end + 2 })
+            }
+            b'(' | b')' | b'#' | b' ' => input.get(2).map(|_| 3),
+            b'7' => {
+                self.saved_cursor = self.cursor;
+                Some(2)
+            }
+            b'8' => {
+                self.move_to(self.saved_cursor.0, self.saved_cursor.1);
+                Some(2)
+            }
+            b'D' => {
+                self.line_feed();
+                Some(2)
+            }
+            b'E' => {
+                self.line_feed();
+                self.move_to(self.cursor.0, 0);
+                Some(2)
+            }
+            b'M' => {
+                if self.cursor.0 == self.scroll_top {
+                    self.scroll_down(1);
+                } else {
+                    self.move_to(self.cursor.0.saturating_sub(1), self.cursor.1);
+                }
+                Some(2)
+            }
+            b'c' => {
+                let (rows, cols) = self.size();
+                *self = Screen::new(rows, cols);
+                Some(2)
+            }
+            _ => Some(2),
+        }
+    }
+
+    /// Apply a CSI sequence with parameter bytes `params` and final byte `action`.
+    fn csi(&mut self, params: &[u8], action: u8) {
+        let private = params.first().is_some_and(|b| b"?<=>".contains(b));
+        // Sequences with intermediate bytes, like the cursor shape, don't change the text.
+        if params.iter().any(|b| (0x20..=0x2F).contains(b)) {
+            return;
+        }
+        let digits = if private { &params[1..] } else { params };
+        let args: Vec<usize> = String::from_utf8_lossy(digits)
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let arg = |i: usize| args.get(i).copied().unwrap_or(0);
+        let count = arg(0).max(1);
+        let (row, col) = self.cursor;
+
+        if private {
+            if matches!(action, b'h' | b'l') && args.iter().any(|&a| matches!(a, 47 | 1047 | 1049))
+            {
+                self.alternate(action == b'h');
+            }
+            return;
+        }
+        match action {
+            b'A' => self.move_to(row.saturating_sub(count), col),
+            b'B' => self.move_to(row + count, col),
+            b'C' => self.move_to(row, col + count),
+            b'D' => self.move_to(row, col.saturating_sub(count)),
+            b'E' => self.move_to(row + count, 0),
+            b'F' => self.move_to(row.saturating_sub(count), 0),
+            b'G' | b'`' => self.move_to(row, count - 1),
+            b'd' => self.move_to(count - 1, col),
+            b'H' | b'f' => self.move_to(arg(0).max(1) - 1, arg(1).max(1) - 1),
+            b'J' => match arg(0) {
+                0 => {
+                    self.erase(row, col, self.cols);
+                    for r in row + 1..self.rows {
+                        self.erase(r, 0, self.cols);
+                    }
+                }
+                1 => {
+                    for r in 0..row {
+                        self.erase(r, 0, self.cols);
+                    }
+                    self.erase(row, 0, col + 1);
+                }
+                _ => {
+                    for r in 0..self.rows {
+                        self.erase(r, 0, self.cols);
+                    }
+                }
+            },
+            b'K' => match arg(0) {
+                0 => self.erase(row, col, self.cols),
+                1 => self.erase(row, 0, col + 1),
+                _ => self.erase(row, 0, self.cols),
+            },
+            b'X' => self.erase(row, col, col + count),
+            b'P' => {
+                let line = &mut self.cells[row];
+                let count = count.min(self.cols - col);
+                line.drain(col..col + count);
+                line.resize(self.cols, ' ');
+            }
+            b'@' => {
+                let line = &mut self.cells[row];
+                for _ in 0..count.min(self.cols - col) {
+                    line.insert(col, ' ');
+                }
+                line.truncate(self.cols);
+            }
+            b'L' | b'M' if (self.scroll_top..=self.scroll_bottom).contains(&row) => {
+                let top = self.scroll_top;
+                self.scroll_top = row;
+                if action == b'L' {
+                    self.scroll_down(count);
+                } else {
+                    self.scroll_up(count);
+                }
+                self.scroll_top = top;
+            }
+            b'S' => self.scroll_up(count),
+            b'T' => self.scroll_down(count),
+            b'r' => {
+                let bottom = if arg(1) == 0 { self.rows } else { arg(1) };
+                let top = arg(0).max(1) - 1;
+                if top < bottom.min(self.rows) {
+                    (self.scroll_top, self.scroll_bottom) = (top, bottom.min(self.rows) - 1);
+                }
+                self.move_to(0, 0);
+            }
+            b's' => self.saved_cursor = self.cursor,
+            b'u' => self.move_to(self.saved_cursor.0, self.saved_cursor.1),
+            b'n' if arg(0) == 6 => {
+                let report = format!("\x1b[{};{}R", row + 1, col + 1);
+                self.replies.extend_from_slice(report.as_bytes());
+            }
+            b'n' if arg(0) == 5 => self.replies.extend_from_slice(b"\x1b[0n"),
+            _ => {}
+        }
+    }
+
+    /// Write `c` at the cursor and move past it, wrapping at the last column.
+    fn put(&mut self, c: char) {
+        let width = char_width(c);
+        if width == 0 {
+            return;
+        }
+        if self.wrap_pending || self.cursor.1 + width > self.cols {
+            self.line_feed();
+            self.cursor.1 = 0;
+        }
+        self.wrap_pending = false;
+        let (row, col) = self.cursor;
+        self.erase(row, col, col + width);
+        self.cells[row][col] = c;
+        if width == 2 && col + 1 < self.cols {
+            self.cells[row][col + 1] = WIDE_TAIL;
+        }
+        if col + width >= self.cols {
+            self.cursor.1 = self.cols - 1;
+            self.wrap_pending = true;
+        } else {
+            self.cursor.1 = col + width;
+        }
+    }
+
+    /// Blank the cells of `row` from `start` up to `end`, along with the other half of any
+    /// wide character cut in two.
+    fn erase(&mut self, row: usize, start: usize, end: usize) {
+        let line = &mut self.cells[row];
+        let end = end.min(self.cols);
+        if start >= end {
+            return;
+        }
+        if line[start] == WIDE_TAIL && start > 0 {
+            line[start - 1] = ' ';
+        }
+        if line.get(end) == Some(&WIDE_TAIL) {
+            line[end] = ' ';
+        }
+        line[start..end].fill(' ');
+    }
+
+    /// Move the cursor to `row` and `col`, kept on the screen.
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.cursor = (row.min(self.rows - 1), col.min(self.cols - 1));
+        self.wrap_pending = false;
+    }
+
+    /// Move the cursor down a row, scrolling at the bottom of the scroll region.
+    fn line_feed(&mut self) {
+        self.wrap_pending = false;
+        if self.cursor.0 == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.cursor.0 + 1 < self.rows {
+            self.cursor.0 += 1;
+        }
+    }
+
+    /// Scroll the scroll region up `count` rows, adding blank rows at its bottom.
+    fn scroll_up(&mut self, count: usize) {
+        for _ in 0..count.min(self.scroll_bottom + 1 - self.scroll_top) {
+            self.cells.remove(self.scroll_top);
+            self.cells.insert(self.scroll_bottom, vec![' '; self.cols]);
+        }
+    }
+
+    /// Scroll the scroll region down `count` rows, adding blank rows at its top.
+    fn scroll_down(&mut self, count: usize) {
+        for _ in 0..count.min(self.scroll_bottom + 1 - self.scroll_top) {
+            self.cells.remove(self.scroll_bottom);
+            self.cells.insert(self.scroll_top, vec![' '; self.cols]);
+        }
+    }
+
+    /// Show the alternate screen, blank, or go back to the main screen as it was.
+    fn alternate(&mut self, enter: bool) {
+        if enter && self.main_screen.is_none() {
+            let blank = vec![vec![' '; self.cols]; self.rows];
+            let cells = mem::replace(&mut self.cells, blank);
+            self.main_screen = Some((cells, self.cursor));
+        } else if !enter && let Some((cells, cursor)) = self.main_screen.take() {
+            self.cells = cells;
+            self.cells.resize(self.rows, vec![' '; self.cols]);
+            for line in &mut self.cells {
+                line.resize(self.cols, ' ');
+            }
+            self.move_to(cursor.0, cursor.1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 5 by 10 screen after `output`.
+    fn after(output: &str) -> Screen {
+        let mut screen = Screen::new(5, 10);
+        screen.process(output.as_bytes());
+        screen
+    }
+
+    #[test]
+    fn text_and_line_breaks() {
+        let screen = after("ab\r\ncd\nx");
+        assert_eq!(screen.row(0), "ab");
+        assert_eq!(screen.row(1), "cd");
+        assert_eq!(screen.row(2), "  x");
+        assert_eq!(screen.cursor(), (2, 3));
+        assert_eq!(screen.contents(), "ab\ncd\n  x");
+        assert!(screen.contains("cd\n  x"));
+    }
+
+    #[test]
+    fn cursor_movement() {
+        let mut screen = after("\x1b[3;4Hx");
+        assert_eq!(screen.row(2), "   x");
+        screen.process(b"\x1b[2A\x1b[3Cy\x1b[B\x1b[2Dz");
+        assert_eq!(screen.row(0), "       y");
+        assert_eq!(screen.row(1), "      z");
+        screen.process(b"\x1b[1Gq\x1b[5;20H");
+        assert_eq!(screen.row(1), "q     z");
+        // Moves stop at the edges.
+        assert_eq!(screen.cursor(), (4, 9));
+        screen.process(b"\x1b[99A\x1b[99D");
+        assert_eq!(screen.cursor(), (0, 0));
+        screen.process(b"ab\x08\x08\tc");
+        assert_eq!(screen.row(0), "ab     yc");
+    }
+
+    #[test]
+    fn save_and_restore_cursor() {
+        let mut screen = after("ab\x1b7\x1b[4;1Hcd\x1b8e");
+        assert_eq!(screen.row(0), "abe");
+        screen.process(b"\x1b[s\x1b[5;5H\x1b[uf");
+        assert_eq!(screen.row(0), "abef");
+    }
+
+    #[test]
+    fn erase_in_line_and_display() {
+        let mut screen = after("abcdefgh\r\x1b[3C\x1b[K");
+        assert_eq!(screen.row(0), "abc");
+        screen.process(b"\x1b[1K");
+        assert_eq!(screen.row(0), "");
+        screen.process(b"\rabcdef\x1b[3G\x1b[2X");
+        assert_eq!(screen.row(0), "ab  ef");
+
+        let mut screen = after("one\r\ntwo\r\nthree\x1b[2;2H\x1b[J");
+        assert_eq!(screen.contents(), "one\nt");
+        screen.process(b"\x1b[1J");
+        assert_eq!(screen.contents(), "");
+        screen.process(b"\x1b[Hx\x1b[2Jy");
+        assert_eq!(screen.contents(), " y");
+    }
+
+    #[test]
+    fn insert_and_delete_characters() {
+        let mut screen = after("abcdef\x1b[3G\x1b[2P");
+        assert_eq!(screen.row(0), "abef");
+        screen.process(b"\x1b[2@XY");
+        assert_eq!(screen.row(0), "abXYef");
+    }
+
+    #[test]
+    fn wraps_after_the_last_column() {
+        let mut screen = after("0123456789");
+        // The cursor stays on the last column until the next character.
+        assert_eq!(screen.cursor(), (0, 9));
+        assert_eq!(screen.row(1), "");
+        screen.process(b"ab");
+        assert_eq!(screen.row(1), "ab");
+        assert_eq!(screen.cursor(), (1, 2));
+
+        // A carriage return cancels the pending wrap.
+        let screen = after("0123456789\rx");
+        assert_eq!(screen.row(0), "x123456789");
+        assert_eq!(screen.row(1), "");
+    }
+
+    #[test]
+    fn wide_characters() {
+        let mut screen = after("a\u{4e2d}b");
+        assert_eq!(screen.row(0), "a\u{4e2d}b");
+        assert_eq!(screen.cursor(), (0, 4));
+        // Writing over either half blanks the other.
+        screen.process(b"\x1b[3Gx");
+        assert_eq!(screen.row(0), "a xb");
+        // A wide character that doesn't fit wraps whole.
+        let screen = after("012345678\u{4e2d}");
+        assert_eq!(screen.row(0), "012345678");
+        assert_eq!(screen.row(1), "\u{4e2d}");
+    }
+
+    #[test]
+    fn scrolls_at_the_bottom() {
+        let screen = after("1\r\n2\r\n3\r\n4\r\n5\r\n6");
+        assert_eq!(screen.contents(), "2\n3\n4\n5\n6");
+        assert_eq!(screen.cursor(), (4, 1));
+    }
+
+    #[test]
+    fn scroll_region() {
+        let mut screen = after("1\r\n2\r\n3\r\n4\r\n5");
+        // Rows 2 to 4 scroll, leaving the first and last in place.
+        screen.process(b"\x1b[2;4r");
+        assert_eq!(screen.cursor(), (0, 0));
+        screen.process(b"\x1b[4;1H\nx");
+        assert_eq!(screen.contents(), "1\n3\n4\nx\n5");
+        // Reverse index at the top of the region scrolls it down.
+        screen.process(b"\x1b[2;1H\x1bM");
+        assert_eq!(screen.contents(), "1\n\n3\n4\n5");
+        // Inserting and deleting lines only moves the region below the cursor.
+        screen.process(b"\x1b[3;1H\x1b[M");
+        assert_eq!(screen.contents(), "1\n\n4\n\n5");
+        screen.process(b"\x1b[L");
+        assert_eq!(screen.contents(), "1\n\n\n4\n5");
+    }
+
+    #[test]
+    fn sequences_split_across_chunks() {
+        let mut screen = after("a\x1b[");
+        screen.process(b"3");
+        screen.process(b"Cb\xe4");
+        assert_eq!(screen.row(0), "a   b");
+        screen.process(b"\xb8\xad");
+        assert_eq!(screen.row(0), "a   b\u{4e2d}");
+    }
+
+    #[test]
+    fn ignores_styles_and_strings() {
+        let screen = after("\x1b[1;31ma\x1b[0m\x1b]0;title\x07b\x1b]8;;url\x1b\\c\x1b[2 qd");
+        assert_eq!(screen.row(0), "abcd");
+    }
+
+    #[test]
+    fn answers_requests() {
+        let mut screen = after("ab\x1b[6n\x1b[5n");
+        assert_eq!(screen.take_replies(), b"\x1b[1;3R\x1b[0n");
+        assert!(screen.take_replies().is_empty());
+    }
+
+    #[test]
+    fn alternate_screen() {
+        let mut screen = after("main\x1b[?1049h");
+        assert!(screen.is_alternate());
+        assert_eq!(screen.contents(), "");
+        screen.process(b"\x1b[3;1Hpager\x1b[?1049l");
+        assert!(!screen.is_alternate());
+        assert_eq!(screen.contents(), "main");
+        assert_eq!(screen.cursor(), (0, 4));
+    }
+
+    #[test]
+    fn resize_keeps_the_top_left() {
+        let mut screen = after("0123456789\r\nab\x1b[5;10H");
+        screen.resize(3, 4);
+        assert_eq!(screen.contents(), "0123\nab");
+        assert_eq!(screen.cursor(), (2, 3));
+    }
+}
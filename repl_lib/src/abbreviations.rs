@@ -0,0 +1,42 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use crate::{Repl, exceeds};
+
+impl<T> Repl<T> {
+    /// Replace the word before the cursor with its expansion if it's a registered
+    /// abbreviation, like fish's `abbr`. Called before Space or Enter is handled. Returns
+    /// `true` if the input changed.
+    ///
+    /// The word must end at the cursor and at whitespace or the end of the input, so
+    /// moving back into a word and typing a space splits it as usual.
+    pub(crate) fn expand_abbreviation(&mut self) -> bool {
+        if self.abbreviations.is_empty() {
+            return false;
+        }
+        let max_length = self.max_length;
+        let abbreviations = &self.abbreviations;
+        let Some(line) = self.lines.get_mut(self.current_line) else {
+            return false;
+        };
+        let (before, after) = line.text.split_at(line.cursor_pos);
+        if after.starts_with(|c: char| !c.is_whitespace()) {
+            return false;
+        }
+        let start = before.rfind(char::is_whitespace).map_or(0, |i| {
+            i + before[i..].chars().next().map_or(1, char::len_utf8)
+        });
+        let word = &before[start..];
+        let Some((_, expansion)) = abbreviations.iter().find(|(name, _)| name == word) else {
+            return false;
+        };
+        let rest = format!("{}{}", &line.text[..start], after);
+        if exceeds(max_length, &rest, expansion) {
+            return false;
+        }
+        line.text.replace_range(start..line.cursor_pos, expansion);
+        line.cursor_pos = start + expansion.len();
+        true
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::Duration,
+};
+
+use crate::{Error, EvalContext, EvalFunc, ReplBuilder, Result, UserError};
+
+/// The future evaluating a line.
+pub type LineFuture<T = String> = Pin<Box<dyn Future<Output = Result<T>>>>;
+
+/// Function type for processing input lines asynchronously, e.g. with a network or database
+/// client.
+pub type AsyncEvalFunc<T = String> = Box<dyn FnMut(String) -> LineFuture<T>>;
+
+/// Function type for running a line's future to completion on an async runtime, e.g.
+/// `Box::new(move |future| runtime.block_on(future))` with a Tokio runtime.
+pub type BlockOnFunc<T = String> = Box<dyn FnMut(LineFuture<T>) -> Result<T>>;
+
+/// How often a pending future is checked for Ctrl-C.
+const INTERRUPT_POLL: Duration = Duration::from_millis(50);
+
+/// Wakes the thread blocked on a future.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+impl<T: 'static> ReplBuilder<T> {
+    /// Set an async function to process completed lines. Each line's future runs to
+    /// completion on the REPL's thread before the next prompt, so output and the terminal
+    /// stay in order.
+    ///
+    /// The future is polled without an async runtime, so it must be woken by something that
+    /// doesn't need one on this thread, such as a task spawned on another runtime's threads.
+    /// Ctrl-C drops the future, cancelling it, and reports an `interrupted` error.
+    ///
+    /// Use `async_eval_func_with` for futures that need a runtime's reactor or timers.
+    ///
+    /// Replaces any function set with `process_line` or `eval_func`.
+    pub fn async_eval_func(mut self, mut eval_func: AsyncEvalFunc<T>) -> Self {
+        let wrapped: EvalFunc<T> = Box::new(move |line, ctx| block_on(eval_func(line), ctx));
+        self.process_line = wrapped;
+        self
+    }
+
+    /// Set an async function to process completed lines, with each line's future run to
+    /// completion by `block_on`, usually an async runtime's, so futures can use its reactor
+    /// and timers. Evaluation still happens on the REPL's thread, one line at a time.
+    ///
+    /// Ctrl-C makes the future passed to `block_on` return an `interrupted` error, dropping
+    /// the line's future.
+    ///
+    /// Replaces any function set with `process_line` or `eval_func`.
+    pub fn async_eval_func_with(
+        mut self,
+        mut eval_func: AsyncEvalFunc<T>,
+        mut block_on: BlockOnFunc<T>,
+    ) -> Self {
+        let wrapped: EvalFunc<T> = Box::new(move |line, ctx| {
+            let future = Interruptible::new(eval_func(line), ctx.interrupt_flag());
+            block_on(Box::pin(future))
+        });
+        self.process_line = wrapped;
+        self
+    }
+}
+
+/// A line's future that ends with an `interrupted` error once Ctrl-C is pressed, for
+/// executors that don't know to check for it.
+struct Interruptible<T> {
+    future: LineFuture<T>,
+    /// Set by Ctrl-C. `None` in batch mode.
+    flag: Option<&'static AtomicBool>,
+    /// Shared with the thread watching for Ctrl-C, started on the first pending poll.
+    watch: Option<Arc<Watch>>,
+}
+
+/// State shared with the thread watching for Ctrl-C.
+struct Watch {
+    /// Waker from the latest poll, woken on Ctrl-C.
+    waker: Mutex<Option<Waker>>,
+    /// The future finished or was dropped, so the thread can stop.
+    done: AtomicBool,
+}
+
+impl<T> Interruptible<T> {
+    fn new(future: LineFuture<T>, flag: Option<&'static AtomicBool>) -> Self {
+        Self {
+            future,
+            flag,
+            watch: None,
+        }
+    }
+}
+
+impl<T> Future for Interruptible<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<T>> {
+        if let Poll::Ready(result) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(result);
+        }
+        let Some(flag) = self.flag else {
+            return Poll::Pending;
+        };
+        if flag.load(Ordering::SeqCst) {
+            return Poll::Ready(Err(interrupted()));
+        }
+        let watch = self.watch.get_or_insert_with(|| {
+            let watch = Arc::new(Watch {
+                waker: Mutex::new(None),
+                done: AtomicBool::new(false),
+            });
+            let watched = Arc::clone(&watch);
+            // Ctrl-C doesn't wake the executor, so check for it now and then.
+            thread::spawn(move || {
+                while !watched.done.load(Ordering::SeqCst) {
+                    if flag.load(Ordering::SeqCst) {
+                        if let Some(waker) = watched.waker.lock().ok().and_then(|mut w| w.take()) {
+                            waker.wake();
+                        }
+                        return;
+                    }
+                    thread::sleep(INTERRUPT_POLL);
+                }
+            });
+            watch
+        });
+        if let Ok(mut waker) = watch.waker.lock() {
+            *waker = Some(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Interruptible<T> {
+    fn drop(&mut self) {
+        if let Some(watch) = &self.watch {
+            watch.done.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+fn interrupted() -> Error {
+    Error::User(UserError::new("interrupted"))
+}
+
+/// Poll `future` until it's ready, parking the thread while it's pending. Returns an error
+/// if Ctrl-C is pressed first.
+///
+/// Used when no runtime's `block_on` is given, so there's no reactor or timer.
+fn block_on<T>(mut future: LineFuture<T>, ctx: &EvalContext<'_>) -> Result<T> {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(result) = future.as_mut().poll(&mut cx) {
+            return result;
+        }
+        if ctx.is_interrupted() {
+            return Err(interrupted());
+        }
+        // Ctrl-C doesn't wake the thread, so check for it now and then.
+        thread::park_timeout(INTERRUPT_POLL);
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use crate::{Line, exceeds};
+
+impl Line {
+    /// Handle typing `c` with automatic closing of `pairs`. Returns `false` if `c` should
+    /// be inserted as usual.
+    ///
+    /// Typing a closer that's already under the cursor moves over it. Typing an opener
+    /// inserts its closer too, unless the cursor is right before other text. Quotes, whose
+    /// opener and closer are the same, aren't paired right after a letter or digit so
+    /// apostrophes in words like `don't` stay single.
+    pub(crate) fn insert_paired(
+        &mut self,
+        c: char,
+        pairs: &[(char, char)],
+        max_length: Option<usize>,
+    ) -> bool {
+        let next = self.text[self.cursor_pos..].chars().next();
+        if next == Some(c) && pairs.iter().any(|&(_, close)| close == c) {
+            self.cursor_pos += c.len_utf8();
+            return true;
+        }
+
+        let Some(&(open, close)) = pairs.iter().find(|&&(open, _)| open == c) else {
+            return false;
+        };
+        let before_text = next.is_some_and(|next| {
+            !next.is_whitespace() && !pairs.iter().any(|&(_, close)| close == next)
+        });
+        let after_word = open == close
+            && self.text[..self.cursor_pos]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric);
+        let pair = format!("{open}{close}");
+        if before_text || after_word || exceeds(max_length, &self.text, &pair) {
+            return false;
+        }
+        self.insert_str(&pair);
+        self.cursor_pos -= close.len_utf8();
+        true
+    }
+
+    /// Remove an empty pair around the cursor on Backspace. Returns `false` if the cursor
+    /// isn't between an opener and its closer.
+    pub(crate) fn backspace_pair(&mut self, pairs: &[(char, char)]) -> bool {
+        let prev = self.text[..self.cursor_pos].chars().next_back();
+        let next = self.text[self.cursor_pos..].chars().next();
+        let (Some(prev), Some(next)) = (prev, next) else {
+            return false;
+        };
+        if !pairs.contains(&(prev, next)) {
+            return false;
+        }
+        let start = self.cursor_pos - prev.len_utf8();
+        self.delete_range(start..self.cursor_pos + next.len_utf8());
+        true
+    }
+}
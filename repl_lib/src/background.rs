@@ -0,0 +1,89 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread::{self, JoinHandle},
+};
+
+use crate::{Repl, Result};
+
+/// A `Repl` reading input on its own thread, delivering completed lines to the thread that
+/// spawned it, e.g. so an application's main thread can run an event loop.
+///
+/// The `Repl` is built on the new thread, so it and its callbacks don't need to be `Send`.
+/// Iterating yields lines like `Repl::lines`. Each prompt is drawn only once the next line
+/// is requested, so output printed for a line appears before it. Dropping the `ReplThread`
+/// drops the `Repl`, restoring the terminal.
+pub struct ReplThread {
+    lines: Receiver<Result<String>>,
+    /// Asks the thread to read another line.
+    next: Option<SyncSender<()>>,
+    handle: Option<JoinHandle<()>>,
+    done: bool,
+}
+
+impl ReplThread {
+    /// Spawn a thread that builds a `Repl` with `build` and reads lines with it.
+    ///
+    /// An error from `build` is the first and only item.
+    pub fn spawn<T, F>(build: F) -> Self
+    where
+        F: FnOnce() -> Result<Repl<T>> + Send + 'static,
+    {
+        let (line_tx, lines) = mpsc::sync_channel(0);
+        let (next, next_rx) = mpsc::sync_channel::<()>(0);
+        let handle = thread::spawn(move || {
+            let mut repl = match build() {
+                Ok(repl) => repl,
+                Err(e) => {
+                    let _ = next_rx.recv();
+                    let _ = line_tx.send(Err(e));
+                    return;
+                }
+            };
+            let mut input = repl.lines();
+            while next_rx.recv().is_ok() {
+                let Some(line) = input.next() else {
+                    return;
+                };
+                if line_tx.send(line).is_err() {
+                    return;
+                }
+            }
+        });
+        Self {
+            lines,
+            next: Some(next),
+            handle: Some(handle),
+            done: false,
+        }
+    }
+}
+
+impl Iterator for ReplThread {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let requested = self.next.as_ref().is_some_and(|next| next.send(()).is_ok());
+        let line = requested.then(|| self.lines.recv().ok()).flatten();
+        if !matches!(line, Some(Ok(_))) {
+            self.done = true;
+        }
+        line
+    }
+}
+
+impl Drop for ReplThread {
+    fn drop(&mut self) {
+        // The thread is waiting for a request between lines; closing the channel ends it.
+        self.next = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! Simplified bidirectional reordering of input lines for display.
+//!
+//! Terminals show characters left to right in the order they're written, so right-to-left
+//! scripts (Hebrew, Arabic, ...) would read backwards. Runs of right-to-left text are
+//! reversed for display, keeping numbers inside them left to right, on a left-to-right
+//! line. This covers mixed-direction input well without implementing all of UAX #9.
+
+use crate::{Style, StyledText};
+
+/// Returns `true` for strong right-to-left characters.
+pub(crate) fn is_rtl(c: char) -> bool {
+    matches!(c as u32,
+        0x0590..=0x08FF // Hebrew, Arabic, Syriac, Thaana, NKo, Samaritan, Mandaic
+        | 0xFB1D..=0xFDFF // Hebrew and Arabic presentation forms A
+        | 0xFE70..=0xFEFF // Arabic presentation forms B
+        | 0x10800..=0x10FFF // Historic right-to-left scripts
+        | 0x1E800..=0x1EFFF)
+}
+
+/// Returns the logical indices of `chars` in display order.
+fn visual_order(chars: &[char]) -> Vec<usize> {
+    // A character is displayed right to left if it's strong RTL, a digit following RTL
+    // text, or a neutral between those.
+    let strong = |c: char| c.is_alphabetic() || is_rtl(c);
+    let mut rtl = vec![false; chars.len()];
+    let mut previous_rtl = false;
+    for (i, &c) in chars.iter().enumerate() {
+        if strong(c) {
+            previous_rtl = is_rtl(c);
+            rtl[i] = previous_rtl;
+        } else if c.is_ascii_digit() {
+            rtl[i] = previous_rtl;
+        } else if previous_rtl {
+            rtl[i] = chars[i + 1..]
+                .iter()
+                .find(|&&c| strong(c) || c.is_ascii_digit())
+                .is_some_and(|&c| is_rtl(c) || c.is_ascii_digit());
+        }
+    }
+
+    let mut order: Vec<usize> = (0..chars.len()).collect();
+    let mut start = 0;
+    while start < chars.len() {
+        if !rtl[start] {
+            start += 1;
+            continue;
+        }
+        let end = (start..chars.len())
+            .find(|&i| !rtl[i])
+            .unwrap_or(chars.len());
+        order[start..end].reverse();
+        // Numbers read left to right even inside right-to-left text.
+        let mut i = start;
+        while i < end {
+            if chars[order[i]].is_ascii_digit() {
+                let run_end = (i..end)
+                    .find(|&j| !chars[order[j]].is_ascii_digit())
+                    .unwrap_or(end);
+                order[i..run_end].reverse();
+                i = run_end;
+            } else {
+                i += 1;
+            }
+        }
+        start = end;
+    }
+    order
+}
+
+/// Returns `line` in display order, and the display position of each of its characters,
+/// or `None` if it has no right-to-left text.
+pub(crate) fn reorder(line: &StyledText) -> Option<(StyledText, Vec<usize>)> {
+    let styled_chars: Vec<(char, Style)> = line
+        .spans()
+        .iter()
+        .flat_map(|span| span.text.chars().map(|c| (c, span.style)))
+        .collect();
+    if !styled_chars.iter().any(|&(c, _)| is_rtl(c)) {
+        return None;
+    }
+    let chars: Vec<char> = styled_chars.iter().map(|&(c, _)| c).collect();
+    let order = visual_order(&chars);
+    let mut positions = vec![0; order.len()];
+    let mut visual = StyledText::new();
+    let mut run = String::new();
+    let mut run_style = Style::new();
+    for (position, &i) in order.iter().enumerate() {
+        positions[i] = position;
+        let (c, style) = styled_chars[i];
+        if style != run_style && !run.is_empty() {
+            visual.push(std::mem::take(&mut run), run_style);
+        }
+        run_style = style;
+        run.push(c);
+    }
+    visual.push(run, run_style);
+    Some((visual, positions))
+}
@@ -0,0 +1,135 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use crate::{Error, Line, Result, UserError, tokenize};
+
+/// Function type for handling a meta-command.
+pub type MetaCommandFunc = Box<dyn FnMut(&CommandContext) -> Result<CommandAction>>;
+
+/// State passed to a meta-command handler.
+pub struct CommandContext<'a> {
+    /// Text following the command name, with surrounding whitespace trimmed.
+    pub args: &'a str,
+    /// Previously entered lines, oldest first.
+    pub history: &'a [Line],
+}
+
+impl CommandContext<'_> {
+    /// Returns the arguments split into words shell-style (see `tokenize`).
+    pub fn words(&self) -> Vec<String> {
+        tokenize(self.args)
+            .into_iter()
+            .map(|token| token.text)
+            .collect()
+    }
+}
+
+/// What the REPL should do after a meta-command runs.
+#[derive(Clone, Debug)]
+pub enum CommandAction {
+    /// Print the output and continue.
+    Output(String),
+    /// Stop reading input.
+    Quit,
+}
+
+/// A registered meta-command.
+struct MetaCommand {
+    name: String,
+    description: String,
+    handler: MetaCommandFunc,
+}
+
+/// Registry of meta-commands (e.g. `:help`, `:quit`) intercepted before `process_line`.
+pub(crate) struct MetaCommands {
+    prefix: char,
+    commands: Vec<MetaCommand>,
+}
+
+impl MetaCommands {
+    pub(crate) fn new(prefix: char) -> Self {
+        Self {
+            prefix,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Register a command, replacing any existing command with the same name.
+    pub(crate) fn register(&mut self, name: &str, description: &str, handler: MetaCommandFunc) {
+        let name = name.trim_start_matches(self.prefix).to_string();
+        self.commands.retain(|c| c.name != name);
+        self.commands.push(MetaCommand {
+            name,
+            description: description.to_string(),
+            handler,
+        });
+    }
+
+    /// Returns `true` if the line should be handled as a meta-command.
+    pub(crate) fn is_command(&self, line: &str) -> bool {
+        !self.commands.is_empty() && line.trim_start().starts_with(self.prefix)
+    }
+
+    /// Run the command in `line`. `:help` is generated unless the application registered its own.
+    pub(crate) fn dispatch(&mut self, line: &str, history: &[Line]) -> Result<CommandAction> {
+        let line = line.trim().trim_start_matches(self.prefix);
+        let (name, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let ctx = CommandContext {
+            args: args.trim(),
+            history,
+        };
+
+        match self.commands.iter_mut().find(|c| c.name == name) {
+            Some(command) => (command.handler)(&ctx),
+            None if name == "help" => Ok(CommandAction::Output(self.help())),
+            None => Err(Error::User(UserError::new(format!(
+                "unknown command '{}{}' (try {}help)",
+                self.prefix, name, self.prefix
+            )))),
+        }
+    }
+
+    /// Returns the full names of commands starting with `input`, for completion.
+    pub(crate) fn completions(&self, input: &str) -> Vec<String> {
+        let Some(partial) = input.strip_prefix(self.prefix) else {
+            return Vec::new();
+        };
+
+        let mut names: Vec<String> = self
+            .commands
+            .iter()
+            .map(|c| c.name.as_str())
+            .chain(std::iter::once("help"))
+            .filter(|name| name.starts_with(partial))
+            .map(|name| format!("{}{}", self.prefix, name))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Build the `help` text listing every command and its description.
+    fn help(&self) -> String {
+        let mut entries: Vec<(&str, &str)> = self
+            .commands
+            .iter()
+            .map(|c| (c.name.as_str(), c.description.as_str()))
+            .collect();
+        if !entries.iter().any(|(name, _)| *name == "help") {
+            entries.push(("help", "Show this message."));
+        }
+        entries.sort();
+
+        let width = entries
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        entries
+            .iter()
+            .map(|(name, description)| format!("{}{:<width$}  {}", self.prefix, name, description))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,290 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{env, fs, path::PathBuf};
+
+use term_manager::{Attribute, cursor};
+
+use crate::{
+    HistoryEntry, Repl, Result, Style, StyledText, WordChars, exceeds, text_width, token_at,
+};
+
+/// Most rows of documentation shown below the input for a completed candidate.
+pub(crate) const DOC_ROWS: usize = 6;
+
+/// Function type for completing the input at the cursor when Tab is pressed.
+pub type CompleterFunc = Box<dyn FnMut(&CompletionContext) -> Completions>;
+
+/// State passed to a `CompleterFunc`.
+pub struct CompletionContext<'a> {
+    /// The input being edited.
+    pub line: &'a str,
+    /// Byte offset of the cursor in `line`.
+    pub cursor: usize,
+    /// Previously entered lines, oldest first.
+    pub history: &'a [HistoryEntry],
+    /// The characters words are made of (see `ReplBuilder::word_chars`).
+    pub word_chars: &'a WordChars,
+}
+
+/// Candidates for completing the input before the cursor.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Completions {
+    /// Byte offset where the replaced text starts. It ends at the cursor.
+    pub start: usize,
+    /// Replacements for the text between `start` and the cursor.
+    pub candidates: Vec<String>,
+    /// Documentation for the candidate at the same index, e.g. a signature and summary. A
+    /// completed candidate's is shown below the input until the next key, and the first line
+    /// of each is shown beside the candidates when they're listed. May be shorter than
+    /// `candidates`.
+    pub docs: Vec<Option<String>>,
+}
+
+impl Completions {
+    /// Returns the documentation for the candidate at `index`, if it has any.
+    pub fn doc(&self, index: usize) -> Option<&str> {
+        self.docs.get(index)?.as_deref()
+    }
+}
+
+/// Returns a completer suggesting words from history that start with the word before the
+/// cursor, newest first, like readline's `dabbrev-expand`.
+///
+/// Words are made of the characters in `CompletionContext::word_chars`.
+pub fn history_words() -> CompleterFunc {
+    Box::new(|ctx| {
+        let start = ctx.line[..ctx.cursor]
+            .rfind(|c: char| !ctx.word_chars.contains(c))
+            .map_or(0, |i| {
+                i + ctx.line[i..].chars().next().map_or(1, char::len_utf8)
+            });
+        let prefix = &ctx.line[start..ctx.cursor];
+        let mut candidates: Vec<String> = Vec::new();
+        if !prefix.is_empty() {
+            for entry in ctx.history.iter().rev() {
+                for word in entry.text.split(|c: char| !ctx.word_chars.contains(c)) {
+                    if word.len() > prefix.len()
+                        && word.starts_with(prefix)
+                        && !candidates.iter().any(|c| c == word)
+                    {
+                        candidates.push(word.to_string());
+                    }
+                }
+            }
+        }
+        Completions {
+            start,
+            candidates,
+            docs: Vec::new(),
+        }
+    })
+}
+
+/// Returns a completer for paths, for commands that take them.
+///
+/// The word before the cursor is completed against the filesystem, with `~` standing for
+/// the home directory. Directories get a trailing `/`, and spaces and quotes in names are
+/// escaped with backslashes. Hidden files are only offered once a `.` is typed.
+pub fn filenames() -> CompleterFunc {
+    Box::new(|ctx| {
+        let (_, token) = token_at(&ctx.line[..ctx.cursor], ctx.cursor);
+        let (dir, prefix) = match token.text.rfind('/') {
+            Some(i) => token.text.split_at(i + 1),
+            None => ("", token.text.as_str()),
+        };
+        let Ok(read_dir) = fs::read_dir(expand_home(dir)) else {
+            return Completions::default();
+        };
+        let mut candidates: Vec<String> = read_dir
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.'))
+                {
+                    return None;
+                }
+                // Follows symlinks, so links to directories get a slash too.
+                let is_dir = fs::metadata(entry.path()).is_ok_and(|m| m.is_dir());
+                let slash = if is_dir { "/" } else { "" };
+                Some(format!("{}{}", escape_path(&format!("{dir}{name}")), slash))
+            })
+            .collect();
+        candidates.sort();
+        Completions {
+            start: token.start,
+            candidates,
+            docs: Vec::new(),
+        }
+    })
+}
+
+/// Returns a completer combining the candidates of `completers`, in order.
+///
+/// The first completer with candidates decides which text is replaced; candidates from
+/// completers replacing different text are left out.
+pub fn merge(mut completers: Vec<CompleterFunc>) -> CompleterFunc {
+    Box::new(move |ctx| {
+        let mut merged: Option<Completions> = None;
+        for completer in completers.iter_mut() {
+            let completions = completer(ctx);
+            if completions.candidates.is_empty() {
+                continue;
+            }
+            match merged.as_mut() {
+                None => merged = Some(completions),
+                Some(merged) if merged.start == completions.start => {
+                    for (i, candidate) in completions.candidates.iter().enumerate() {
+                        if !merged.candidates.contains(candidate) {
+                            merged.docs.resize(merged.candidates.len(), None);
+                            merged.docs.push(completions.doc(i).map(str::to_string));
+                            merged.candidates.push(candidate.clone());
+                        }
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+        merged.unwrap_or_default()
+    })
+}
+
+/// Returns `dir` with a leading `~` replaced by the home directory, or `.` if it's empty.
+fn expand_home(dir: &str) -> PathBuf {
+    if dir.is_empty() {
+        return PathBuf::from(".");
+    }
+    match (dir.strip_prefix('~'), env::var_os("HOME")) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            PathBuf::from(home).join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(dir),
+    }
+}
+
+/// Returns `path` with whitespace, quotes, and backslashes escaped with a backslash.
+fn escape_path(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_whitespace() || matches!(c, '\\' | '"' | '\'') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl<T> Repl<T> {
+    /// Add `completer` after the `Repl` is built, e.g. from a plugin. Its candidates are
+    /// merged after those of the completer already set, as by `merge`.
+    ///
+    /// In a sub-mode, it's added to the mode's completer.
+    pub fn register_completer(&mut self, completer: CompleterFunc) {
+        self.completer = Some(match self.completer.take() {
+            Some(existing) => merge(vec![existing, completer]),
+            None => completer,
+        });
+    }
+
+    /// Complete the input at the cursor with the completer.
+    ///
+    /// A single candidate, or the prefix shared by all candidates, replaces the text being
+    /// completed. If that doesn't change anything the candidates are listed below the
+    /// input. A completed candidate's documentation is shown below the input. Returns `false`
+    /// if there are no candidates.
+    pub(crate) fn complete(&mut self) -> Result<bool> {
+        let Some(completer) = self.completer.as_mut() else {
+            return Ok(false);
+        };
+        let line = &self.lines[self.current_line];
+        let history = self.history.lock();
+        let default_words = WordChars::default();
+        let ctx = CompletionContext {
+            line: &line.text,
+            cursor: line.cursor_pos,
+            history: history.entries(),
+            word_chars: self.word_chars.as_ref().unwrap_or(&default_words),
+        };
+        let completions = completer(&ctx);
+        drop(history);
+        let Completions {
+            start, candidates, ..
+        } = &completions;
+        let start = *start;
+        let line = &self.lines[self.current_line];
+        if candidates.is_empty() || start > line.cursor_pos || !line.text.is_char_boundary(start) {
+            return Ok(false);
+        }
+
+        let typed = &line.text[start..line.cursor_pos];
+        let replacement = match candidates.as_slice() {
+            [only] => only.clone(),
+            [first, rest @ ..] => {
+                let shared = rest.iter().fold(first.as_str(), |shared, c| {
+                    let len = shared
+                        .char_indices()
+                        .zip(c.chars())
+                        .find(|((_, a), b)| a != b)
+                        .map_or(shared.len().min(c.len()), |((i, _), _)| i);
+                    &shared[..len]
+                });
+                shared.to_string()
+            }
+            [] => unreachable!(),
+        };
+        if candidates.len() > 1 && replacement.len() <= typed.len() {
+            self.list_candidates(&completions)?;
+            return Ok(true);
+        }
+
+        let rest = format!("{}{}", &line.text[..start], &line.text[line.cursor_pos..]);
+        if exceeds(self.max_length, &rest, &replacement) {
+            self.bell()?;
+            return Ok(true);
+        }
+        let line = self.current_line_mut()?;
+        line.text
+            .replace_range(start..line.cursor_pos, &replacement);
+        line.cursor_pos = start + replacement.len();
+        if candidates.len() == 1 {
+            self.completion_doc = completions.doc(0).map(str::to_string);
+        }
+        self.redraw_current_line()?;
+        Ok(true)
+    }
+
+    /// Print the candidates in columns below the input, then redraw the input under them.
+    /// Candidates with documentation are listed one per row, followed by its first line.
+    fn list_candidates(&mut self, completions: &Completions) -> Result<()> {
+        let candidates = &completions.candidates;
+        let cols = self
+            .tmanager
+            .as_ref()
+            .and_then(|tmanager| tmanager.size().ok())
+            .map_or(80, |(_, cols)| cols as usize);
+        let width = candidates.iter().map(|c| text_width(c)).max().unwrap_or(0) + 2;
+        let documented = (0..candidates.len()).any(|i| completions.doc(i).is_some());
+        let per_row = if documented { 1 } else { (cols / width).max(1) };
+
+        let below = self.rows_below_cursor();
+        let mut out = format!("{}\r\n", cursor::down(below));
+        for (i, row) in candidates.chunks(per_row).enumerate() {
+            for candidate in row {
+                out.push_str(candidate);
+                out.push_str(&" ".repeat(width - text_width(candidate)));
+            }
+            if let Some(doc) = completions.doc(i) {
+                let summary = doc.lines().next().unwrap_or_default();
+                let shown = StyledText::new()
+                    .span(summary, Style::new().attr(Attribute::Dim))
+                    .truncate(cols.saturating_sub(width + 1));
+                out.push_str(&self.render(&shown));
+            }
+            out.push_str("\r\n");
+        }
+        self.write(&out)?;
+        self.rendered_row = 0;
+        self.redraw_current_line()
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use term_manager::Color;
+
+use crate::{
+    BellStyle, EditMode, Error, ErrorContext, InternalError, ReplBuilder, Result, Style,
+    StyledText, TabMode,
+};
+
+/// User settings for a REPL, read from a configuration file so end users can customize
+/// every application built on `repl_lib` the same way.
+///
+/// The file uses a subset of TOML: `key = value` pairs with string or integer values, `#`
+/// comments, and a `[colors]` section. Every setting is optional:
+///
+/// ```toml
+/// prompt = "λ "
+/// continuation_prompt = ". "
+/// bell = "visible"         # "audible", "visible", or "none"
+/// tab = 4                  # "ignore", "literal", or a number of spaces
+/// edit_mode = "vi"         # "emacs" or "vi"
+/// history_file = "~/.myrepl_history"
+/// history_size = 1000
+///
+/// [colors]
+/// prompt = "green"         # a color name, "#rrggbb", or a 256-color palette index
+/// continuation_prompt = "bright-black"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    /// `prompt`
+    pub prompt: Option<String>,
+    /// `continuation_prompt`
+    pub continuation_prompt: Option<String>,
+    /// `prompt` in `[colors]`
+    pub prompt_color: Option<Color>,
+    /// `continuation_prompt` in `[colors]`
+    pub continuation_prompt_color: Option<Color>,
+    /// `bell`
+    pub bell_style: Option<BellStyle>,
+    /// `tab`
+    pub tab_mode: Option<TabMode>,
+    /// `edit_mode`
+    pub edit_mode: Option<EditMode>,
+    /// `history_file`, with a leading `~/` expanded to the home directory.
+    pub history_file: Option<PathBuf>,
+    /// `history_size`
+    pub history_size: Option<usize>,
+}
+
+/// Environment variables read by `Config::from_env`, with the setting each one overrides.
+const ENV_VARS: [(&str, &str, &str); 7] = [
+    ("REPL_HISTORY_FILE", "", "history_file"),
+    ("REPL_HISTORY_SIZE", "", "history_size"),
+    ("REPL_BELL", "", "bell"),
+    ("REPL_TAB", "", "tab"),
+    ("REPL_EDIT_MODE", "", "edit_mode"),
+    ("REPL_PROMPT_COLOR", "colors", "prompt"),
+    (
+        "REPL_CONTINUATION_PROMPT_COLOR",
+        "colors",
+        "continuation_prompt",
+    ),
+];
+
+impl Config {
+    /// Read the configuration file at `path`. A missing file gives an empty configuration.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(Error::Internal(InternalError::IoRead(
+                    ErrorContext::with_source(
+                        format!("unable to load config from {}", path.display()),
+                        e,
+                    ),
+                )));
+            }
+        };
+        parse(&contents).map_err(|e| invalid_config(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parse configuration file contents.
+    pub fn parse(text: &str) -> Result<Self> {
+        parse(text).map_err(invalid_config)
+    }
+
+    /// Read settings from environment variables, so deployments can tune a REPL without
+    /// code changes. `ReplBuilder::build` applies them over the builder's settings, skipping
+    /// invalid values with a warning on stderr.
+    ///
+    /// Each variable takes the same values as the config file setting it overrides:
+    ///
+    /// | Variable                         | Setting                             |
+    /// |----------------------------------|-------------------------------------|
+    /// | `REPL_HISTORY_FILE`              | `history_file`                      |
+    /// | `REPL_HISTORY_SIZE`              | `history_size`                      |
+    /// | `REPL_BELL`                      | `bell`                              |
+    /// | `REPL_TAB`                       | `tab`                               |
+    /// | `REPL_EDIT_MODE`                 | `edit_mode`                         |
+    /// | `REPL_PROMPT_COLOR`              | `prompt` in `[colors]`              |
+    /// | `REPL_CONTINUATION_PROMPT_COLOR` | `continuation_prompt` in `[colors]` |
+    ///
+    /// Unset and empty variables are ignored. Returns an error for the first invalid value.
+    pub fn from_env() -> Result<Self> {
+        let mut invalid = None;
+        let config = Self::read_env(|message| {
+            invalid.get_or_insert(message);
+        });
+        match invalid {
+            Some(message) => Err(invalid_config(message)),
+            None => Ok(config),
+        }
+    }
+
+    /// Read settings from environment variables like `from_env`, leaving out invalid values
+    /// with a warning on stderr.
+    pub(crate) fn from_env_or_warn() -> Self {
+        Self::read_env(|message| eprintln!("warning: ignoring invalid {}", message))
+    }
+
+    /// Read the settings in `ENV_VARS`, passing a message for each invalid value to
+    /// `invalid`.
+    fn read_env(mut invalid: impl FnMut(String)) -> Self {
+        let mut config = Self::default();
+        for (var, section, key) in ENV_VARS {
+            let Some(text) = env::var(var).ok().filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            let value = match text.parse() {
+                Ok(n) if key != "history_file" => Value::Integer(n),
+                _ => Value::String(text),
+            };
+            if let Err(e) = config.set(section, key, value) {
+                invalid(format!("{}: {}", var, e));
+            }
+        }
+        config
+    }
+
+    /// Set `key` in `section` (empty for the top level).
+    fn set(&mut self, section: &str, key: &str, value: Value) -> std::result::Result<(), String> {
+        match (section, key) {
+            ("", "prompt") => self.prompt = Some(value.into_string(key)?),
+            ("", "continuation_prompt") => self.continuation_prompt = Some(value.into_string(key)?),
+            ("", "bell") => {
+                self.bell_style = Some(match value.into_string(key)?.as_str() {
+                    "audible" => BellStyle::Audible,
+                    "visible" => BellStyle::Visible,
+                    "none" => BellStyle::None,
+                    other => return Err(format!("unknown bell style \"{}\"", other)),
+                })
+            }
+            ("", "tab") => {
+                self.tab_mode = Some(match value {
+                    Value::Integer(n) if n > 0 => TabMode::Spaces(n as usize),
+                    Value::String(s) if s == "ignore" => TabMode::Ignore,
+                    Value::String(s) if s == "literal" => TabMode::Literal,
+                    _ => return Err("tab must be \"ignore\", \"literal\", or a number".into()),
+                })
+            }
+            ("", "edit_mode") => {
+                self.edit_mode = Some(match value.into_string(key)?.as_str() {
+                    "emacs" => EditMode::Emacs,
+                    "vi" => EditMode::Vi,
+                    other => return Err(format!("unknown edit mode \"{}\"", other)),
+                })
+            }
+            ("", "history_file") => {
+                self.history_file = Some(expand_home(&value.into_string(key)?));
+            }
+            ("", "history_size") => match value {
+                Value::Integer(n) if n >= 0 => self.history_size = Some(n as usize),
+                _ => return Err("history_size must be a non-negative number".into()),
+            },
+            ("colors", "prompt") => self.prompt_color = Some(value.into_color(key)?),
+            ("colors", "continuation_prompt") => {
+                self.continuation_prompt_color = Some(value.into_color(key)?);
+            }
+            ("", _) => return Err(format!("unknown setting `{}`", key)),
+            _ => return Err(format!("unknown setting `{}` in [{}]", key, section)),
+        }
+        Ok(())
+    }
+}
+
+impl<T> ReplBuilder<T> {
+    /// Apply the settings present in `config`.
+    ///
+    /// Settings made after this call take precedence, so call it before builder methods
+    /// the application always wants, and after its defaults.
+    pub fn config(mut self, config: &Config) -> Self {
+        if config.prompt.is_some() || config.prompt_color.is_some() {
+            let text = config.prompt.clone().unwrap_or_else(|| self.prompt.text());
+            self.prompt = colored(text, config.prompt_color);
+        }
+        let continuation = config
+            .continuation_prompt
+            .clone()
+            .or_else(|| self.continuation_prompt.as_ref().map(StyledText::text));
+        if let Some(text) = continuation
+            && (config.continuation_prompt.is_some() || config.continuation_prompt_color.is_some())
+        {
+            self.continuation_prompt = Some(colored(text, config.continuation_prompt_color));
+        }
+        if let Some(bell_style) = config.bell_style {
+            self.bell_style = bell_style;
+        }
+        if let Some(tab_mode) = config.tab_mode {
+            self.tab_mode = tab_mode;
+        }
+        if let Some(edit_mode) = config.edit_mode {
+            self.edit_mode = edit_mode;
+        }
+        #[cfg(feature = "persistence")]
+        if let Some(path) = config.history_file.clone() {
+            self = self.history_file(path);
+        }
+        if let Some(size) = config.history_size {
+            self.history_size = Some(size);
+        }
+        self
+    }
+}
+
+/// A value on the right of `=`.
+enum Value {
+    String(String),
+    Integer(i64),
+}
+
+impl Value {
+    fn parse(text: &str) -> std::result::Result<Self, String> {
+        if let Some(quoted) = text.strip_prefix('"') {
+            return unquote(quoted).map(Value::String);
+        }
+        text.replace('_', "")
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| format!("invalid value `{}`", text))
+    }
+
+    fn into_string(self, key: &str) -> std::result::Result<String, String> {
+        match self {
+            Value::String(s) => Ok(s),
+            _ => Err(format!("{} must be a string", key)),
+        }
+    }
+
+    fn into_color(self, key: &str) -> std::result::Result<Color, String> {
+        match self {
+            Value::Integer(n) => u8::try_from(n)
+                .map(Color::Ansi)
+                .map_err(|_| format!("{} must be a palette index from 0 to 255", key)),
+            Value::String(s) => parse_color(&s).ok_or_else(|| format!("unknown color \"{}\"", s)),
+        }
+    }
+}
+
+fn parse(text: &str) -> std::result::Result<Config, String> {
+    let mut config = Config::default();
+    let mut section = String::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let in_line = |e: String| format!("line {}: {}", i + 1, e);
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            if section != "colors" {
+                return Err(in_line(format!("unknown section [{}]", section)));
+            }
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| in_line("expected `key = value`".into()))?;
+        let value = Value::parse(value.trim()).map_err(in_line)?;
+        config.set(&section, key.trim(), value).map_err(in_line)?;
+    }
+    Ok(config)
+}
+
+/// Returns `line` without a trailing `#` comment, ignoring `#` inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Returns the contents of a string whose opening quote has been removed.
+fn unquote(text: &str) -> std::result::Result<String, String> {
+    let mut unquoted = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if chars.as_str().trim().is_empty() => return Ok(unquoted),
+            '"' => return Err("unexpected text after string".into()),
+            '\\' => unquoted.push(match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('e') => '\x1b',
+                Some(c @ ('"' | '\\')) => c,
+                _ => return Err("invalid escape in string".into()),
+            }),
+            c => unquoted.push(c),
+        }
+    }
+    Err("unterminated string".into())
+}
+
+/// Parse a color name (e.g. `red`, `bright-blue`) or `#rrggbb`.
+fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    let color = match name.to_ascii_lowercase().replace('_', "-").as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        "bright-black" | "gray" | "grey" => Color::BrightBlack,
+        "bright-red" => Color::BrightRed,
+        "bright-green" => Color::BrightGreen,
+        "bright-yellow" => Color::BrightYellow,
+        "bright-blue" => Color::BrightBlue,
+        "bright-magenta" => Color::BrightMagenta,
+        "bright-cyan" => Color::BrightCyan,
+        "bright-white" => Color::BrightWhite,
+        _ => return None,
+    };
+    Some(color)
+}
+
+/// Expand a leading `~/` to the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn colored(text: String, color: Option<Color>) -> StyledText {
+    match color {
+        Some(color) => StyledText::new().span(text, Style::new().fg(color)),
+        None => StyledText::from(text),
+    }
+}
+
+fn invalid_config(message: String) -> Error {
+    Error::Internal(InternalError::InitFail(ErrorContext::new(format!(
+        "invalid config: {}",
+        message
+    ))))
+}
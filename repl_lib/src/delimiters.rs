@@ -0,0 +1,56 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use crate::Validation;
+
+/// Parentheses, square brackets, and curly braces.
+pub const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+/// Check that the opening and closing delimiters in `pairs` are balanced in `text`, for use
+/// in a `ValidatorFunc`.
+///
+/// Delimiters inside double-quoted strings are ignored, and a backslash escapes the next
+/// character in a string. Returns `Validation::Incomplete` if a delimiter or string is still
+/// open, and `Validation::Invalid` for a closing delimiter that doesn't match.
+pub fn delimiters_balanced(text: &str, pairs: &[(char, char)]) -> Validation {
+    let mut open: Vec<char> = Vec::new();
+    let mut in_string = false;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+        } else if let Some(&(_, close)) = pairs.iter().find(|(o, _)| *o == c) {
+            open.push(close);
+        } else if pairs.iter().any(|(_, close)| *close == c) {
+            match open.pop() {
+                Some(expected) if expected == c => {}
+                Some(expected) => {
+                    return Validation::Invalid {
+                        message: format!("mismatched '{c}', expected '{expected}'"),
+                    };
+                }
+                None => {
+                    return Validation::Invalid {
+                        message: format!("unmatched '{c}'"),
+                    };
+                }
+            }
+        }
+    }
+    if in_string || !open.is_empty() {
+        Validation::Incomplete
+    } else {
+        Validation::Complete
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{env, fs, path::Path, process};
+
+use crate::{Error, ErrorContext, InternalError, Repl, Result, UserError};
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set.
+const DEFAULT_EDITOR: &str = "vi";
+
+impl<T> Repl<T> {
+    /// Open the current input in `$VISUAL`/`$EDITOR` and replace it with the edited text.
+    ///
+    /// Raw mode is suspended while the editor runs. If the editor can't be started or exits
+    /// with an error, the message is shown and the input is left unchanged.
+    pub(crate) fn edit_in_editor(&mut self) -> Result<()> {
+        let text = self.current_line_mut()?.text().to_string();
+        let path = env::temp_dir().join(format!("repl_lib-{}.txt", process::id()));
+
+        let edited = self.run_editor(&path, &text);
+        let _ = fs::remove_file(&path);
+
+        match edited {
+            Ok(mut edited) => {
+                // Editors usually add a final newline.
+                if edited.ends_with('\n') {
+                    edited.pop();
+                }
+                let line = self.current_line_mut()?;
+                line.text = edited;
+                line.cursor_pos = line.text.len();
+            }
+            // Without raw mode the line editor can't continue.
+            Err(e @ Error::Internal(InternalError::InitFail(_))) => return Err(e),
+            Err(e) => {
+                self.write(&format!("\r\n{}\r\n", e))?;
+                self.rendered_row = 0;
+            }
+        }
+        self.redraw_current_line()
+    }
+
+    /// Write `text` to `path`, run the editor on it and return the file's new contents.
+    fn run_editor(&mut self, path: &Path, text: &str) -> Result<String> {
+        fs::write(path, text).map_err(|e| {
+            Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                format!("unable to write {}", path.display()),
+                e,
+            )))
+        })?;
+
+        let editor = env::var("VISUAL")
+            .or_else(|_| env::var("EDITOR"))
+            .unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+
+        self.write("\r\n")?;
+        self.flush()?;
+        self.tmanager_mut()?.suspend_raw_mode().map_err(|e| {
+            Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                "unable to restore terminal",
+                e,
+            )))
+        })?;
+        // Run through the shell so `$EDITOR` may include arguments (e.g. `code --wait`).
+        let status = process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("sh")
+            .arg(path)
+            .status();
+        self.tmanager_mut()?.resume_raw_mode().map_err(|e| {
+            Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                "unable to enter raw mode",
+                e,
+            )))
+        })?;
+        self.rendered_row = 0;
+
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                return Err(Error::User(UserError::new(format!(
+                    "editor '{}' exited with {}",
+                    editor, status
+                ))));
+            }
+            Err(e) => {
+                return Err(Error::User(UserError::new(format!(
+                    "unable to run editor '{}': {}",
+                    editor, e
+                ))));
+            }
+        }
+
+        fs::read_to_string(path).map_err(|e| {
+            Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+                format!("unable to read {}", path.display()),
+                e,
+            )))
+        })
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    io::{self, Write},
+    sync::atomic::AtomicBool,
+};
+
+use term_manager::{ColorDepth, InterruptGuard, TermManager};
+
+use crate::{Result, StyledText, spinner::Spinner};
+
+/// Function type for processing input lines with access to the `EvalContext`.
+pub type EvalFunc<T = String> = Box<dyn FnMut(String, &mut EvalContext<'_>) -> Result<T>>;
+
+/// State passed to an `EvalFunc` while a line is evaluated.
+///
+/// The context is a writer: output written to it is printed immediately, so long-running
+/// evaluations can report progress before returning their value. Writing to it also removes
+/// the spinner shown by `ReplBuilder::spinner`; output printed to stdout by other means may
+/// be overwritten by it.
+///
+/// Ctrl-C during evaluation doesn't end the process or reach the line editor; it sets a flag
+/// that `is_interrupted` reports, so the evaluation can stop and return to the prompt.
+pub struct EvalContext<'a> {
+    tmanager: Option<&'a mut TermManager>,
+    /// Catches Ctrl-C while evaluating. Not installed in batch mode.
+    interrupt: Option<&'a InterruptGuard>,
+    execution_count: usize,
+    color_depth: ColorDepth,
+    /// Output written so far, kept if a transcript or the session is being recorded.
+    recorded: Option<String>,
+    /// Shown until the evaluation writes output or returns.
+    spinner: Option<Spinner>,
+}
+
+impl<'a> EvalContext<'a> {
+    pub(crate) fn new(
+        tmanager: Option<&'a mut TermManager>,
+        interrupt: Option<&'a InterruptGuard>,
+        execution_count: usize,
+        color_depth: ColorDepth,
+        record: bool,
+        spinner: Option<Spinner>,
+    ) -> Self {
+        Self {
+            tmanager,
+            interrupt,
+            execution_count,
+            color_depth,
+            recorded: record.then(String::new),
+            spinner,
+        }
+    }
+
+    /// Returns the number of the line being evaluated, counting from 1, as shown by
+    /// `ReplBuilder::numbered`.
+    pub fn execution_count(&self) -> usize {
+        self.execution_count
+    }
+
+    /// Returns the colors output is rendered with: those the terminal supports, or none in
+    /// screen reader mode.
+    pub fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    /// Returns the terminal's width in columns, or `None` in batch mode or if it's unknown.
+    pub fn width(&self) -> Option<usize> {
+        let (_, cols) = self.tmanager.as_deref()?.size().ok()?;
+        (cols > 0).then_some(cols as usize)
+    }
+
+    /// Write `text` and a newline, styled for the terminal, e.g. output from the `output`
+    /// helpers.
+    pub fn print(&mut self, text: impl Into<StyledText>) -> io::Result<()> {
+        let text = text.into();
+        let hyperlinks = self.color_depth != ColorDepth::NoColor
+            && self
+                .tmanager
+                .as_ref()
+                .is_some_and(|tmanager| tmanager.caps().hyperlinks);
+        let rendered = if hyperlinks {
+            text.render_hyperlinks(self.color_depth)
+        } else {
+            text.render(self.color_depth)
+        };
+        writeln!(self, "{}", rendered)
+    }
+
+    /// Returns `true` if Ctrl-C was pressed since evaluation started.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupt.is_some_and(InterruptGuard::is_interrupted)
+    }
+
+    /// Returns the flag set by Ctrl-C, for checking from other threads. `None` in batch mode.
+    pub fn interrupt_flag(&self) -> Option<&'static AtomicBool> {
+        self.interrupt.map(InterruptGuard::flag)
+    }
+
+    /// Returns the output written during evaluation, if it was kept for recording.
+    pub(crate) fn into_recorded(self) -> Option<String> {
+        self.recorded
+    }
+}
+
+impl Write for EvalContext<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(mut spinner) = self.spinner.take() {
+            spinner.stop();
+        }
+        match self.tmanager.as_deref_mut() {
+            Some(tmanager) => tmanager.write(buf).map_err(io::Error::other)?,
+            None => {
+                let mut stdout = io::stdout();
+                stdout.write_all(buf)?;
+                stdout.flush()?;
+            }
+        }
+        if let Some(recorded) = self.recorded.as_mut() {
+            recorded.push_str(&String::from_utf8_lossy(buf));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.tmanager.as_deref_mut() {
+            Some(tmanager) => tmanager.flush().map_err(io::Error::other),
+            None => io::stdout().flush(),
+        }
+    }
+}
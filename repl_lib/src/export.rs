@@ -0,0 +1,303 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{fmt::Write as _, fs, path::Path};
+
+use term_manager::{Color, ColorDepth};
+
+use crate::{Error, ErrorContext, InternalError, Repl, Result, StyledText, UserError};
+
+/// Default text color of the page.
+const FOREGROUND: &str = "#e5e5e5";
+/// Background color of the page.
+const BACKGROUND: &str = "#1e1e1e";
+
+/// Format of a session exported with `Repl::export_session`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    /// Text with ANSI escape sequences, for viewing with `cat` or `less -R`.
+    Ansi,
+    /// A standalone HTML page showing the session as it looked in a dark terminal.
+    Html,
+}
+
+impl<T> Repl<T> {
+    /// Returns the session recorded with `ReplBuilder::record_session` in `format`, or
+    /// `None` if it isn't being recorded.
+    ///
+    /// Prompts and input are shown with their colors and highlighting, and output as it was
+    /// printed.
+    pub fn session_text(&self, format: ExportFormat) -> Option<String> {
+        let session = self.session.as_deref()?;
+        Some(match format {
+            ExportFormat::Ansi => session.to_string(),
+            ExportFormat::Html => html_page(session),
+        })
+    }
+
+    /// Write the session recorded with `ReplBuilder::record_session` to the file at `path`
+    /// in `format`, e.g. from a `:export` meta-command.
+    pub fn export_session(&self, path: impl AsRef<Path>, format: ExportFormat) -> Result<()> {
+        let path = path.as_ref();
+        let Some(text) = self.session_text(format) else {
+            return Err(Error::User(UserError::new(
+                "the session isn't being recorded",
+            )));
+        };
+        fs::write(path, text).map_err(|e| {
+            Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                format!("unable to export session to {}", path.display()),
+                e,
+            )))
+        })
+    }
+
+    /// Add a line of input to the session, after its prompts and styled by the highlighter.
+    pub(crate) fn session_input(&mut self, line: &str) {
+        if self.session.is_none() {
+            return;
+        }
+        let highlighted = self
+            .highlighter
+            .as_mut()
+            .map(|highlighter| highlighter(line, line.len()))
+            .filter(|styled| styled.text() == line)
+            .unwrap_or_else(|| StyledText::from(line));
+        let mut lines = vec![StyledText::new()];
+        for span in highlighted.spans() {
+            for (i, part) in span.text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(StyledText::new());
+                }
+                if let Some(last) = lines.last_mut() {
+                    last.push(part, span.style);
+                }
+            }
+        }
+        let mut record = String::new();
+        for (i, styled) in lines.iter().enumerate() {
+            let prompt = if i == 0 {
+                &self.prompt
+            } else {
+                &self.continuation_prompt
+            };
+            record.push_str(&prompt.render_hyperlinks(ColorDepth::TrueColor));
+            record.push_str(&styled.render(ColorDepth::TrueColor));
+            record.push('\n');
+        }
+        if let Some(session) = self.session.as_mut() {
+            session.push_str(&record);
+        }
+    }
+
+    /// Add output to the session.
+    pub(crate) fn session_output(&mut self, output: &str) {
+        if let Some(session) = self.session.as_mut() {
+            session.push_str(output);
+            session.push('\n');
+        }
+    }
+}
+
+/// Style set by SGR sequences while converting to HTML.
+#[derive(Clone, Default, PartialEq)]
+struct CellStyle {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    reverse: bool,
+    link: Option<String>,
+}
+
+impl CellStyle {
+    /// Apply the parameters of an SGR sequence.
+    fn apply(&mut self, params: &str) {
+        let mut params = params
+            .split([';', ':'])
+            .map(|p| p.parse::<u8>().unwrap_or(0));
+        while let Some(param) = params.next() {
+            match param {
+                0 => {
+                    *self = CellStyle {
+                        link: self.link.take(),
+                        ..CellStyle::default()
+                    }
+                }
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                22 => (self.bold, self.dim) = (false, false),
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                30..=37 => self.fg = Some(Color::Ansi(param - 30)),
+                90..=97 => self.fg = Some(Color::Ansi(param - 90 + 8)),
+                40..=47 => self.bg = Some(Color::Ansi(param - 40)),
+                100..=107 => self.bg = Some(Color::Ansi(param - 100 + 8)),
+                38 | 48 => {
+                    let color = match params.next() {
+                        Some(5) => params.next().map(Color::Ansi),
+                        Some(2) => match (params.next(), params.next(), params.next()) {
+                            (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    if param == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                }
+                39 => self.fg = None,
+                49 => self.bg = None,
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the CSS for the style, or an empty string for the default.
+    fn css(&self) -> String {
+        let (mut fg, mut bg) = (self.fg.map(css_color), self.bg.map(css_color));
+        if self.reverse {
+            (fg, bg) = (
+                Some(bg.unwrap_or_else(|| BACKGROUND.to_string())),
+                Some(fg.unwrap_or_else(|| FOREGROUND.to_string())),
+            );
+        }
+        let mut css = String::new();
+        if let Some(fg) = fg {
+            let _ = write!(css, "color:{};", fg);
+        }
+        if let Some(bg) = bg {
+            let _ = write!(css, "background:{};", bg);
+        }
+        if self.bold {
+            css.push_str("font-weight:bold;");
+        }
+        if self.dim {
+            css.push_str("opacity:0.6;");
+        }
+        if self.italic {
+            css.push_str("font-style:italic;");
+        }
+        if self.underline {
+            css.push_str("text-decoration:underline;");
+        }
+        css
+    }
+}
+
+/// Returns `color` as a CSS hex color.
+fn css_color(color: Color) -> String {
+    let (r, g, b) = color.rgb();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Returns a standalone HTML page showing `ansi`, with SGR styles and OSC 8 hyperlinks kept
+/// and other escape sequences dropped.
+fn html_page(ansi: &str) -> String {
+    let mut body = String::new();
+    let mut style = CellStyle::default();
+    let mut open: Option<CellStyle> = None;
+    let mut chars = ansi.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            if c == 'm' {
+                                style.apply(&params);
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                Some(']') => {
+                    let mut command = String::new();
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' {
+                            chars.next_if_eq(&'\\');
+                            break;
+                        }
+                        command.push(c);
+                    }
+                    if let Some(link) = command.strip_prefix("8;") {
+                        let url = link.split_once(';').map_or("", |(_, url)| url);
+                        style.link = (!url.is_empty()).then(|| url.to_string());
+                    }
+                }
+                _ => {}
+            },
+            '\r' => {}
+            c if c.is_control() && c != '\n' && c != '\t' => {}
+            c => {
+                if open.as_ref() != Some(&style) {
+                    if let Some(previous) = open.take() {
+                        close_span(&mut body, &previous);
+                    }
+                    open_span(&mut body, &style);
+                    open = Some(style.clone());
+                }
+                escape_html(&mut body, c);
+            }
+        }
+    }
+    if let Some(previous) = open {
+        close_span(&mut body, &previous);
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Session</title>\n\
+         </head>\n<body style=\"margin:0;background:{bg}\">\n\
+         <pre style=\"margin:0;padding:1em;color:{fg};background:{bg};\
+         font-family:monospace\">{body}</pre>\n</body>\n</html>\n",
+        fg = FOREGROUND,
+        bg = BACKGROUND,
+        body = body
+    )
+}
+
+fn open_span(body: &mut String, style: &CellStyle) {
+    if let Some(link) = style.link.as_deref() {
+        body.push_str("<a href=\"");
+        for c in link.chars() {
+            escape_html(body, c);
+        }
+        body.push_str("\" style=\"color:inherit\">");
+    }
+    let css = style.css();
+    if !css.is_empty() {
+        let _ = write!(body, "<span style=\"{}\">", css);
+    }
+}
+
+fn close_span(body: &mut String, style: &CellStyle) {
+    if !style.css().is_empty() {
+        body.push_str("</span>");
+    }
+    if style.link.is_some() {
+        body.push_str("</a>");
+    }
+}
+
+fn escape_html(body: &mut String, c: char) {
+    match c {
+        '&' => body.push_str("&amp;"),
+        '<' => body.push_str("&lt;"),
+        '>' => body.push_str("&gt;"),
+        '"' => body.push_str("&quot;"),
+        c => body.push(c),
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::PathBuf,
+};
+
+#[cfg(feature = "encryption")]
+use crate::history_cipher::HistoryCipher;
+use crate::{Error, ErrorContext, HistoryEntry, HistoryStore, InternalError, Result};
+
+/// History stored in a plain text file.
+///
+/// The file uses bash's timestamped layout: a `#<seconds>` line before each entry, with
+/// backslashes, newlines and a leading `#` in the entry escaped with `\`. Appending merges
+/// with entries written by other running instances by timestamp, and the file is replaced
+/// atomically. Appending and truncating hold an advisory lock on a `.lock` file beside it, so
+/// instances saving at the same time don't lose each other's entries. Files are created
+/// readable only by their owner.
+///
+/// With the `encryption` feature, the file can be encrypted so entries aren't left on disk in
+/// plain text.
+pub struct FileHistory {
+    path: PathBuf,
+    #[cfg(feature = "encryption")]
+    cipher: Option<HistoryCipher>,
+}
+
+impl FileHistory {
+    /// Create a store backed by the file at `path`. The file and its directory are created on
+    /// first append.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            #[cfg(feature = "encryption")]
+            cipher: None,
+        }
+    }
+
+    /// Encrypt the file with ChaCha20-Poly1305 using `key`, e.g. one kept in the system
+    /// keyring.
+    ///
+    /// Loading fails if the file wasn't encrypted with the same key, rather than discarding
+    /// its entries. A plain text file must be removed or converted first.
+    #[cfg(feature = "encryption")]
+    pub fn encrypt_with_key(mut self, key: &[u8; 32]) -> Self {
+        self.cipher = Some(HistoryCipher::from_key(key));
+        self
+    }
+
+    /// Encrypt the file with ChaCha20-Poly1305 using a key derived from `passphrase` with
+    /// Argon2. The salt is stored in the file, so the same passphrase opens it later.
+    ///
+    /// Deriving the key is deliberately slow, so this is done once here. Returns an error if
+    /// the file can't be read. Loading fails like with `encrypt_with_key` for a different
+    /// passphrase.
+    #[cfg(feature = "encryption")]
+    pub fn encrypt_with_passphrase(mut self, passphrase: &str) -> Result<Self> {
+        let _lock = self.lock()?;
+        let existing = self.read_bytes()?;
+        let cipher =
+            HistoryCipher::from_passphrase(passphrase, existing.as_deref()).map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::new(format!(
+                    "unable to derive history key: {}",
+                    e
+                ))))
+            })?;
+        self.cipher = Some(cipher);
+        // Write the new salt now, so other instances starting before the first append derive
+        // the same key.
+        if existing.is_none() {
+            self.write(&[])?;
+        }
+        Ok(self)
+    }
+
+    /// Take an exclusive lock on the lock file beside the history file, blocking until other
+    /// instances release it. The lock is released when the returned file is dropped.
+    ///
+    /// The history file itself isn't locked, since it's replaced rather than written in place.
+    fn lock(&self) -> Result<File> {
+        let dir = self.path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        dir.map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                OpenOptions::new()
+                    .create(true)
+                    .truncate(false)
+                    .write(true)
+                    .mode(0o600)
+                    .open(self.sibling(".lock"))
+            })
+            .and_then(|file| file.lock().map(|_| file))
+            .map_err(|e| {
+                Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                    format!("unable to lock history file {}", self.path.display()),
+                    e,
+                )))
+            })
+    }
+
+    /// Returns the path of the file beside the history file named with `suffix` appended, so
+    /// `history.txt` and `history.log` don't share one.
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self
+            .path
+            .file_name()
+            .map(OsString::from)
+            .unwrap_or_default();
+        name.push(suffix);
+        self.path.with_file_name(name)
+    }
+
+    /// Returns the file's contents, or `None` if it's missing.
+    fn read_bytes(&self) -> Result<Option<Vec<u8>>> {
+        match fs::read(&self.path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(self.read_error(e)),
+        }
+    }
+
+    fn read_error(&self, source: impl std::error::Error + Send + Sync + 'static) -> Error {
+        Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+            format!("unable to load history from {}", self.path.display()),
+            source,
+        )))
+    }
+
+    /// Read entries from the file. A missing file has no entries.
+    fn read(&self) -> Result<Vec<HistoryEntry>> {
+        let Some(contents) = self.read_bytes()? else {
+            return Ok(Vec::new());
+        };
+        #[cfg(feature = "encryption")]
+        let contents = match &self.cipher {
+            Some(cipher) => cipher.decrypt(&contents).ok_or_else(|| {
+                Error::Internal(InternalError::IoRead(ErrorContext::new(format!(
+                    "unable to decrypt history in {}: wrong key or not encrypted",
+                    self.path.display()
+                ))))
+            })?,
+            None => contents,
+        };
+        let contents = String::from_utf8(contents).map_err(|e| self.read_error(e))?;
+
+        let mut entries = Vec::new();
+        let mut timestamp = 0;
+        for line in contents.lines() {
+            match line.strip_prefix('#').map(str::parse::<u64>) {
+                Some(Ok(t)) => timestamp = t,
+                _ => {
+                    entries.push(HistoryEntry {
+                        text: unescape(line),
+                        timestamp,
+                    });
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Replace the file contents with `entries`. Call with the lock held.
+    fn write(&self, entries: &[HistoryEntry]) -> Result<()> {
+        let contents: String = entries
+            .iter()
+            .map(|e| format!("#{}\n{}\n", e.timestamp, escape(&e.text)))
+            .collect();
+        #[cfg(feature = "encryption")]
+        let contents = match &self.cipher {
+            Some(cipher) => cipher.encrypt(contents.as_bytes()).ok_or_else(|| {
+                Error::Internal(InternalError::IoWrite(ErrorContext::new(format!(
+                    "unable to encrypt history for {}",
+                    self.path.display()
+                ))))
+            })?,
+            None => contents.into_bytes(),
+        };
+        let tmp = self.sibling(".tmp");
+        let dir = self.path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        dir.map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                OpenOptions::new()
+                    .create(true)
+                    .truncate(true)
+                    .write(true)
+                    .mode(0o600)
+                    .open(&tmp)
+            })
+            .and_then(|mut file| file.write_all(contents.as_ref()))
+            .and_then(|_| fs::rename(&tmp, &self.path))
+            .map_err(|e| {
+                Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                    format!("unable to save history to {}", self.path.display()),
+                    e,
+                )))
+            })
+    }
+}
+
+impl HistoryStore for FileHistory {
+    fn append(&mut self, entries: &[HistoryEntry]) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut merged = self.read()?;
+        merged.extend_from_slice(entries);
+        merged.sort_by_key(|e| e.timestamp);
+        self.write(&merged)
+    }
+
+    fn iter(&self) -> Result<Box<dyn Iterator<Item = HistoryEntry> + '_>> {
+        Ok(Box::new(self.read()?.into_iter()))
+    }
+
+    fn truncate(&mut self, len: usize) -> Result<()> {
+        let _lock = self.lock()?;
+        let entries = self.read()?;
+        let start = entries.len().saturating_sub(len);
+        self.write(&entries[start..])
+    }
+}
+
+/// Escape an entry so it fits on one line and can't be mistaken for a timestamp.
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('\n', "\\n");
+    if escaped.starts_with('#') {
+        format!("\\{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                chars.next();
+                unescaped.push('\n');
+            }
+            ('\\', Some(escaped @ ('\\' | '#'))) => {
+                chars.next();
+                unescaped.push(escaped);
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    #[test]
+    fn files_are_private_and_named_after_the_whole_file_name() {
+        let dir = std::env::temp_dir().join(format!("repl_lib_history_{}", std::process::id()));
+        let path = dir.join("history.txt");
+        let mut store = FileHistory::new(&path);
+        let entry = HistoryEntry {
+            text: "1 + 2".to_string(),
+            timestamp: 1,
+        };
+        store.append(&[entry]).unwrap();
+
+        let lock = dir.join("history.txt.lock");
+        for file in [&path, &lock] {
+            let mode = fs::metadata(file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", file.display());
+        }
+        assert!(!dir.join("history.lock").exists());
+        assert!(!dir.join("history.txt.tmp").exists());
+        assert_eq!(store.iter().unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use tree_sitter::{Language, Node, Parser};
+
+use crate::{Validation, ValidatorFunc};
+
+/// Returns a validator deciding whether the input is complete by parsing it with a
+/// tree-sitter grammar, e.g. `tree_sitter_python::LANGUAGE.into()`.
+///
+/// Input that parses without errors is `Validation::Complete`, unless it ends with an empty
+/// node such as the body of Python's `def f():`. That, or a syntax error running to the end
+/// of the input, such as a missing closing bracket, makes it `Validation::Incomplete`, so
+/// Enter continues it on a new line. An error before the end is
+/// `Validation::Invalid`, with its line and column. If the grammar can't be loaded, e.g.
+/// because it was built for an incompatible version of tree-sitter, all input is complete.
+pub fn grammar_validator(language: Language) -> ValidatorFunc {
+    let mut parser = Parser::new();
+    let loaded = parser.set_language(&language).is_ok();
+    Box::new(move |text| {
+        if !loaded || text.trim().is_empty() {
+            return Validation::Complete;
+        }
+        let Some(tree) = parser.parse(text, None) else {
+            return Validation::Complete;
+        };
+        let root = tree.root_node();
+        let Some(error) = first_error(root) else {
+            return if ends_empty(root) {
+                Validation::Incomplete
+            } else {
+                Validation::Complete
+            };
+        };
+        if error.end_byte() >= text.trim_end().len() {
+            return Validation::Incomplete;
+        }
+        let position = error.start_position();
+        let message = if error.is_missing() {
+            format!("missing '{}'", error.kind())
+        } else {
+            "syntax error".to_string()
+        };
+        Validation::Invalid {
+            message: format!(
+                "{} at line {}, column {}",
+                message,
+                position.row + 1,
+                position.column + 1
+            ),
+        }
+    })
+}
+
+/// Returns whether the last node in `node`'s subtree is empty, like a block waiting for its
+/// first statement.
+fn ends_empty(node: Node) -> bool {
+    let mut last = node;
+    while let Some(child) = last.child(last.child_count().saturating_sub(1)) {
+        if child.is_named() && child.child_count() == 0 && child.byte_range().is_empty() {
+            return true;
+        }
+        last = child;
+    }
+    false
+}
+
+/// Returns the first error or missing node in `node`'s subtree, in document order.
+fn first_error(node: Node) -> Option<Node> {
+    if node.is_error() || node.is_missing() {
+        return Some(node);
+    }
+    if !node.has_error() {
+        return None;
+    }
+    (0..node.child_count()).find_map(|i| first_error(node.child(i)?))
+}
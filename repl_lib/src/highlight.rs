@@ -0,0 +1,48 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use crate::{Repl, StyledText, selection::reverse_range};
+
+/// Function type for styling the input as it's drawn, e.g. for syntax highlighting or
+/// matching brackets.
+///
+/// Called with the input and the byte offset of the cursor. The returned text must have the
+/// same characters as the input; otherwise the input is drawn unstyled.
+pub type HighlightFunc = Box<dyn FnMut(&str, usize) -> StyledText>;
+
+impl<T> Repl<T> {
+    /// Returns the physical lines of the current input, styled by the highlighter if one is
+    /// set, with any selection in reverse video.
+    pub(crate) fn highlighted_lines(&mut self) -> Vec<StyledText> {
+        let line = &self.lines[self.current_line];
+        let highlighted = self
+            .highlighter
+            .as_mut()
+            .map(|highlighter| highlighter(&line.text, line.cursor_pos))
+            .filter(|styled| styled.text() == line.text);
+        let highlighted = match (highlighted, self.selection_range()) {
+            (highlighted, Some(range)) => reverse_range(
+                &highlighted.unwrap_or_else(|| StyledText::from(line.text.as_str())),
+                range,
+            ),
+            (Some(highlighted), None) => highlighted,
+            (None, None) => return line.text.split('\n').map(StyledText::from).collect(),
+        };
+
+        let mut lines = vec![StyledText::new()];
+        for span in highlighted.spans() {
+            for (i, part) in span.text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(StyledText::new());
+                }
+                if !part.is_empty()
+                    && let Some(last) = lines.last_mut()
+                {
+                    last.push(part, span.style);
+                }
+            }
+        }
+        lines
+    }
+}
@@ -0,0 +1,478 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Error, Line, Result, UserError};
+
+/// Number of newer entries after which a submission counts half as much toward ranking.
+const RECENCY_HALF_LIFE: f64 = 100.0;
+
+/// A submitted line of input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Text of the entry. May contain newlines for multi-line input.
+    pub text: String,
+    /// Seconds since the Unix epoch when the entry was submitted.
+    pub timestamp: u64,
+}
+
+impl HistoryEntry {
+    /// Create an entry timestamped with the current time.
+    pub fn new(text: impl Into<String>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            text: text.into(),
+            timestamp,
+        }
+    }
+}
+
+/// Persistent storage for history entries.
+///
+/// Implement this to keep history somewhere other than a plain file (e.g. SQLite or an
+/// application database). `FileHistory`, enabled by the `persistence` feature, is the
+/// in-tree implementation.
+pub trait HistoryStore: Send {
+    /// Append entries submitted since the last call.
+    fn append(&mut self, entries: &[HistoryEntry]) -> Result<()>;
+
+    /// Returns all stored entries, oldest first.
+    fn iter(&self) -> Result<Box<dyn Iterator<Item = HistoryEntry> + '_>>;
+
+    /// Returns stored entries containing `query`, oldest first.
+    fn search(&self, query: &str) -> Result<Vec<HistoryEntry>> {
+        Ok(self.iter()?.filter(|e| e.text.contains(query)).collect())
+    }
+
+    /// Keep only the newest `len` entries.
+    fn truncate(&mut self, len: usize) -> Result<()>;
+}
+
+/// Timestamped record of submitted lines, optionally persisted to a `HistoryStore`.
+pub(crate) struct History {
+    entries: Vec<HistoryEntry>,
+    store: Option<Box<dyn HistoryStore>>,
+    /// Number of leading entries already present in the store.
+    saved: usize,
+    /// Number of entries the store keeps, oldest removed first.
+    max_len: Option<usize>,
+}
+
+impl History {
+    /// Create a history, loading existing entries from `store` if given.
+    ///
+    /// With `max_len`, the store is trimmed to its newest `max_len` entries on load and save.
+    pub(crate) fn open(
+        mut store: Option<Box<dyn HistoryStore>>,
+        max_len: Option<usize>,
+    ) -> Result<Self> {
+        if let (Some(store), Some(max_len)) = (store.as_mut(), max_len) {
+            store.truncate(max_len)?;
+        }
+        let entries: Vec<HistoryEntry> = match &store {
+            Some(store) => store.iter()?.collect(),
+            None => Vec::new(),
+        };
+        let saved = entries.len();
+
+        Ok(Self {
+            entries,
+            store,
+            saved,
+            max_len,
+        })
+    }
+
+    /// Returns all entries, oldest first.
+    pub(crate) fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Record a submitted line, returning its index. Empty lines are not recorded.
+    pub(crate) fn push(&mut self, text: &str) -> Option<usize> {
+        if text.trim().is_empty() {
+            return None;
+        }
+        self.entries.push(HistoryEntry::new(text));
+        Some(self.entries.len() - 1)
+    }
+
+    /// Insert entries that aren't saved to the store, before any unsaved entries.
+    pub(crate) fn preload(&mut self, texts: Vec<String>) {
+        let entries: Vec<HistoryEntry> = texts
+            .into_iter()
+            .filter(|text| !text.trim().is_empty())
+            .map(HistoryEntry::new)
+            .collect();
+        let count = entries.len();
+        self.entries.splice(self.saved..self.saved, entries);
+        self.saved += count;
+    }
+
+    /// Returns the distinct entries starting with `prefix`, best first.
+    ///
+    /// Each submission of an entry adds to its score, weighted by how recent it is, so both
+    /// frequently and recently used entries rank high.
+    pub(crate) fn suggestions(&self, prefix: &str) -> Vec<String> {
+        // Score and age of the newest submission, per entry.
+        let mut scores: HashMap<&str, (f64, usize)> = HashMap::new();
+        for (age, entry) in self.entries.iter().rev().enumerate() {
+            if entry.text.starts_with(prefix) && !entry.text.trim().is_empty() {
+                let weight = 0.5f64.powf(age as f64 / RECENCY_HALF_LIFE);
+                scores.entry(&entry.text).or_insert((0.0, age)).0 += weight;
+            }
+        }
+        let mut ranked: Vec<(&str, (f64, usize))> = scores.into_iter().collect();
+        ranked.sort_by(|(_, (a, a_age)), (_, (b, b_age))| b.total_cmp(a).then(a_age.cmp(b_age)));
+        ranked
+            .into_iter()
+            .map(|(text, _)| text.to_string())
+            .collect()
+    }
+
+    /// Remove all entries. The store is left unchanged.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.saved = 0;
+    }
+
+    /// Replace the text of an unsaved entry (e.g. after history expansion).
+    pub(crate) fn replace(&mut self, index: usize, text: &str) {
+        if index >= self.saved
+            && let Some(entry) = self.entries.get_mut(index)
+        {
+            entry.text = text.to_string();
+        }
+    }
+
+    /// Append entries added since the last save to the store.
+    pub(crate) fn save(&mut self) -> Result<()> {
+        let Some(store) = self.store.as_mut() else {
+            return Ok(());
+        };
+        if self.saved == self.entries.len() {
+            return Ok(());
+        }
+
+        store.append(&self.entries[self.saved..])?;
+        if let Some(max_len) = self.max_len {
+            store.truncate(max_len)?;
+        }
+        self.saved = self.entries.len();
+        Ok(())
+    }
+}
+
+/// Thread-safe handle to a history shared between `Repl` instances.
+///
+/// Entries submitted in one `Repl` can be recalled in every other `Repl` built with a clone
+/// of the same handle.
+#[derive(Clone)]
+pub struct SharedHistory {
+    inner: Arc<Mutex<History>>,
+}
+
+impl SharedHistory {
+    /// Create an in-memory shared history.
+    pub fn new() -> Self {
+        Self::from(History {
+            entries: Vec::new(),
+            store: None,
+            saved: 0,
+            max_len: None,
+        })
+    }
+
+    /// Create a shared history, loading existing entries from `store`.
+    pub fn open(store: Box<dyn HistoryStore>) -> Result<Self> {
+        History::open(Some(store), None).map(Self::from)
+    }
+
+    /// Lock the history, recovering it if another thread panicked while holding the lock.
+    pub(crate) fn lock(&self) -> MutexGuard<'_, History> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for SharedHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<History> for SharedHistory {
+    fn from(history: History) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(history)),
+        }
+    }
+}
+
+/// How `Repl::history_search` matches its pattern against entries.
+///
+/// Non-exhaustive, since `Regex` is only there with the `regex` feature.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SearchMode {
+    /// Entries containing the pattern.
+    Substring,
+    /// Entries matching a shell glob as a whole: `*` matches any text, `?` any character,
+    /// and `[abc]`, `[a-z]` or `[!abc]` a character in or outside a set. `\` escapes the
+    /// next character.
+    Glob,
+    /// Entries containing a match of a regular expression, in the syntax of the `regex`
+    /// crate.
+    #[cfg(feature = "regex")]
+    Regex,
+}
+
+/// Tests whether an entry's text matches a search pattern.
+pub(crate) type Matcher = Box<dyn Fn(&str) -> bool>;
+
+/// Returns a function testing whether an entry's text matches `pattern` in `mode`, failing
+/// with a user error if it isn't a valid regular expression.
+pub(crate) fn matcher(pattern: &str, mode: SearchMode) -> Result<Matcher> {
+    match mode {
+        SearchMode::Substring => {
+            let pattern = pattern.to_string();
+            Ok(Box::new(move |text| text.contains(&pattern)))
+        }
+        SearchMode::Glob => {
+            let pattern: Vec<char> = pattern.chars().collect();
+            Ok(Box::new(move |text| {
+                let text: Vec<char> = text.chars().collect();
+                glob_match(&pattern, &text)
+            }))
+        }
+        #[cfg(feature = "regex")]
+        SearchMode::Regex => {
+            let regex = regex::Regex::new(pattern)
+                .map_err(|e| Error::User(UserError::new(format!("invalid pattern: {}", e))))?;
+            Ok(Box::new(move |text| regex.is_match(text)))
+        }
+    }
+}
+
+/// Returns whether all of `text` matches the glob `pattern`.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Where to retry after a mismatch: just past the last `*`, matching one more character.
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        let len = match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(1),
+            Some('[') => match_class(&pattern[p..], text[t]),
+            Some('\\') if p + 1 < pattern.len() => (pattern[p + 1] == text[t]).then_some(2),
+            Some(&c) => (c == text[t]).then_some(1),
+            None => None,
+        };
+        match (len, star) {
+            (Some(len), _) => {
+                p += len;
+                t += 1;
+            }
+            (None, Some((after_star, from))) => {
+                p = after_star;
+                t = from + 1;
+                star = Some((after_star, t));
+            }
+            (None, None) => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Match `c` against the bracket expression at the start of `pattern`, returning its length
+/// if it matches. A `[` without a closing `]` stands for itself.
+fn match_class(pattern: &[char], c: char) -> Option<usize> {
+    let negated = matches!(pattern.get(1), Some('!' | '^'));
+    let start = if negated { 2 } else { 1 };
+    // A `]` first in the set is part of it.
+    let Some(end) = pattern
+        .iter()
+        .skip(start + 1)
+        .position(|&b| b == ']')
+        .map(|i| i + start + 1)
+    else {
+        return (c == '[').then_some(1);
+    };
+    let set = &pattern[start..end];
+    let mut found = false;
+    let mut i = 0;
+    while i < set.len() {
+        if i + 2 < set.len() && set[i + 1] == '-' {
+            found |= (set[i]..=set[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= set[i] == c;
+            i += 1;
+        }
+    }
+    (found != negated).then_some(end + 1)
+}
+
+/// Perform csh-style history expansion on a line.
+///
+/// Supports `!!` (last entry), `!N` (entry N, starting at 1), `!-N` (Nth previous entry)
+/// and `!prefix` (most recent entry starting with `prefix`). A `!` followed by whitespace,
+/// `=` or `(`, escaped with `\`, or inside single quotes is left untouched.
+pub(crate) fn expand(line: &str, history: &[HistoryEntry]) -> Result<String> {
+    let mut expanded = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut in_single_quotes = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_single_quotes = !in_single_quotes;
+                expanded.push(c);
+            }
+            '\\' if chars.peek() == Some(&'!') => {
+                chars.next();
+                expanded.push('!');
+            }
+            '!' if !in_single_quotes => match chars.peek() {
+                None => expanded.push(c),
+                Some(&next) if next.is_whitespace() || next == '=' || next == '(' => {
+                    expanded.push(c)
+                }
+                Some('!') => {
+                    chars.next();
+                    expanded.push_str(last(history, "!!")?);
+                }
+                Some(_) => {
+                    let mut designator = String::new();
+                    while let Some(&next) = chars.peek() {
+                        if next.is_whitespace() {
+                            break;
+                        }
+                        designator.push(next);
+                        chars.next();
+                    }
+                    expanded.push_str(lookup(history, &designator)?);
+                }
+            },
+            _ => expanded.push(c),
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Returns the index of the closest entry before `from` that starts with `prefix`.
+pub(crate) fn search_prev(history: &[Line], from: usize, prefix: &str) -> Option<usize> {
+    let current = history.get(from).map(|l| l.text());
+    (0..from.min(history.len()))
+        .rev()
+        .find(|&i| history[i].text().starts_with(prefix) && Some(history[i].text()) != current)
+}
+
+/// Returns the index of the closest entry after `from` that starts with `prefix`.
+pub(crate) fn search_next(history: &[Line], from: usize, prefix: &str) -> Option<usize> {
+    let current = history.get(from).map(|l| l.text());
+    (from + 1..history.len())
+        .find(|&i| history[i].text().starts_with(prefix) && Some(history[i].text()) != current)
+}
+
+/// Returns the most recent history entry.
+fn last<'a>(history: &'a [HistoryEntry], event: &str) -> Result<&'a str> {
+    history
+        .last()
+        .map(|e| e.text.as_str())
+        .ok_or_else(|| event_not_found(event))
+}
+
+/// Resolve a `!N`, `!-N` or `!prefix` designator.
+fn lookup<'a>(history: &'a [HistoryEntry], designator: &str) -> Result<&'a str> {
+    let event = format!("!{}", designator);
+
+    if let Ok(n) = designator.parse::<isize>() {
+        let index = if n < 0 {
+            history.len().checked_sub(n.unsigned_abs())
+        } else {
+            n.unsigned_abs().checked_sub(1)
+        };
+        return index
+            .and_then(|i| history.get(i))
+            .map(|e| e.text.as_str())
+            .ok_or_else(|| event_not_found(&event));
+    }
+
+    history
+        .iter()
+        .rev()
+        .map(|e| e.text.as_str())
+        .find(|text| text.starts_with(designator))
+        .ok_or_else(|| event_not_found(&event))
+}
+
+fn event_not_found(event: &str) -> Error {
+    Error::User(UserError::new(format!("{}: event not found", event)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str, text: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let text: Vec<char> = text.chars().collect();
+        glob_match(&pattern, &text)
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob("ls -la", "ls -la"));
+        assert!(!glob("ls", "ls -la"));
+        assert!(glob("ls*", "ls -la"));
+        assert!(glob("ls*", "ls"));
+        assert!(glob("*", ""));
+        assert!(glob("git *commit*", "git log; git commit -m x"));
+        assert!(!glob("git *commit", "git commit -m x"));
+        assert!(glob("c?t", "cat"));
+        assert!(glob("c?t", "c\u{e9}t"));
+        assert!(!glob("c?t", "ct"));
+        assert!(glob("a**b", "axxb"));
+    }
+
+    #[test]
+    fn glob_classes() {
+        assert!(glob("[bc]at", "cat"));
+        assert!(!glob("[bc]at", "hat"));
+        assert!(glob("x[0-9]", "x7"));
+        assert!(!glob("x[0-9]", "xa"));
+        assert!(glob("x[a-cx-z]", "xy"));
+        assert!(glob("[!bc]at", "hat"));
+        assert!(!glob("[!bc]at", "cat"));
+        assert!(glob("[^bc]at", "hat"));
+        // A `]` first in the set is part of it, and an unclosed `[` stands for itself.
+        assert!(glob("[]]", "]"));
+        assert!(glob("[!]]", "a"));
+        assert!(glob("a[b", "a[b"));
+        assert!(!glob("a[b", "ab"));
+    }
+
+    #[test]
+    fn glob_escapes() {
+        assert!(glob("\\*", "*"));
+        assert!(!glob("\\*", "x"));
+        assert!(glob("what\\?", "what?"));
+        assert!(!glob("what\\?", "whats"));
+        assert!(glob("\\[a]", "[a]"));
+        assert!(glob("end\\", "end\\"));
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use term_manager::{Attribute, clear, cursor, screen};
+
+use crate::{Error, ErrorContext, InternalError, Repl, Result, Style, StyledText};
+
+impl<T> Repl<T> {
+    /// Show history on the alternate screen, newest first, filtered as the user types.
+    ///
+    /// Up/Down and PgUp/PgDn move the selection, Enter loads the selected entry into the
+    /// input for editing, and Esc, Ctrl-C or Ctrl-G close the list without changing it.
+    pub(crate) fn browse_history(&mut self) -> Result<()> {
+        let Some(tmanager) = self.tmanager.as_ref() else {
+            return Ok(());
+        };
+        let size = tmanager
+            .size()
+            .ok()
+            .filter(|&(rows, cols)| rows >= 3 && cols > 0);
+        let (Some((rows, cols)), true) = (size, tmanager.caps().alternate_screen) else {
+            return self.bell();
+        };
+        let mut entries: Vec<String> = Vec::new();
+        for entry in self.history.lock().entries().iter().rev() {
+            if !entries.contains(&entry.text) {
+                entries.push(entry.text.clone());
+            }
+        }
+        if entries.is_empty() {
+            return self.bell();
+        }
+
+        self.flush()?;
+        self.tmanager_mut()?
+            .enter_alternate_screen()
+            .map_err(browser_error)?;
+        if self.status.is_some() {
+            self.write(screen::RESET_SCROLL_REGION)?;
+        }
+        // The query line and the status line take a row each.
+        let height = rows as usize - 2;
+        let mut query = String::new();
+        let mut selected = 0;
+        let mut top = 0;
+        let chosen = loop {
+            let lowercase = query.to_lowercase();
+            let matches: Vec<&String> = entries
+                .iter()
+                .filter(|text| text.to_lowercase().contains(&lowercase))
+                .collect();
+            selected = selected.min(matches.len().saturating_sub(1));
+            if selected < top {
+                top = selected;
+            } else if selected >= top + height {
+                top = selected + 1 - height;
+            }
+            self.draw_browser(&query, &matches, selected, top, height, cols as usize)?;
+
+            let sequence = self.read_sequence()?;
+            match sequence.as_slice() {
+                b"\r" | b"\n" => break matches.get(selected).map(|text| text.to_string()),
+                b"\x1b" | b"\x03" | b"\x07" => break None,
+                b"\x1b[A" | b"\x1bOA" | b"\x10" => selected = selected.saturating_sub(1),
+                b"\x1b[B" | b"\x1bOB" | b"\x0e" => selected += 1,
+                b"\x1b[5~" => selected = selected.saturating_sub(height),
+                b"\x1b[6~" => selected += height,
+                b"\x7f" | b"\x08" => {
+                    query.pop();
+                    selected = 0;
+                }
+                [0x1b, ..] => {}
+                bytes => {
+                    let text = String::from_utf8_lossy(bytes);
+                    if !text.chars().any(char::is_control) {
+                        query.push_str(&text);
+                        selected = 0;
+                    }
+                }
+            }
+        };
+        self.tmanager_mut()?
+            .leave_alternate_screen()
+            .map_err(browser_error)?;
+        let sequence = self.status_sequence();
+        self.write(&sequence)?;
+
+        if let Some(text) = chosen {
+            self.current_line = self.lines.len() - 1;
+            let line = self.current_line_mut()?;
+            line.cursor_pos = text.len();
+            line.text = text;
+        }
+        self.redraw_current_line()
+    }
+
+    /// Draw the query, the matching entries from `top` with `selected` highlighted, and a
+    /// status line.
+    fn draw_browser(
+        &mut self,
+        query: &str,
+        matches: &[&String],
+        selected: usize,
+        top: usize,
+        height: usize,
+        cols: usize,
+    ) -> Result<()> {
+        let bold = Style::new().attr(Attribute::Bold);
+        let reverse = Style::new().attr(Attribute::Reverse);
+        let mut out = String::from(clear::SCREEN);
+        let header = StyledText::new().span("history: ", bold).plain(query);
+        out.push_str(&header.truncate(cols).render(self.color_depth));
+        for (i, text) in matches.iter().enumerate().skip(top).take(height) {
+            // Multi-line entries are shown on one row.
+            let text = text.replace(['\n', '\t'], " ");
+            let style = if i == selected { reverse } else { Style::new() };
+            let row = StyledText::new().span(text, style).truncate(cols);
+            out.push('\n');
+            out.push_str(&row.render(self.color_depth));
+        }
+        out.push_str(&"\n".repeat(height - matches.len().saturating_sub(top).min(height)));
+        let status = format!(
+            "{}/{} (enter: select, esc: cancel)",
+            matches.len().min(selected + 1),
+            matches.len()
+        );
+        let status = StyledText::new().span(status, reverse).truncate(cols - 1);
+        out.push('\n');
+        out.push_str(&status.render(self.color_depth));
+        // Leave the cursor after the query.
+        out.push_str(&cursor::move_to(0, header.width().min(cols - 1) as u16));
+        self.write(&out)?;
+        self.flush()
+    }
+}
+
+fn browser_error(e: term_manager::Error) -> Error {
+    Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+        "unable to switch screens for the history browser",
+        e,
+    )))
+}
@@ -0,0 +1,89 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, Payload, rand_core::RngCore},
+};
+
+/// Marks an encrypted history file and its layout version.
+const MAGIC: &[u8] = b"REPLHIST\x01";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN;
+
+/// Encrypts history files with ChaCha20-Poly1305.
+///
+/// An encrypted file is the magic bytes, the salt the key was derived with, a random nonce,
+/// then the ciphertext. The header is authenticated along with the entries, so a file
+/// that was tampered with or encrypted with another key fails to decrypt.
+pub(crate) struct HistoryCipher {
+    cipher: ChaCha20Poly1305,
+    salt: [u8; SALT_LEN],
+}
+
+impl HistoryCipher {
+    /// Create a cipher using `key` as is. The salt is written but unused.
+    pub(crate) fn from_key(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(key)),
+            salt: [0; SALT_LEN],
+        }
+    }
+
+    /// Create a cipher with a key derived from `passphrase` with Argon2, salted with the salt
+    /// in `existing` if it's an encrypted file, or a new random one.
+    pub(crate) fn from_passphrase(
+        passphrase: &str,
+        existing: Option<&[u8]>,
+    ) -> Result<Self, argon2::Error> {
+        let salt = existing.and_then(salt_of).unwrap_or_else(|| {
+            let mut salt = [0; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            salt
+        });
+        let mut key = [0; 32];
+        Argon2::default().hash_password_into(passphrase.as_bytes(), &salt, &mut key)?;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        key.fill(0);
+        Ok(Self { cipher, salt })
+    }
+
+    /// Returns `plaintext` encrypted with a new nonce, with the header, or `None` if it's too
+    /// large for the cipher.
+    pub(crate) fn encrypt(&self, plaintext: &[u8]) -> Option<Vec<u8>> {
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&self.salt);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: plaintext,
+            aad: &header,
+        };
+        let ciphertext = self.cipher.encrypt(&nonce, payload).ok()?;
+        let mut contents = header;
+        contents.extend_from_slice(&nonce);
+        contents.extend_from_slice(&ciphertext);
+        Some(contents)
+    }
+
+    /// Returns the plaintext of an encrypted file, or `None` if it isn't one or the key
+    /// doesn't match.
+    pub(crate) fn decrypt(&self, contents: &[u8]) -> Option<Vec<u8>> {
+        salt_of(contents)?;
+        let (header, rest) = contents.split_at(HEADER_LEN);
+        let (nonce, ciphertext) = rest.split_at_checked(NONCE_LEN)?;
+        let payload = Payload {
+            msg: ciphertext,
+            aad: header,
+        };
+        self.cipher.decrypt(Nonce::from_slice(nonce), payload).ok()
+    }
+}
+
+/// Returns the salt from an encrypted file's header, or `None` if it isn't one.
+fn salt_of(contents: &[u8]) -> Option<[u8; SALT_LEN]> {
+    let salt = contents.strip_prefix(MAGIC)?.get(..SALT_LEN)?;
+    salt.try_into().ok()
+}
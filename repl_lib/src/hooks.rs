@@ -0,0 +1,133 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    mem,
+    time::{Duration, Instant},
+};
+
+use crate::{Repl, Result};
+
+/// Function type for a hook run by the REPL loop, with access to the `Repl`.
+pub type HookFunc<T = String> = Box<dyn FnMut(&mut Repl<T>) -> Result<()>>;
+
+/// Function type for a hook run when the terminal gains or loses focus, with access to the
+/// `Repl` and whether it now has focus.
+pub type FocusFunc<T = String> = Box<dyn FnMut(&mut Repl<T>, bool) -> Result<()>>;
+
+/// A hook run every `interval` while waiting for input.
+pub(crate) struct TickHook<T> {
+    interval: Duration,
+    due: Instant,
+    hook: HookFunc<T>,
+}
+
+impl<T> Repl<T> {
+    /// Register a hook that runs before each prompt is drawn by `run` or `lines`, e.g. to
+    /// refresh data shown in the prompt or status line.
+    ///
+    /// Hooks run in the order they were registered. An error ends the loop like an error
+    /// from reading input.
+    pub fn register_pre_input_hook(&mut self, hook: HookFunc<T>) {
+        self.pre_input_hooks.push(hook);
+    }
+
+    /// Register a hook that runs after `run` prints each result or evaluation error, e.g. to
+    /// write a checkpoint or update the status line.
+    ///
+    /// Hooks run in the order they were registered. An error ends `run`.
+    pub fn register_post_output_hook(&mut self, hook: HookFunc<T>) {
+        self.post_output_hooks.push(hook);
+    }
+
+    /// Register a hook that runs when the `Repl` is shut down with `close`, e.g. to save
+    /// application state. Hooks don't run if the `Repl` is only dropped.
+    ///
+    /// Hooks run in the order they were registered. `close` still shuts down after an error
+    /// and returns the first one.
+    pub fn register_exit_hook(&mut self, hook: HookFunc<T>) {
+        self.exit_hooks.push(hook);
+    }
+
+    /// Register a hook that runs while editing when the terminal gains or loses focus, e.g.
+    /// to dim the prompt with `set_prompt` or pause a background refresh. Needs focus
+    /// reporting enabled with `ReplBuilder::focus_reporting`.
+    ///
+    /// The input is redrawn after the hooks run. An error ends the read like an error from
+    /// reading input.
+    pub fn register_focus_hook(&mut self, hook: FocusFunc<T>) {
+        self.focus_hooks.push(hook);
+    }
+
+    /// Register a hook that runs every `interval` while waiting for a key, e.g. to update a
+    /// clock in the prompt with `set_prompt` or refresh a connection status line.
+    ///
+    /// The input is redrawn after the hooks run. Hooks don't run during evaluation or in
+    /// batch mode. An error ends the read like an error from reading input.
+    pub fn register_tick_hook(&mut self, interval: Duration, hook: HookFunc<T>) {
+        self.tick_hooks.push(TickHook {
+            interval,
+            due: Instant::now() + interval,
+            hook,
+        });
+    }
+
+    /// Returns `false` if the terminal reported losing focus and hasn't regained it.
+    pub fn has_focus(&self) -> bool {
+        self.focused
+    }
+
+    pub(crate) fn run_pre_input_hooks(&mut self) -> Result<()> {
+        self.run_hooks(|repl| &mut repl.pre_input_hooks)
+    }
+
+    pub(crate) fn run_post_output_hooks(&mut self) -> Result<()> {
+        self.run_hooks(|repl| &mut repl.post_output_hooks)
+    }
+
+    pub(crate) fn run_exit_hooks(&mut self) -> Result<()> {
+        self.run_hooks(|repl| &mut repl.exit_hooks)
+    }
+
+    pub(crate) fn run_focus_hooks(&mut self) -> Result<()> {
+        let focused = self.focused;
+        let mut running = mem::take(&mut self.focus_hooks);
+        let result = running.iter_mut().try_for_each(|hook| hook(self, focused));
+        let added = mem::replace(&mut self.focus_hooks, running);
+        self.focus_hooks.extend(added);
+        result
+    }
+
+    /// Returns when the next tick hook is due.
+    pub(crate) fn next_tick(&self) -> Option<Instant> {
+        self.tick_hooks.iter().map(|tick| tick.due).min()
+    }
+
+    /// Run the tick hooks that are due and redraw the input.
+    pub(crate) fn run_tick_hooks(&mut self) -> Result<()> {
+        let now = Instant::now();
+        let mut running = mem::take(&mut self.tick_hooks);
+        let result = running
+            .iter_mut()
+            .filter(|tick| tick.due <= now)
+            .try_for_each(|tick| {
+                // Scheduled from now, so a slow hook doesn't run repeatedly to catch up.
+                tick.due = now + tick.interval;
+                (tick.hook)(self)
+            });
+        let added = mem::replace(&mut self.tick_hooks, running);
+        self.tick_hooks.extend(added);
+        result.and_then(|_| self.redraw())
+    }
+
+    /// Run the hooks in the list returned by `hooks`, stopping at the first error.
+    fn run_hooks(&mut self, hooks: fn(&mut Self) -> &mut Vec<HookFunc<T>>) -> Result<()> {
+        // Taken out while running, since each hook borrows the `Repl`.
+        let mut running = mem::take(hooks(self));
+        let result = running.iter_mut().try_for_each(|hook| hook(self));
+        let added = mem::replace(hooks(self), running);
+        hooks(self).extend(added);
+        result
+    }
+}
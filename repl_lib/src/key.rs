@@ -0,0 +1,143 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::io::{self, Read};
+
+use term_manager::input::decode;
+pub use term_manager::input::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{Error, ErrorContext, InternalError, Line, Repl, Result};
+
+/// Function type for handling a key bound with `Repl::bind_key`, given the input being
+/// edited.
+pub type KeyBindingFunc = Box<dyn FnMut(&mut Line) -> Result<()>>;
+
+impl<T> Repl<T> {
+    /// Read a single key press without line editing.
+    ///
+    /// Useful for "press any key" prompts, pagers and confirmations. A lone Escape is only
+    /// reported once the following key arrives.
+    pub fn read_key(&mut self) -> Result<KeyEvent> {
+        if self.is_interactive() {
+            decode(|| self.read_byte())
+        } else {
+            let mut stdin = io::stdin().lock();
+            decode(|| {
+                let mut buf = [0u8; 1];
+                match stdin.read(&mut buf) {
+                    Ok(0) => Err(Error::Internal(InternalError::Eof)),
+                    Ok(_) => Ok(buf[0]),
+                    Err(e) => Err(Error::Internal(InternalError::IoRead(
+                        ErrorContext::with_source("error reading from stdin", e),
+                    ))),
+                }
+            })
+        }
+    }
+
+    /// Run `handler` when `key` is pressed while editing, in place of its usual binding.
+    ///
+    /// The input is redrawn after the handler edits it. Binding a key again replaces its
+    /// handler.
+    pub fn bind_key(&mut self, key: KeyEvent, handler: KeyBindingFunc) {
+        self.key_bindings.retain(|(bound, _)| *bound != key);
+        self.key_bindings.push((key, handler));
+    }
+
+    /// Run the handler bound to the key sent as `sequence`, if there is one. Returns whether
+    /// there was.
+    pub(crate) fn run_key_binding(&mut self, sequence: &[u8]) -> Result<bool> {
+        if self.key_bindings.is_empty() {
+            return Ok(false);
+        }
+        let mut bytes = sequence.iter().copied();
+        let key = decode(|| bytes.next().ok_or(Error::Internal(InternalError::Eof)));
+        let Ok(key) = key else {
+            return Ok(false);
+        };
+        let Some((_, handler)) = self
+            .key_bindings
+            .iter_mut()
+            .find(|(bound, _)| *bound == key)
+        else {
+            return Ok(false);
+        };
+        let Some(line) = self.lines.get_mut(self.current_line) else {
+            return Ok(false);
+        };
+        self.numeric_arg = None;
+        self.search_prefix = None;
+        self.kill_ring.last_yank = None;
+        handler(line)?;
+        self.redraw_current_line()?;
+        Ok(true)
+    }
+}
+
+/// Returns the legacy encoding of `sequence` if it's a key reported as
+/// `ESC [ code ; modifiers u` by the kitty keyboard protocol, empty if the code isn't a
+/// character.
+///
+/// Esc becomes a lone `ESC`, which the protocol reports without the wait for a following key.
+pub(crate) fn kitty_to_legacy(sequence: &[u8]) -> Option<Vec<u8>> {
+    let body = sequence.strip_prefix(b"\x1b[")?.strip_suffix(b"u")?;
+    if body.is_empty()
+        || !body
+            .iter()
+            .all(|&b| b.is_ascii_digit() || b == b';' || b == b':')
+    {
+        return None;
+    }
+    // Sub-parameters after `:` are dropped.
+    let params: Vec<usize> = body
+        .split(|&b| b == b';')
+        .filter_map(|p| {
+            let p = p.split(|&b| b == b':').next()?;
+            std::str::from_utf8(p).ok()?.parse().ok()
+        })
+        .collect();
+    let Some(c) = params.first().and_then(|&code| char::from_u32(code as u32)) else {
+        return Some(Vec::new());
+    };
+    let param = params.get(1).copied().unwrap_or(1);
+    let modifiers = KeyModifiers::from_param(param.min(u16::MAX as usize) as u16);
+
+    let mut bytes = Vec::new();
+    if modifiers.alt {
+        bytes.push(0x1B);
+    }
+    match c {
+        '\x1b' => bytes.push(0x1B),
+        '\t' if modifiers.shift => return Some(b"\x1b[Z".to_vec()),
+        ' ' if modifiers.ctrl => bytes.push(0x00),
+        c if modifiers.ctrl && matches!(c, 'a'..='z' | '@' | '['..='_') => {
+            bytes.push(c as u8 & 0x1F);
+        }
+        c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReplBuilder;
+
+    #[test]
+    fn kitty_keys_map_to_legacy_bytes() {
+        assert_eq!(kitty_to_legacy(b"\x1b[27u"), Some(vec![0x1B]));
+        assert_eq!(kitty_to_legacy(b"\x1b[27;3u"), Some(vec![0x1B, 0x1B]));
+        assert_eq!(kitty_to_legacy(b"\x1b[97;5u"), Some(vec![0x01]));
+        assert_eq!(kitty_to_legacy(b"\x1b[9;2u"), Some(b"\x1b[Z".to_vec()));
+        assert_eq!(kitty_to_legacy(b"\x1b[u"), None);
+        assert_eq!(kitty_to_legacy(b"\x1b[A"), None);
+    }
+
+    #[test]
+    fn kitty_esc_twice_clears_the_line() {
+        let mut repl = ReplBuilder::new().build_detached().unwrap();
+        repl.feed_bytes(b"abc\x1b[27u\x1b[27uxy\r");
+        assert_eq!(repl.process_input().unwrap(), "xy");
+    }
+}
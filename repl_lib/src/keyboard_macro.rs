@@ -0,0 +1,92 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use term_manager::Event;
+
+use crate::{
+    Repl, Result,
+    paste::{PASTE_END, PASTE_START},
+};
+
+/// Keys recorded with Ctrl-X ( and Ctrl-X ), for replaying with Ctrl-X e like readline's
+/// keyboard macros.
+pub(crate) struct KeyboardMacro {
+    /// Keys read since recording started, if it has.
+    recording: Option<Vec<Vec<u8>>>,
+    /// The last completed recording.
+    keys: Vec<Vec<u8>>,
+    /// Keys aren't recorded for now, e.g. while a secret is typed.
+    paused: bool,
+}
+
+impl KeyboardMacro {
+    pub(crate) fn new() -> Self {
+        Self {
+            recording: None,
+            keys: Vec::new(),
+            paused: false,
+        }
+    }
+
+    /// Record a key read from the terminal, if recording and not paused.
+    pub(crate) fn record(&mut self, sequence: &[u8]) {
+        if let Some(recording) = self.recording.as_mut()
+            && !self.paused
+        {
+            recording.push(sequence.to_vec());
+        }
+    }
+
+    /// Stop or resume recording the keys read, without ending the recording.
+    pub(crate) fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+}
+
+impl<T> Repl<T> {
+    /// Record a key or paste read from the terminal, if recording. A paste is recorded with
+    /// its markers, so it's read as a paste again.
+    pub(crate) fn record_event(&mut self, event: &Event) {
+        match event {
+            Event::Key { sequence, .. } => self.keyboard_macro.record(sequence),
+            Event::Paste(text) => {
+                self.keyboard_macro.record(PASTE_START);
+                self.keyboard_macro
+                    .record(&[text.as_bytes(), PASTE_END].concat());
+            }
+            _ => {}
+        }
+    }
+
+    /// Start recording keys, discarding a recording in progress.
+    pub(crate) fn start_macro(&mut self) {
+        self.keyboard_macro.recording = Some(Vec::new());
+    }
+
+    /// Stop recording keys and keep the recording for `play_macro`. Rings the bell if
+    /// nothing was being recorded.
+    pub(crate) fn end_macro(&mut self) -> Result<()> {
+        let Some(mut keys) = self.keyboard_macro.recording.take() else {
+            return self.bell();
+        };
+        // Drop the Ctrl-X ) that ended it.
+        keys.truncate(keys.len().saturating_sub(2));
+        self.keyboard_macro.keys = keys;
+        Ok(())
+    }
+
+    /// Replay the recorded keys `count` times, as if they were typed next. Rings the bell
+    /// while recording, so a macro can't contain itself, or if there's no recording.
+    pub(crate) fn play_macro(&mut self, count: usize) -> Result<()> {
+        if self.keyboard_macro.recording.is_some() || self.keyboard_macro.keys.is_empty() {
+            return self.bell();
+        }
+        for _ in 0..count {
+            for sequence in self.keyboard_macro.keys.iter().rev() {
+                self.unread.push_front(sequence.clone());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{collections::VecDeque, ops::Range};
+
+/// Number of killed strings kept for yanking.
+const KILL_RING_SIZE: usize = 16;
+
+/// Text removed by kill commands, newest first, for yanking back with Ctrl-Y and Alt-Y.
+pub(crate) struct KillRing {
+    entries: VecDeque<String>,
+    /// Entry inserted by the last yank.
+    yank_index: usize,
+    /// Byte range of the text inserted by the last yank, if the previous key was a yank.
+    pub(crate) last_yank: Option<Range<usize>>,
+}
+
+impl KillRing {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            yank_index: 0,
+            last_yank: None,
+        }
+    }
+
+    /// Store killed text, dropping the oldest entry when full. Empty kills are ignored.
+    pub(crate) fn kill(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        self.entries.push_front(text);
+        self.entries.truncate(KILL_RING_SIZE);
+    }
+
+    /// Returns the most recent kill, to be inserted by a yank.
+    pub(crate) fn yank(&mut self) -> Option<&str> {
+        self.yank_index = 0;
+        self.entries.front().map(String::as_str)
+    }
+
+    /// Returns the kill before the one last yanked, wrapping to the newest after the oldest.
+    pub(crate) fn yank_pop(&mut self) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.yank_index = (self.yank_index + 1) % self.entries.len();
+        self.entries.get(self.yank_index).map(String::as_str)
+    }
+}
@@ -2,21 +2,143 @@
 // Author: Sebastian Ibanez
 // Created: 2025-09-17
 
-use std::fmt::Display;
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::{self, BufRead, Write},
+    ops::Range,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
 
-use term_manager::TermManager;
+pub use term_manager::{Attribute, Canceller, Color, ColorDepth, TermCaps};
+use term_manager::{Event, InterruptGuard, TermManager, clear, cursor, input, screen};
+#[cfg(feature = "normalize")]
+use unicode_normalization::UnicodeNormalization;
+
+mod abbreviations;
+#[cfg(feature = "async")]
+mod async_eval;
+mod auto_pairs;
+mod background;
+mod bidi;
+mod commands;
+pub mod completion;
+#[cfg(feature = "config")]
+mod config;
+mod delimiters;
+mod editor;
+mod eval;
+mod export;
+#[cfg(feature = "persistence")]
+mod file_history;
+#[cfg(feature = "tree-sitter")]
+mod grammar;
+mod highlight;
+mod history;
+mod history_browser;
+#[cfg(feature = "encryption")]
+mod history_cipher;
+mod hooks;
+mod key;
+mod keyboard_macro;
+mod kill_ring;
+mod line_ending;
+#[cfg(feature = "lsp")]
+mod lsp;
+mod modes;
+#[cfg(feature = "mouse")]
+mod mouse;
+pub mod output;
+mod pager;
+mod paste;
+pub mod paths;
+mod plugin;
+mod prompt;
+mod replay;
+mod scrollback;
+mod secret;
+mod selection;
+mod signature;
+mod spinner;
+mod status;
+mod styled;
+#[cfg(feature = "syntect")]
+mod syntax;
+mod tokenize;
+mod transcript;
+mod vi;
+mod widgets;
+mod words;
+
+#[cfg(feature = "async")]
+pub use async_eval::{AsyncEvalFunc, BlockOnFunc, LineFuture};
+pub use background::ReplThread;
+use commands::MetaCommands;
+pub use commands::{CommandAction, CommandContext, MetaCommandFunc};
+use completion::DOC_ROWS;
+pub use completion::{CompleterFunc, CompletionContext, Completions};
+#[cfg(feature = "config")]
+pub use config::Config;
+pub use delimiters::{BRACKETS, delimiters_balanced};
+pub use eval::{EvalContext, EvalFunc};
+pub use export::ExportFormat;
+#[cfg(feature = "persistence")]
+pub use file_history::FileHistory;
+#[cfg(feature = "tree-sitter")]
+pub use grammar::grammar_validator;
+pub use highlight::HighlightFunc;
+use history::History;
+pub use history::{HistoryEntry, HistoryStore, SearchMode, SharedHistory};
+use hooks::TickHook;
+pub use hooks::{FocusFunc, HookFunc};
+pub use key::{KeyBindingFunc, KeyCode, KeyEvent, KeyModifiers};
+use keyboard_macro::KeyboardMacro;
+use kill_ring::KillRing;
+pub use line_ending::LineEnding;
+#[cfg(feature = "lsp")]
+pub use lsp::LspClient;
+use modes::SavedMode;
+pub use modes::SubMode;
+pub use paste::PasteFunc;
+pub use plugin::ReplPlugin;
+pub use prompt::{PromptContext, PromptFunc};
+use replay::Replay;
+use scrollback::Scrollback;
+use selection::Selection;
+pub use signature::SignatureFunc;
+use spinner::Spinner;
+pub use status::StatusPosition;
+pub use styled::{Span, Style, StyledText, char_width, text_width};
+#[cfg(feature = "syntect")]
+pub use syntax::syntax_highlighter;
+pub use tokenize::{Token, token_at, tokenize};
+use transcript::Transcript;
+pub use vi::EditMode;
+use vi::ViState;
+pub use words::WordChars;
 
 /// Result type alias for repl_lib operations.
 pub type Result<T> = std::result::Result<T, Error>;
 
-/// Function type for processing input lines.
-pub type ProcessLineFunc = Box<dyn FnMut(String) -> Result<String>>;
+/// Function type for processing input lines, producing a value of type `T`.
+pub type ProcessLineFunc<T = String> = Box<dyn FnMut(String) -> Result<T>>;
+
+/// Function type for checking the input when Enter is pressed.
+pub type ValidatorFunc = Box<dyn FnMut(&str) -> Validation>;
+
+/// Function type for computing the indentation of a new continuation line.
+///
+/// Receives the input before the cursor and returns the whitespace to pre-fill.
+pub type IndentFunc = Box<dyn FnMut(&str) -> String>;
 
-/// Function type for determining if a line is complete.
-pub type LineCompletionFunc = Box<dyn FnMut(String) -> bool>;
+/// Function type for determining if `Repl::run` should exit before processing a line.
+pub type ExitConditionFunc = Box<dyn FnMut(&str) -> bool>;
 
 /// Repl error.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     Internal(InternalError),
     User(UserError),
@@ -31,33 +153,173 @@ impl Display for Error {
     }
 }
 
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Internal(e) => e.source(),
+            Self::User(_) => None,
+        }
+    }
+}
+
 /// Error type for REPL operations.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum InternalError {
-    InitFail(String),
-    IoFlush(String),
-    IoRead(String),
-    IoWrite(String),
+    Eof,
+    /// Reading input was stopped with a `Canceller`.
+    Cancelled,
+    /// No input arrived within the idle timeout.
+    TimedOut,
+    InitFail(ErrorContext),
+    IoFlush(ErrorContext),
+    IoRead(ErrorContext),
+    IoWrite(ErrorContext),
 }
 
 impl Display for InternalError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            InternalError::InitFail(s) => write!(f, "initialization failed: {}", s),
-            InternalError::IoFlush(s) => write!(f, "IO flush error: {}", s),
-            InternalError::IoRead(s) => write!(f, "IO read error: {}", s),
-            InternalError::IoWrite(s) => write!(f, "IO write error: {}", s),
+            InternalError::Eof => write!(f, "end of input"),
+            InternalError::Cancelled => write!(f, "input cancelled"),
+            InternalError::TimedOut => write!(f, "timed out waiting for input"),
+            InternalError::InitFail(c) => write!(f, "initialization failed: {}", c),
+            InternalError::IoFlush(c) => write!(f, "IO flush error: {}", c),
+            InternalError::IoRead(c) => write!(f, "IO read error: {}", c),
+            InternalError::IoWrite(c) => write!(f, "IO write error: {}", c),
+        }
+    }
+}
+
+impl std::error::Error for InternalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InternalError::Eof | InternalError::Cancelled | InternalError::TimedOut => None,
+            InternalError::InitFail(c)
+            | InternalError::IoFlush(c)
+            | InternalError::IoRead(c)
+            | InternalError::IoWrite(c) => c.source(),
+        }
+    }
+}
+
+/// What an internal error was doing when it failed, and the error that caused it.
+///
+/// The underlying `io::Error` or `term_manager::Error` is kept as the source. Only the message
+/// is displayed; walk `source()` to report the cause.
+#[derive(Debug)]
+pub struct ErrorContext {
+    message: String,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl ErrorContext {
+    /// Create a context without an underlying error.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create a context for a failure caused by `source`.
+    pub fn with_source(
+        message: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            source: Some(Box::new(source)),
         }
     }
+
+    /// Returns the description of the failed operation, without the underlying error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
 }
 
+impl std::error::Error for ErrorContext {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as _)
+    }
+}
+
+impl From<String> for ErrorContext {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for ErrorContext {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+/// An error in the input, such as one returned by `process_line`, shown to the user.
+///
+/// Create one with `UserError::new`, adding corrections with `with_suggestions`.
 #[derive(Debug)]
 pub struct UserError {
-    pub error: String,
+    error: String,
+    /// Corrected versions of the input that failed, best first.
+    suggestions: Vec<String>,
+}
+
+impl UserError {
+    /// Create an error with the message `error`.
+    pub fn new(error: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Offer corrections of the input that failed, e.g. with a misspelled name replaced by
+    /// the closest known one.
+    ///
+    /// `run` prints them as a "did you mean" hint after the error, and the first one is
+    /// suggested at the next prompt, where Right arrow or Ctrl-E inserts it.
+    pub fn with_suggestions<I>(mut self, suggestions: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.suggestions
+            .extend(suggestions.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns the message shown to the user.
+    pub fn message(&self) -> &str {
+        &self.error
+    }
+
+    /// Returns the corrections offered for the input, best first.
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+}
+
+impl Display for UserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
 }
 
+impl std::error::Error for UserError {}
+
 /// Represents a single line of input with cursor position.
-#[derive(Clone, Debug)]
+///
+/// Lines converted from strings have the cursor at the end.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Line {
     text: String,
     cursor_pos: usize,
@@ -72,31 +334,323 @@ impl Line {
         }
     }
 
+    /// Creates a line containing `text` with the cursor at byte offset `pos`, clamped as by
+    /// `set_cursor`.
+    pub fn with_cursor(text: impl Into<String>, pos: usize) -> Self {
+        let mut line = Self {
+            text: text.into(),
+            cursor_pos: 0,
+        };
+        line.set_cursor(pos);
+        line
+    }
+
+    /// Returns the cursor position as a byte offset into the text.
+    pub fn cursor(&self) -> usize {
+        self.cursor_pos
+    }
+
+    /// Moves the cursor to byte offset `pos`, clamped to the text and moved back to the
+    /// nearest character boundary.
+    pub fn set_cursor(&mut self, pos: usize) {
+        let mut pos = pos.min(self.text.len());
+        while !self.text.is_char_boundary(pos) {
+            pos -= 1;
+        }
+        self.cursor_pos = pos;
+    }
+
+    /// Removes all text.
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor_pos = 0;
+    }
+
+    /// Removes and returns all text, leaving the line empty.
+    pub fn take_text(&mut self) -> String {
+        self.cursor_pos = 0;
+        std::mem::take(&mut self.text)
+    }
+
     /// Inserts a character at the current cursor position.
     pub fn insert_char(&mut self, c: char) {
         self.text.insert(self.cursor_pos, c);
-        self.cursor_pos += 1;
+        self.cursor_pos += c.len_utf8();
+    }
+
+    /// Inserts a string at the current cursor position.
+    pub fn insert_str(&mut self, s: &str) {
+        self.text.insert_str(self.cursor_pos, s);
+        self.cursor_pos += s.len();
+    }
+
+    /// Replaces characters after the cursor with `s`, as in overwrite mode, and moves the
+    /// cursor past it. Text typed at the end of a physical line is inserted.
+    pub fn overwrite_str(&mut self, s: &str) {
+        let rest = &self.text[self.cursor_pos..];
+        let line_end = rest.find('\n').unwrap_or(rest.len());
+        let replaced: usize = rest[..line_end]
+            .chars()
+            .take(s.chars().count())
+            .map(char::len_utf8)
+            .sum();
+        self.text
+            .replace_range(self.cursor_pos..self.cursor_pos + replaced, s);
+        self.cursor_pos += s.len();
     }
 
     /// Removes the character before the cursor.
     pub fn backspace(&mut self) {
-        if self.cursor_pos > 0 {
-            self.cursor_pos -= 1;
+        if let Some(c) = self.text[..self.cursor_pos].chars().next_back() {
+            self.cursor_pos -= c.len_utf8();
+            self.text.remove(self.cursor_pos);
+        }
+    }
+
+    /// Removes the character under the cursor.
+    pub fn delete(&mut self) {
+        if self.cursor_pos < self.text.len() {
             self.text.remove(self.cursor_pos);
         }
     }
 
     /// Moves cursor one position to the left.
     pub fn move_left(&mut self) {
-        if self.cursor_pos > 0 {
-            self.cursor_pos -= 1;
+        if let Some(c) = self.text[..self.cursor_pos].chars().next_back() {
+            self.cursor_pos -= c.len_utf8();
         }
     }
 
     /// Moves cursor one position to the right.
     pub fn move_right(&mut self) {
-        if self.cursor_pos < self.text.len() {
-            self.cursor_pos += 1;
+        if let Some(c) = self.text[self.cursor_pos..].chars().next() {
+            self.cursor_pos += c.len_utf8();
+        }
+    }
+
+    /// Removes and returns the text from the cursor to the end of the physical line, or the
+    /// newline itself if the cursor is already there.
+    pub fn kill_to_end(&mut self) -> String {
+        let rest = &self.text[self.cursor_pos..];
+        let end = match rest.find('\n') {
+            Some(0) => 1,
+            Some(i) => i,
+            None => rest.len(),
+        };
+        self.delete_range(self.cursor_pos..self.cursor_pos + end)
+    }
+
+    /// Removes and returns the text from the start of the physical line to the cursor.
+    pub fn kill_to_start(&mut self) -> String {
+        let start = self.text[..self.cursor_pos]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        self.delete_range(start..self.cursor_pos)
+    }
+
+    /// Removes and returns the whitespace-delimited word before the cursor.
+    pub fn kill_word_back(&mut self) -> String {
+        let before = self.text[..self.cursor_pos].trim_end();
+        let start = before.rfind(char::is_whitespace).map_or(0, |i| {
+            i + before[i..].chars().next().map_or(1, char::len_utf8)
+        });
+        self.delete_range(start..self.cursor_pos)
+    }
+
+    /// Removes and returns the word before the cursor and anything between it and the
+    /// cursor, with words made of `words`.
+    pub fn kill_word_back_with(&mut self, words: &WordChars) -> String {
+        let start = self.word_start_before(self.cursor_pos, words);
+        self.delete_range(start..self.cursor_pos)
+    }
+
+    /// Removes and returns the text in the byte range `range`, keeping the cursor on the same
+    /// text (or at the start of the range, if it was inside it).
+    ///
+    /// Panics if the range is out of bounds or not on character boundaries, like
+    /// `String::drain`.
+    pub fn delete_range(&mut self, range: Range<usize>) -> String {
+        if self.cursor_pos >= range.end {
+            self.cursor_pos -= range.len();
+        } else if self.cursor_pos > range.start {
+            self.cursor_pos = range.start;
+        }
+        self.text.drain(range).collect()
+    }
+
+    /// Returns the byte offset of the start of the word before the cursor.
+    pub fn word_start(&self) -> usize {
+        self.word_start_with(&WordChars::default())
+    }
+
+    /// Returns the byte offset of the end of the word at or after the cursor.
+    pub fn word_end(&self) -> usize {
+        self.word_end_with(&WordChars::default())
+    }
+
+    /// Returns the byte offset of the start of the word before the cursor, with words made
+    /// of `words`.
+    pub fn word_start_with(&self, words: &WordChars) -> usize {
+        self.word_start_before(self.cursor_pos, words)
+    }
+
+    /// Returns the byte offset of the end of the word at or after the cursor, with words
+    /// made of `words`.
+    pub fn word_end_with(&self, words: &WordChars) -> usize {
+        self.word_end_after(self.cursor_pos, words)
+    }
+
+    /// Swaps the character before the cursor with the one under it and moves the cursor
+    /// forward. At the end of the line, swaps the last two characters instead.
+    pub fn transpose_chars(&mut self) {
+        if self.cursor_pos == self.text.len() {
+            self.move_left();
+        }
+        let Some(before) = self.text[..self.cursor_pos].chars().next_back() else {
+            return;
+        };
+        let Some(after) = self.text[self.cursor_pos..].chars().next() else {
+            return;
+        };
+
+        let start = self.cursor_pos - before.len_utf8();
+        let end = self.cursor_pos + after.len_utf8();
+        self.text
+            .replace_range(start..end, &format!("{}{}", after, before));
+        self.cursor_pos = end;
+    }
+
+    /// Swaps the word before the cursor with the word after it, leaving the cursor after
+    /// both. At the end of the line, swaps the last two words instead.
+    pub fn transpose_words(&mut self) {
+        self.transpose_words_with(&WordChars::default());
+    }
+
+    pub(crate) fn transpose_words_with(&mut self, words: &WordChars) {
+        let second_end = self.word_end_after(self.cursor_pos, words);
+        let second_start = self.word_start_before(second_end, words);
+        let first_start = self.word_start_before(second_start, words);
+        let first_end = self.word_end_after(first_start, words);
+        if first_start == second_start || first_end > second_start {
+            return;
+        }
+
+        let first = self.text[first_start..first_end].to_string();
+        let between = self.text[first_end..second_start].to_string();
+        let second = self.text[second_start..second_end].to_string();
+        self.text.replace_range(
+            first_start..second_end,
+            &format!("{}{}{}", second, between, first),
+        );
+        self.cursor_pos = second_end;
+    }
+
+    /// Uppercases from the cursor to the end of the word and moves the cursor past it.
+    pub fn upcase_word(&mut self) {
+        self.change_word_case(&WordChars::default(), |word| word.to_uppercase());
+    }
+
+    /// Lowercases from the cursor to the end of the word and moves the cursor past it.
+    pub fn downcase_word(&mut self) {
+        self.change_word_case(&WordChars::default(), |word| word.to_lowercase());
+    }
+
+    /// Capitalizes from the cursor to the end of the word and moves the cursor past it.
+    pub fn capitalize_word(&mut self) {
+        self.capitalize_word_with(&WordChars::default());
+    }
+
+    pub(crate) fn capitalize_word_with(&mut self, words: &WordChars) {
+        self.change_word_case(words, |word| {
+            let start = word.find(|c| words.contains(c)).unwrap_or(word.len());
+            let mut rest = word[start..].chars();
+            let first = rest.next().map(|c| c.to_uppercase().collect::<String>());
+            format!(
+                "{}{}{}",
+                &word[..start],
+                first.unwrap_or_default(),
+                rest.as_str().to_lowercase()
+            )
+        });
+    }
+
+    /// Replaces the text from the cursor to the end of the word with `change(text)`.
+    pub(crate) fn change_word_case(&mut self, words: &WordChars, change: impl Fn(&str) -> String) {
+        let end = self.word_end_after(self.cursor_pos, words);
+        let changed = change(&self.text[self.cursor_pos..end]);
+        self.text.replace_range(self.cursor_pos..end, &changed);
+        self.cursor_pos += changed.len();
+    }
+
+    /// Returns the byte offset of the end of the next word at or after `pos`.
+    fn word_end_after(&self, pos: usize, words: &WordChars) -> usize {
+        let rest = &self.text[pos..];
+        let start = rest.find(|c| words.contains(c)).unwrap_or(rest.len());
+        let len = rest[start..]
+            .find(|c| !words.contains(c))
+            .unwrap_or(rest.len() - start);
+        pos + start + len
+    }
+
+    /// Returns the byte offset of the start of the word before `pos`.
+    fn word_start_before(&self, pos: usize, words: &WordChars) -> usize {
+        let before = &self.text[..pos];
+        let end = before
+            .rfind(|c| words.contains(c))
+            .map(|i| i + before[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0);
+        before[..end]
+            .rfind(|c| !words.contains(c))
+            .map(|i| i + before[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0)
+    }
+
+    /// Returns the cursor's row (counted by newlines) and column (in characters).
+    pub fn cursor_row_col(&self) -> (usize, usize) {
+        let before = &self.text[..self.cursor_pos];
+        let row = before.matches('\n').count();
+        let col = before.rsplit('\n').next().unwrap_or("").chars().count();
+        (row, col)
+    }
+
+    /// Moves the cursor to the previous physical line, keeping its column where possible.
+    ///
+    /// Returns `false` if the cursor is already on the first line.
+    pub fn move_up(&mut self) -> bool {
+        let (row, col) = self.cursor_row_col();
+        if row == 0 {
+            return false;
+        }
+        self.set_row_col(row - 1, col);
+        true
+    }
+
+    /// Moves the cursor to the next physical line, keeping its column where possible.
+    ///
+    /// Returns `false` if the cursor is already on the last line.
+    pub fn move_down(&mut self) -> bool {
+        let (row, col) = self.cursor_row_col();
+        if row >= self.text.matches('\n').count() {
+            return false;
+        }
+        self.set_row_col(row + 1, col);
+        true
+    }
+
+    /// Places the cursor at `col` on physical line `row`, clamping to the end of that line.
+    fn set_row_col(&mut self, row: usize, col: usize) {
+        let mut offset = 0;
+        for (i, physical) in self.text.split('\n').enumerate() {
+            if i == row {
+                let byte_col = physical
+                    .char_indices()
+                    .nth(col)
+                    .map(|(b, _)| b)
+                    .unwrap_or(physical.len());
+                self.cursor_pos = offset + byte_col;
+                return;
+            }
+            offset += physical.len() + 1;
         }
     }
 
@@ -106,18 +660,103 @@ impl Line {
     }
 }
 
+impl Default for Line {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&str> for Line {
+    fn from(text: &str) -> Self {
+        Self::from(text.to_string())
+    }
+}
+
+impl From<String> for Line {
+    fn from(text: String) -> Self {
+        Self {
+            cursor_pos: text.len(),
+            text,
+        }
+    }
+}
+
 impl Display for Line {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.text)
     }
 }
 
+/// Result of a `ValidatorFunc`, deciding what Enter does with the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Validation {
+    /// Submit the input for evaluation.
+    Complete,
+    /// Continue the input on a new line, e.g. after an unclosed bracket.
+    Incomplete,
+    /// Reject the input, keeping it and the cursor in place for correction. The message is
+    /// shown in red below the input until the next key is pressed.
+    Invalid { message: String },
+}
+
+/// What the Tab key does when no completer is set.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TabMode {
+    /// Ignore Tab.
+    Ignore,
+    /// Insert a literal tab character, displayed up to the next tab stop.
+    Literal,
+    /// Insert the given number of spaces.
+    Spaces(usize),
+}
+
+/// How the REPL signals a rejected edit, such as backspace at the start of the input.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BellStyle {
+    /// Ring the terminal bell.
+    #[default]
+    Audible,
+    /// Flash the screen.
+    Visible,
+    /// Do nothing.
+    None,
+}
+
+/// How long the screen stays inverted for a visible bell.
+const VISIBLE_BELL_MS: u64 = 50;
+
+/// Distance between tab stops when displaying literal tabs.
+const TAB_WIDTH: usize = 8;
+
+/// Largest repeat count accepted from Alt-digit arguments.
+const MAX_NUMERIC_ARG: usize = 1000;
+
+/// Queued output is written before reading more keys once it grows past this many bytes,
+/// even if keys are already waiting.
+const MAX_QUEUED_OUTPUT: usize = 64 * 1024;
+
 /// Type of input being processed by the REPL.
 #[derive(Copy, Clone, Debug)]
 enum InputType {
     Normal,
-    Escape,
-    EscapeSequence,
+    /// After Ctrl-X, waiting for the second key of a chord.
+    CtrlX,
+    /// After Ctrl-V, waiting for a key to insert literally.
+    Quoted,
+    /// After Ctrl-] or Alt-Ctrl-], waiting for the character to move the cursor to.
+    CharSearch {
+        backward: bool,
+    },
+}
+
+/// Outcome of evaluating a line.
+enum Evaluation<T> {
+    /// `process_line` returned a value.
+    Value(T),
+    /// A meta-command ran and printed its output.
+    Command,
+    /// A meta-command asked to quit.
+    Quit,
 }
 
 /// Internal state for REPL operation flow.
@@ -128,200 +767,2582 @@ enum ReplState {
 }
 
 /// Interactive Read-Eval-Print Loop implementation.
-pub struct Repl {
-    tmanager: TermManager,
+///
+/// The terminal keeps translating `\n` to `\r\n` while the line editor is active, so
+/// callbacks can print with `println!`.
+///
+/// When stdin is not a terminal (piped input, CI) the REPL runs in batch mode:
+/// raw mode and rendering are skipped and input is read line by line until EOF.
+///
+/// On a dumb terminal (`TERM=dumb`, as in Emacs shell-mode) input is also read line by line,
+/// but prompts are still printed.
+///
+/// `T` is the value `process_line` returns. `run` prints it with `Display`; with
+/// `process_input` the application renders it.
+pub struct Repl<T = String> {
+    tmanager: Option<TermManager>,
+    /// Output of a REPL built with `build_detached`, kept here instead of written to stdout.
+    #[cfg(test)]
+    output: Option<Vec<u8>>,
+    /// Attached to a terminal that can't handle escape sequences, or in screen reader mode.
+    dumb: bool,
+    screen_reader: bool,
     lines: Vec<Line>,
+    history: SharedHistory,
+    /// Number of shared history entries mirrored into `lines`.
+    history_seen: usize,
+    /// Index of the history entry for the line being evaluated.
+    last_entry: Option<usize>,
+    /// Index in `lines` of the line being evaluated.
+    committed_line: usize,
     current_line: usize,
-    escape_buffer: Vec<u8>,
+    /// Keys to read again before new input, from translated key sequences.
+    unread: VecDeque<Vec<u8>>,
     input_state: InputType,
-    process_line: ProcessLineFunc,
-    is_line_complete: LineCompletionFunc,
-    prompt: String,
+    /// Key sequence that discards the input, and how much of it has been typed.
+    clear_line_keys: Vec<KeyEvent>,
+    word_chars: Option<WordChars>,
+    abbreviations: Vec<(String, String)>,
+    auto_pairs: Vec<(char, char)>,
+    clear_line_progress: usize,
+    selection: Option<Selection>,
+    editing: bool,
+    overwrite: bool,
+    process_line: EvalFunc<T>,
+    /// Renders the prompt from the REPL's state, replacing `prompt`.
+    prompt_func: Option<PromptFunc>,
+    /// Modes that sub-modes were entered from, innermost last.
+    modes: Vec<SavedMode>,
+    /// The last `process_line` call returned an error.
+    last_failed: bool,
+    pre_input_hooks: Vec<HookFunc<T>>,
+    post_output_hooks: Vec<HookFunc<T>>,
+    exit_hooks: Vec<HookFunc<T>>,
+    focus_hooks: Vec<FocusFunc<T>>,
+    tick_hooks: Vec<TickHook<T>>,
+    /// Whether the terminal has focus, as last reported.
+    focused: bool,
+    validator: ValidatorFunc,
+    /// Message of the last `Validation::Invalid`, shown below the input until the next key.
+    validation_message: Option<String>,
+    /// Documentation of the candidate just completed, shown below the input until the next
+    /// key.
+    completion_doc: Option<String>,
+    indent: Option<IndentFunc>,
+    completer: Option<CompleterFunc>,
+    highlighter: Option<HighlightFunc>,
+    signature_help: Option<SignatureFunc>,
+    /// A signature hint is drawn below the input.
+    signature_shown: bool,
+    tab_mode: TabMode,
+    edit_mode: EditMode,
+    vi: ViState,
+    /// Maximum number of characters in a line of input.
+    max_length: Option<usize>,
+    bell_style: BellStyle,
+    show_timing: bool,
+    /// Wall-clock time of the last `process_line` call.
+    last_eval_duration: Option<Duration>,
+    /// Number prompts and outputs IPython-style.
+    numbered: bool,
+    line_numbers: bool,
+    /// Show a spinner while `process_line` runs.
+    spinner: bool,
+    autosuggest: bool,
+    /// Rest of the history suggestion shown after the cursor.
+    suggestion: Option<String>,
+    /// The terminal's BiDi reordering was turned off to show right-to-left input.
+    bidi_explicit: bool,
+    /// First correction offered by the last evaluation error, suggested at the next prompt.
+    correction: Option<String>,
+    pager: bool,
+    /// Number of the next line passed to `process_line`, starting at 1.
+    execution_count: usize,
+    exit_condition: Option<ExitConditionFunc>,
+    commands: MetaCommands,
+    kill_ring: KillRing,
+    /// Byte range of the word inserted by the last Alt-., and how many history entries back
+    /// it came from, if the previous key was Alt-.
+    last_argument: Option<(Range<usize>, usize)>,
+    keyboard_macro: KeyboardMacro,
+    /// Keys bound with `bind_key`, checked before the built-in bindings.
+    key_bindings: Vec<(KeyEvent, KeyBindingFunc)>,
+    clipboard: bool,
+    bracketed_paste: bool,
+    paste_func: Option<PasteFunc>,
+    #[cfg(feature = "normalize")]
+    normalize_input: bool,
+    line_ending: LineEnding,
+    /// Whether the last key or byte read was `\r`, for `LineEnding::Auto`.
+    after_cr: bool,
+    idle_timeout: Option<Duration>,
+    history_expansion: bool,
+    echo_history_expansion: bool,
+    history_ignore_space: bool,
+    search_prefix: Option<String>,
+    /// Repeat count entered with Alt-digit, applied to the next key.
+    numeric_arg: Option<usize>,
+    /// History line to pre-load into the next input, set by Ctrl-O.
+    next_history_line: Option<usize>,
+    /// Text to pre-fill the next input with, set by `set_initial_input`.
+    initial_input: Option<Line>,
+    /// Screen row of the input the cursor was left on by the last redraw.
+    rendered_row: usize,
+    /// Screen rows taken by the input at the last redraw.
+    rendered_rows: usize,
+    /// Drawn at the end of each screen row a long line is wrapped from, if set.
+    wrap_indicator: Option<StyledText>,
+    prompt: StyledText,
+    continuation_prompt: StyledText,
+    /// The continuation prompt is padding matching the prompt's width.
+    padded_continuation: bool,
+    /// Text of the status line, set by `set_status`.
+    status: Option<StyledText>,
+    status_position: StatusPosition,
+    color_depth: ColorDepth,
     banner: String,
     welcome_msg: String,
+    transcript: Option<Transcript>,
+    /// The session so far as ANSI text, if it's being recorded for export.
+    session: Option<String>,
+    scrollback: Option<Scrollback>,
+    replay: Option<Replay>,
 }
 
-impl Repl {
-    /// Create a new REPL instance.
-    ///
-    /// ### Arguments
-    ///
-    /// * `prompt` - The prompt string to display
-    /// * `banner` - Startup banner to display.
-    /// * `welcome_msg` - Welcome message to display.
-    /// * `process_line` - Function to process completed lines
-    /// * `line_is_finished` - Function to determine if a line is terminated
-    pub fn new(
-        prompt: String,
-        banner: String,
-        welcome_msg: String,
-        process_line: ProcessLineFunc,
-        line_is_terminated: LineCompletionFunc,
-    ) -> Result<Self> {
-        let tmanager = TermManager::new().or_else(|e| {
-            let msg = format!("failed to initialized Repl: {}", e);
-            Err(Error::Internal(InternalError::InitFail(msg)))
-        })?;
-        let mut lines: Vec<Line> = Vec::new();
-        lines.push(Line::new());
-        let current_line = 0;
-        let escape_buffer = Vec::new();
-        let input_state = InputType::Normal;
+/// Builder for configuring a `Repl`.
+///
+/// `ReplBuilder::new` builds a `Repl` that returns strings; use
+/// `ReplBuilder::with_process_line` for a `Repl` returning other values.
+pub struct ReplBuilder<T = String> {
+    prompt: StyledText,
+    continuation_prompt: Option<StyledText>,
+    banner: String,
+    welcome_msg: String,
+    process_line: EvalFunc<T>,
+    prompt_func: Option<PromptFunc>,
+    validator: ValidatorFunc,
+    indent: Option<IndentFunc>,
+    completer: Option<CompleterFunc>,
+    highlighter: Option<HighlightFunc>,
+    signature_help: Option<SignatureFunc>,
+    tab_mode: TabMode,
+    edit_mode: EditMode,
+    max_length: Option<usize>,
+    bell_style: BellStyle,
+    clear_line_keys: Vec<KeyEvent>,
+    word_chars: Option<WordChars>,
+    abbreviations: Vec<(String, String)>,
+    auto_pairs: Vec<(char, char)>,
+    show_timing: bool,
+    numbered: bool,
+    line_numbers: bool,
+    wrap_indicator: Option<StyledText>,
+    spinner: bool,
+    autosuggest: bool,
+    pager: bool,
+    status_position: StatusPosition,
+    exit_condition: Option<ExitConditionFunc>,
+    command_prefix: char,
+    history_expansion: bool,
+    echo_history_expansion: bool,
+    history_ignore_space: bool,
+    history_store: Option<Box<dyn HistoryStore>>,
+    history_size: Option<usize>,
+    shared_history: Option<SharedHistory>,
+    history_entries: Vec<String>,
+    tty_fallback: bool,
+    screen_reader: bool,
+    transcript: Option<PathBuf>,
+    record_session: bool,
+    scrollback: Option<usize>,
+    clipboard: bool,
+    bracketed_paste: bool,
+    paste_func: Option<PasteFunc>,
+    #[cfg(feature = "normalize")]
+    normalize_input: bool,
+    line_ending: LineEnding,
+    idle_timeout: Option<Duration>,
+    #[cfg(feature = "mouse")]
+    mouse: bool,
+    kitty_keyboard: bool,
+    focus_reporting: bool,
+    flow_control: bool,
+    signals: bool,
+    replay: Option<(PathBuf, Duration)>,
+    /// Use the terminal if attached to one. Only cleared by tests.
+    terminal: bool,
+}
 
-        Ok(Repl {
-            tmanager,
-            lines,
-            current_line,
-            escape_buffer,
-            input_state,
+impl ReplBuilder {
+    /// Create a builder that echoes every line back.
+    pub fn new() -> Self {
+        Self::with_process_line(Box::new(Ok))
+    }
+}
+
+impl<T> ReplBuilder<T> {
+    /// Create a builder for a `Repl` whose lines are processed by `process_line`.
+    pub fn with_process_line(process_line: ProcessLineFunc<T>) -> Self
+    where
+        T: 'static,
+    {
+        Self::with_eval_func(wrap_process_line(process_line))
+    }
+
+    /// Create a builder for a `Repl` whose lines are processed by `eval_func`.
+    pub fn with_eval_func(process_line: EvalFunc<T>) -> Self {
+        Self {
+            prompt: StyledText::from("> "),
+            continuation_prompt: None,
+            banner: String::new(),
+            welcome_msg: String::new(),
             process_line,
-            is_line_complete: line_is_terminated,
-            prompt,
-            banner,
-            welcome_msg,
-        })
+            prompt_func: None,
+            validator: Box::new(|_| Validation::Complete),
+            indent: None,
+            completer: None,
+            highlighter: None,
+            signature_help: None,
+            tab_mode: TabMode::Ignore,
+            edit_mode: EditMode::Emacs,
+            max_length: None,
+            bell_style: BellStyle::Audible,
+            clear_line_keys: vec![KeyEvent::new(KeyCode::Esc); 2],
+            word_chars: None,
+            abbreviations: Vec::new(),
+            auto_pairs: Vec::new(),
+            show_timing: false,
+            numbered: false,
+            line_numbers: false,
+            wrap_indicator: None,
+            spinner: false,
+            autosuggest: false,
+            pager: false,
+            status_position: StatusPosition::Bottom,
+            exit_condition: None,
+            command_prefix: ':',
+            history_expansion: false,
+            echo_history_expansion: false,
+            history_ignore_space: false,
+            history_store: None,
+            history_size: None,
+            shared_history: None,
+            history_entries: Vec::new(),
+            tty_fallback: false,
+            screen_reader: false,
+            transcript: None,
+            record_session: false,
+            scrollback: None,
+            clipboard: false,
+            bracketed_paste: true,
+            paste_func: None,
+            #[cfg(feature = "normalize")]
+            normalize_input: false,
+            line_ending: LineEnding::Auto,
+            idle_timeout: None,
+            #[cfg(feature = "mouse")]
+            mouse: false,
+            kitty_keyboard: false,
+            focus_reporting: false,
+            flow_control: false,
+            signals: false,
+            replay: None,
+            terminal: true,
+        }
     }
 
-    /// Prints the welcome banner and message.
-    pub fn print_welcome(&mut self) {
-        println!("{}\n{}", self.banner, self.welcome_msg);
+    /// Set the prompt, either a plain string or `StyledText`.
+    pub fn prompt(mut self, prompt: impl Into<StyledText>) -> Self {
+        self.prompt = prompt.into();
+        self
     }
 
-    /// Prints the REPL prompt.
-    pub fn print_prompt(&mut self) {
-        print!("{}", self.prompt);
+    /// Set a function that renders the prompt each time it's drawn, e.g. in red after an
+    /// error or yellow while the input is incomplete. Replaces the prompt set with `prompt`.
+    pub fn prompt_func(mut self, prompt_func: PromptFunc) -> Self {
+        self.prompt_func = Some(prompt_func);
+        self
     }
 
-    /// Gets a line by index from the history.
-    pub fn get_line(&self, index: usize) -> Option<&Line> {
-        self.lines.get(index)
+    /// Set the prompt shown before continuation lines of multi-line input.
+    ///
+    /// Defaults to spaces as wide as the prompt, aligning continuation lines with the first.
+    pub fn continuation_prompt(mut self, prompt: impl Into<StyledText>) -> Self {
+        self.continuation_prompt = Some(prompt.into());
+        self
     }
 
-    /// Read and process input until a complete line is entered.
-    pub fn process_input(&mut self) -> Result<String> {
-        self.tmanager.flush().map_err(|_| {
-            Error::Internal(InternalError::IoFlush("unable to flush stdout".into()))
-        })?;
+    /// Set the startup banner.
+    pub fn banner(mut self, banner: impl Into<String>) -> Self {
+        self.banner = banner.into();
+        self
+    }
 
-        let mut output: Option<String> = None;
+    /// Set the welcome message.
+    pub fn welcome_msg(mut self, welcome_msg: impl Into<String>) -> Self {
+        self.welcome_msg = welcome_msg.into();
+        self
+    }
 
-        loop {
-            let mut buf = [0u8; 1];
-            self.tmanager.read(&mut buf).map_err(|e| {
-                Error::Internal(InternalError::IoRead(format!(
-                    "error reading from stdin: {}",
-                    e
-                )))
-            })?;
-            let c = buf[0];
+    /// Set the function used to process completed lines.
+    pub fn process_line(mut self, process_line: ProcessLineFunc<T>) -> Self
+    where
+        T: 'static,
+    {
+        self.process_line = wrap_process_line(process_line);
+        self
+    }
 
-            self.input_state = match self.input_state {
-                InputType::Escape => {
-                    self.escape_buffer.push(c);
-                    if c == b'[' {
-                        InputType::EscapeSequence
-                    } else {
-                        self.escape_buffer.clear();
-                        InputType::Normal
-                    }
-                }
-                InputType::EscapeSequence => {
-                    self.escape_buffer.push(c);
-                    if self.escape_buffer.len() == 2 && self.escape_buffer[0] == b'[' {
-                        let final_byte = c;
-                        self.handle_escape_sequence(final_byte)?;
-                        self.escape_buffer.clear();
-                        InputType::Normal
-                    } else {
-                        InputType::EscapeSequence
-                    }
-                }
-                InputType::Normal => match self.handle_normal_input(c)? {
-                    ReplState::Break => {
-                        let finished_line = self
-                            .get_line(self.current_line)
-                            .map(|l| l.text.clone())
-                            .unwrap_or_default();
-                        self.lines.push(Line::new());
-                        self.current_line = self.lines.len() - 1;
-                        output = Some((self.process_line)(finished_line)?);
-
-                        break;
-                    }
-                    ReplState::Continue => self.input_state,
-                },
-            };
-        }
+    /// Set the function used to process completed lines, with access to the `EvalContext`
+    /// for streaming output.
+    ///
+    /// Replaces any function set with `process_line`.
+    pub fn eval_func(mut self, eval_func: EvalFunc<T>) -> Self {
+        self.process_line = eval_func;
+        self
+    }
 
-        Ok(output.unwrap_or_default())
+    /// Set the function that checks the input when Enter is pressed, deciding whether it's
+    /// submitted, continued on a new line, or rejected. By default every input is complete.
+    ///
+    /// In batch mode there's no way to correct rejected input, so reading it returns a
+    /// `UserError` with the validator's message instead.
+    pub fn validator(mut self, validator: ValidatorFunc) -> Self {
+        self.validator = validator;
+        self
     }
 
-    /// Handles ANSI escape sequences (arrow keys).
-    fn handle_escape_sequence(&mut self, c: u8) -> Result<()> {
-        match c {
-            b'A' => {
-                // Up arrow: recall previous line in history
-                if self.current_line > 0 {
-                    self.current_line -= 1;
-                    self.redraw_current_line()?;
-                }
-            }
-            b'B' => {
-                // Down arrow: recall next line in history
-                if self.current_line + 1 < self.lines.len() {
-                    self.current_line += 1;
-                    self.redraw_current_line()?;
-                } else {
-                    self.lines.push(Line::new());
-                    self.current_line = self.lines.len() - 1;
-                    self.redraw_current_line()?;
-                }
-            }
-            b'C' => {
-                // Right arrow
-                if let Some(line) = self.lines.get_mut(self.current_line) {
-                    line.move_right();
-                    self.redraw_current_line()?;
-                }
-            }
-            b'D' => {
-                // Left arrow
-                if let Some(line) = self.lines.get_mut(self.current_line) {
-                    line.move_left();
-                    self.redraw_current_line()?;
-                }
-            }
-            _ => {}
-        }
+    /// Pre-fill continuation lines with the previous line's leading whitespace.
+    pub fn auto_indent(mut self, enabled: bool) -> Self {
+        self.indent = enabled.then(|| Box::new(previous_indentation) as IndentFunc);
+        self
+    }
 
-        self.escape_buffer.clear();
-        self.input_state = InputType::Normal;
+    /// Set the function that computes indentation for new continuation lines.
+    pub fn indent_func(mut self, indent: IndentFunc) -> Self {
+        self.indent = Some(indent);
+        self
+    }
 
-        Ok(())
+    /// Set the function that completes the input at the cursor when Tab is pressed (see
+    /// `completion::history_words`).
+    pub fn completer(mut self, completer: CompleterFunc) -> Self {
+        self.completer = Some(completer);
+        self
     }
 
-    /// Handles normal character input and control characters.
-    fn handle_normal_input(&mut self, c: u8) -> Result<ReplState> {
-        let current_line = self.lines.get_mut(self.current_line).ok_or_else(|| {
-            Error::User(UserError {
-                error: "no active line".into(),
+    /// Set the function that styles the input as it's drawn.
+    pub fn highlighter(mut self, highlighter: HighlightFunc) -> Self {
+        self.highlighter = Some(highlighter);
+        self
+    }
+
+    /// Set the function that hints the signature of the call being typed, shown on the row
+    /// below the input.
+    pub fn signature_help(mut self, signature_help: SignatureFunc) -> Self {
+        self.signature_help = Some(signature_help);
+        self
+    }
+
+    /// Set what the Tab key does when no completer is set (default `TabMode::Ignore`).
+    pub fn tab_mode(mut self, tab_mode: TabMode) -> Self {
+        self.tab_mode = tab_mode;
+        self
+    }
+
+    /// Set how keys edit the input (default `EditMode::Emacs`).
+    ///
+    /// In `EditMode::Vi`, Esc switches to command mode instead of starting the clear-line
+    /// keys.
+    pub fn edit_mode(mut self, mode: EditMode) -> Self {
+        self.edit_mode = mode;
+        self
+    }
+
+    /// Limit input to `max` characters, counting newlines of multi-line input.
+    ///
+    /// Typing, pasting or yanking past the limit is rejected with a bell.
+    pub fn max_line_length(mut self, max: usize) -> Self {
+        self.max_length = Some(max);
+        self
+    }
+
+    /// Set how rejected edits are signalled (default `BellStyle::Audible`).
+    ///
+    /// The bell rings for backspace or delete with nothing to remove, history with no more
+    /// entries, yanking with an empty kill ring, Tab with nothing to insert, and input past
+    /// `max_line_length`.
+    pub fn bell_style(mut self, style: BellStyle) -> Self {
+        self.bell_style = style;
+        self
+    }
+
+    /// Set the keys that, pressed in sequence, discard the input and start over with an
+    /// empty prompt (default Escape twice). Unlike Ctrl-C, nothing is printed and the input
+    /// isn't saved to the kill ring. An empty list turns this off.
+    pub fn clear_line_keys(mut self, keys: Vec<KeyEvent>) -> Self {
+        self.clear_line_keys = keys;
+        self
+    }
+
+    /// Set which characters make up words for Alt-B / Alt-F, Ctrl-W, Alt-T, Alt-U / Alt-L /
+    /// Alt-C, and `completion::history_words`.
+    ///
+    /// By default words are letters, digits, and underscores, except that Ctrl-W removes
+    /// everything back to the previous whitespace.
+    pub fn word_chars(mut self, words: WordChars) -> Self {
+        self.word_chars = Some(words);
+        self
+    }
+
+    /// Register an abbreviation that expands in place when Space or Enter is typed right
+    /// after it, e.g. `sel` to `SELECT * FROM`. Registering the same name again replaces
+    /// its expansion.
+    ///
+    /// Abbreviations only match whole whitespace-separated words.
+    pub fn abbreviation(mut self, name: &str, expansion: &str) -> Self {
+        self.abbreviations.retain(|(n, _)| n != name);
+        self.abbreviations
+            .push((name.to_string(), expansion.to_string()));
+        self
+    }
+
+    /// Insert the closing partner when an opening character in `pairs` is typed, leaving
+    /// the cursor between them, e.g. `&[('(', ')'), ('[', ']'), ('"', '"')]`. Off by
+    /// default.
+    ///
+    /// Typing a closer that's already under the cursor moves past it, and Backspace
+    /// between an empty pair removes both.
+    pub fn auto_pairs(mut self, pairs: &[(char, char)]) -> Self {
+        self.auto_pairs = pairs.to_vec();
+        self
+    }
+
+    /// Print how long each `process_line` call took after its result, dimmed and
+    /// right-aligned.
+    ///
+    /// Only `run` prints the time; `Repl::last_eval_duration` returns it either way.
+    pub fn show_timing(mut self, enabled: bool) -> Self {
+        self.show_timing = enabled;
+        self
+    }
+
+    /// Show the rest of the best history suggestion for the input, dimmed, after the cursor
+    /// (see `Repl::history_suggestions`). Right arrow or Ctrl-E at the end of the input
+    /// accepts it.
+    pub fn autosuggest(mut self, enabled: bool) -> Self {
+        self.autosuggest = enabled;
+        self
+    }
+
+    /// Show a spinner and the elapsed time on the row below the input while `process_line`
+    /// runs for more than a moment, so slow evaluations don't look like a hang.
+    ///
+    /// The spinner is drawn from another thread and removed when the result arrives or the
+    /// evaluation writes to its `EvalContext`.
+    pub fn spinner(mut self, enabled: bool) -> Self {
+        self.spinner = enabled;
+        self
+    }
+
+    /// Number inputs and outputs IPython-style: the prompt becomes `In [n]: ` and `run`
+    /// labels each result `Out[n]: `.
+    ///
+    /// The number increases with every line passed to `process_line`, which can read it with
+    /// `EvalContext::execution_count`. Replaces the prompt set with `prompt`.
+    pub fn numbered(mut self, enabled: bool) -> Self {
+        self.numbered = enabled;
+        self
+    }
+
+    /// Number the lines of multi-line input in a dimmed gutter after the prompt.
+    pub fn line_numbers(mut self, enabled: bool) -> Self {
+        self.line_numbers = enabled;
+        self
+    }
+
+    /// Wrap input wider than the terminal before the last column and draw `indicator` at the
+    /// end of each wrapped row, e.g. a dimmed `↩`, so soft wraps stand out from line breaks.
+    ///
+    /// By default the terminal wraps long input without a marker.
+    pub fn wrap_indicator(mut self, indicator: impl Into<StyledText>) -> Self {
+        self.wrap_indicator = Some(indicator.into());
+        self
+    }
+
+    /// Page output taller than the terminal on the alternate screen instead of printing it.
+    ///
+    /// Space shows the next page, Enter the next line, `b` the previous page and `q` returns
+    /// to the prompt. Applies to results printed by `run` and meta-command output.
+    pub fn pager(mut self, enabled: bool) -> Self {
+        self.pager = enabled;
+        self
+    }
+
+    /// Set where `Repl::set_status` pins the status line (default `StatusPosition::Bottom`).
+    pub fn status_position(mut self, position: StatusPosition) -> Self {
+        self.status_position = position;
+        self
+    }
+
+    /// Set the function that tells `Repl::run` to exit when it returns `true` for a line.
+    pub fn exit_condition(mut self, exit_condition: ExitConditionFunc) -> Self {
+        self.exit_condition = Some(exit_condition);
+        self
+    }
+
+    /// Set the character that introduces meta-commands (default `:`).
+    pub fn command_prefix(mut self, prefix: char) -> Self {
+        self.command_prefix = prefix;
+        self
+    }
+
+    /// Enable csh-style history expansion (`!!`, `!N`, `!-N`, `!prefix`) before processing.
+    pub fn history_expansion(mut self, enabled: bool) -> Self {
+        self.history_expansion = enabled;
+        self
+    }
+
+    /// Print the line after history expansion when it differs from what was typed.
+    pub fn echo_history_expansion(mut self, enabled: bool) -> Self {
+        self.echo_history_expansion = enabled;
+        self
+    }
+
+    /// Load history from `path` and save new entries back to it.
+    ///
+    /// Entries are timestamped; when saving, entries written by other running instances
+    /// are merged in by time instead of being overwritten.
+    #[cfg(feature = "persistence")]
+    pub fn history_file(self, path: impl Into<PathBuf>) -> Self {
+        self.history_store(Box::new(FileHistory::new(path)))
+    }
+
+    /// Load history from `store` and save new entries back to it.
+    pub fn history_store(mut self, store: Box<dyn HistoryStore>) -> Self {
+        self.history_store = Some(store);
+        self
+    }
+
+    /// Keep only the newest `size` entries in the history store, removing older ones when
+    /// history is loaded and saved. Unlimited by default.
+    pub fn history_size(mut self, size: usize) -> Self {
+        self.history_size = Some(size);
+        self
+    }
+
+    /// Leave lines starting with a space out of history, like bash's `ignorespace`, so they
+    /// can't be recalled or saved. They're still processed as usual.
+    pub fn history_ignore_space(mut self, enabled: bool) -> Self {
+        self.history_ignore_space = enabled;
+        self
+    }
+
+    /// Share history with other `Repl`s built from clones of the same handle.
+    ///
+    /// Takes precedence over `history_file` and `history_store`; open the shared history
+    /// with a store instead.
+    pub fn shared_history(mut self, history: SharedHistory) -> Self {
+        self.shared_history = Some(history);
+        self
+    }
+
+    /// Seed history with `entries`, oldest first, before the first prompt.
+    ///
+    /// Seeded entries can be recalled and expanded like typed ones but are never saved to
+    /// the history store, so they can be reloaded (e.g. from a project file) on every start.
+    /// They're placed before entries submitted since the history was loaded; with
+    /// `shared_history`, seed only the first `Repl` built from the handle.
+    pub fn history_entries<I>(mut self, entries: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.history_entries
+            .extend(entries.into_iter().map(Into::into));
+        self
+    }
+
+    /// Read keyboard input from `/dev/tty` when stdin is redirected.
+    ///
+    /// The piped data is left on stdin for the application to consume. If there is
+    /// no controlling terminal, the REPL falls back to batch mode.
+    pub fn tty_fallback(mut self, enabled: bool) -> Self {
+        self.tty_fallback = enabled;
+        self
+    }
+
+    /// Work well with screen readers: input is read a line at a time with the terminal's
+    /// own line editing, as on a dumb terminal, so nothing is repainted while typing.
+    ///
+    /// Prompts and output are printed without colors, and status line changes are printed
+    /// as plain text on their own line.
+    pub fn screen_reader(mut self, enabled: bool) -> Self {
+        self.screen_reader = enabled;
+        self
+    }
+
+    /// Append a timestamped record of prompts, input and output to the file at `path`.
+    pub fn transcript(mut self, path: impl Into<PathBuf>) -> Self {
+        self.transcript = Some(path.into());
+        self
+    }
+
+    /// Keep the session's prompts, input and output in memory with their styles, for
+    /// `Repl::export_session` to save as ANSI text or HTML.
+    pub fn record_session(mut self, enabled: bool) -> Self {
+        self.record_session = enabled;
+        self
+    }
+
+    /// Keep the last `max_lines` lines of input and output, and view them with Ctrl-X
+    /// Ctrl-O in a pager on the alternate screen, for terminals with little scrollback.
+    pub fn scrollback(mut self, max_lines: usize) -> Self {
+        self.scrollback = Some(max_lines);
+        self
+    }
+
+    /// Also copy killed text to the system clipboard using OSC 52 escape sequences.
+    ///
+    /// This works over SSH, but only in terminals that support OSC 52 (and some require it to
+    /// be enabled).
+    pub fn clipboard(mut self, enabled: bool) -> Self {
+        self.clipboard = enabled;
+        self
+    }
+
+    /// Ask the terminal to mark pasted text, so it's inserted as typed text instead of run as
+    /// keys. Enabled by default where the terminal supports it.
+    ///
+    /// Line breaks in a paste continue the input rather than submitting it, and control
+    /// characters and escape sequences are removed, so pasting can't run a command or send
+    /// sequences to the terminal.
+    pub fn bracketed_paste(mut self, enabled: bool) -> Self {
+        self.bracketed_paste = enabled;
+        self
+    }
+
+    /// Set a function to see each bracketed paste before it's inserted, e.g. to reformat
+    /// copied code or to refuse a paste that's too large.
+    ///
+    /// It receives the pasted text with control characters and escape sequences already
+    /// removed, and returns the text to insert instead, or `None` to discard the paste.
+    pub fn paste_func(mut self, paste_func: PasteFunc) -> Self {
+        self.paste_func = Some(paste_func);
+        self
+    }
+
+    /// Set which carriage returns and line feeds end a line. Defaults to `LineEnding::Auto`,
+    /// where `\r`, `\n` and `\r\n` each end one line.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Stop reading input after `timeout` passes without any, e.g. to lock an unattended
+    /// admin console. The read returns `InternalError::TimedOut`, leaving the input on
+    /// screen; `run` and `lines` pass the error on.
+    ///
+    /// Only applies when reading from a terminal.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Convert submitted input to Unicode Normalization Form C before it's processed and
+    /// recorded, so text typed as a letter and combining marks compares equal to the same
+    /// text typed precomposed, e.g. when looking up identifiers.
+    #[cfg(feature = "normalize")]
+    pub fn normalize_input(mut self, enabled: bool) -> Self {
+        self.normalize_input = enabled;
+        self
+    }
+
+    /// Enable mouse reporting so clicking in the input moves the cursor there, and the wheel
+    /// over the input scrolls through history.
+    ///
+    /// While enabled, most terminals only select text with the mouse when Shift is held.
+    #[cfg(feature = "mouse")]
+    pub fn mouse(mut self, enabled: bool) -> Self {
+        self.mouse = enabled;
+        self
+    }
+
+    /// Enable the kitty keyboard protocol in terminals that support it.
+    ///
+    /// Keys such as Esc and Shift-Tab are then reported unambiguously, so a lone Esc no longer
+    /// waits for the next key.
+    pub fn kitty_keyboard(mut self, enabled: bool) -> Self {
+        self.kitty_keyboard = enabled;
+        self
+    }
+
+    /// Ask the terminal to report when its window gains or loses focus, for hooks registered
+    /// with `Repl::register_focus_hook`.
+    pub fn focus_reporting(mut self, enabled: bool) -> Self {
+        self.focus_reporting = enabled;
+        self
+    }
+
+    /// Leave Ctrl-S/Ctrl-Q software flow control to the terminal.
+    ///
+    /// Disabled by default, so Ctrl-S doesn't freeze the terminal until Ctrl-Q is pressed.
+    pub fn flow_control(mut self, enabled: bool) -> Self {
+        self.flow_control = enabled;
+        self
+    }
+
+    /// Leave Ctrl-C, Ctrl-Z and Ctrl-\\ to the terminal, which sends the process SIGINT,
+    /// SIGTSTP and SIGQUIT.
+    ///
+    /// By default the line editor reads these keys itself: Ctrl-C discards the input and
+    /// Ctrl-Z suspends the process.
+    pub fn signals(mut self, enabled: bool) -> Self {
+        self.signals = enabled;
+        self
+    }
+
+    /// Read keyboard input from the script at `path` instead of the terminal, pausing for
+    /// `delay` before each key.
+    ///
+    /// Input goes through the full line editor, so this suits demos and regression tests of
+    /// interpreter frontends. Each newline in the script presses Enter; other keys are written
+    /// as `<Up>`, `<Tab>`, `<C-a>` and so on, with `\` escaping the next character and
+    /// `\xHH` inserting a raw byte. Input ends when the script runs out.
+    pub fn replay(mut self, path: impl Into<PathBuf>, delay: Duration) -> Self {
+        self.replay = Some((path.into(), delay));
+        self
+    }
+
+    /// Build the `Repl`, entering raw mode if attached to a terminal.
+    ///
+    /// On a dumb terminal raw mode is skipped, and input is read a line at a time without
+    /// editing. Otherwise the terminal is restored if the process is killed by SIGTERM,
+    /// SIGHUP, or SIGQUIT (see `term_manager::restore_on_fatal_signals`).
+    ///
+    /// Settings from the environment variables listed in `Config::from_env` take precedence
+    /// over the builder's. Invalid values are skipped with a warning on stderr.
+    pub fn build(self) -> Result<Repl<T>> {
+        #[cfg(feature = "config")]
+        let builder = self.config(&Config::from_env_or_warn());
+        #[cfg(not(feature = "config"))]
+        let builder = self;
+        builder.build_repl()
+    }
+
+    /// Build a `Repl` that reads only fed input, ignoring the terminal and the environment,
+    /// so tests behave the same however they're run. Its output is kept for `take_output`.
+    #[cfg(test)]
+    pub(crate) fn build_detached(mut self) -> Result<Repl<T>> {
+        self.terminal = false;
+        self.build_repl()
+    }
+
+    fn build_repl(self) -> Result<Repl<T>> {
+        let dumb =
+            (self.screen_reader || !TermCaps::detect().cursor_control) && self.replay.is_none();
+        let mut tmanager = if dumb || !self.terminal {
+            None
+        } else if term_manager::stdin_is_tty() {
+            let tmanager = TermManager::new().map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "failed to initialized Repl",
+                    e,
+                )))
+            })?;
+            Some(tmanager)
+        } else if self.tty_fallback {
+            TermManager::from_tty().ok()
+        } else {
+            None
+        };
+        if tmanager.is_some() {
+            term_manager::restore_on_fatal_signals().map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "unable to catch fatal signals",
+                    e,
+                )))
+            })?;
+        }
+        if self.kitty_keyboard
+            && let Some(tmanager) = tmanager.as_mut()
+        {
+            tmanager.enable_kitty_keyboard().map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "failed to enable kitty keyboard protocol",
+                    e,
+                )))
+            })?;
+        }
+        if self.focus_reporting
+            && let Some(tmanager) = tmanager.as_mut()
+        {
+            tmanager.enable_focus_reporting().map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "failed to enable focus reporting",
+                    e,
+                )))
+            })?;
+        }
+        if self.flow_control
+            && let Some(tmanager) = tmanager.as_mut()
+        {
+            tmanager.set_flow_control(true).map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "failed to enable flow control",
+                    e,
+                )))
+            })?;
+        }
+        // Carriage returns are needed as read to tell `\r\n` from two line endings.
+        if self.line_ending != LineEnding::Each
+            && let Some(tmanager) = tmanager.as_mut()
+        {
+            tmanager.set_cr_to_newline(false).map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "failed to configure line endings",
+                    e,
+                )))
+            })?;
+        }
+        if self.signals
+            && let Some(tmanager) = tmanager.as_mut()
+        {
+            tmanager.set_signals(true).map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "failed to enable signal keys",
+                    e,
+                )))
+            })?;
+        }
+        #[cfg(feature = "mouse")]
+        if self.mouse
+            && let Some(tmanager) = tmanager.as_mut()
+        {
+            tmanager.enable_mouse().map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "failed to enable mouse reporting",
+                    e,
+                )))
+            })?;
+        }
+        let history = match self.shared_history {
+            Some(history) => history,
+            None => SharedHistory::from(History::open(self.history_store, self.history_size)?),
+        };
+        if !self.history_entries.is_empty() {
+            history.lock().preload(self.history_entries);
+        }
+        let color_depth = if self.screen_reader {
+            ColorDepth::NoColor
+        } else {
+            tmanager
+                .as_ref()
+                .map_or_else(ColorDepth::detect, TermManager::color_depth)
+        };
+        let transcript = self
+            .transcript
+            .as_deref()
+            .map(Transcript::open)
+            .transpose()?;
+        let replay = self
+            .replay
+            .map(|(path, delay)| Replay::open(&path, delay))
+            .transpose()?;
+        let lines = vec![Line::new()];
+        let current_line = 0;
+        let input_state = InputType::Normal;
+
+        let mut repl = Repl {
+            tmanager,
+            #[cfg(test)]
+            output: (!self.terminal).then(Vec::new),
+            dumb: dumb && self.terminal && term_manager::stdin_is_tty(),
+            screen_reader: self.screen_reader,
+            lines,
+            history,
+            history_seen: 0,
+            last_entry: None,
+            committed_line: 0,
+            current_line,
+            unread: VecDeque::new(),
+            input_state,
+            clear_line_keys: self.clear_line_keys,
+            word_chars: self.word_chars,
+            abbreviations: self.abbreviations,
+            auto_pairs: self.auto_pairs,
+            clear_line_progress: 0,
+            selection: None,
+            editing: false,
+            overwrite: false,
+            process_line: self.process_line,
+            prompt_func: self.prompt_func,
+            modes: Vec::new(),
+            last_failed: false,
+            pre_input_hooks: Vec::new(),
+            post_output_hooks: Vec::new(),
+            exit_hooks: Vec::new(),
+            focus_hooks: Vec::new(),
+            tick_hooks: Vec::new(),
+            focused: true,
+            validator: self.validator,
+            validation_message: None,
+            completion_doc: None,
+            indent: self.indent,
+            completer: self.completer,
+            highlighter: self.highlighter,
+            signature_help: self.signature_help,
+            signature_shown: false,
+            tab_mode: self.tab_mode,
+            edit_mode: self.edit_mode,
+            vi: ViState::default(),
+            max_length: self.max_length,
+            bell_style: self.bell_style,
+            show_timing: self.show_timing,
+            spinner: self.spinner,
+            autosuggest: self.autosuggest,
+            suggestion: None,
+            bidi_explicit: false,
+            correction: None,
+            last_eval_duration: None,
+            numbered: self.numbered,
+            line_numbers: self.line_numbers,
+            pager: self.pager,
+            execution_count: 1,
+            exit_condition: self.exit_condition,
+            commands: MetaCommands::new(self.command_prefix),
+            kill_ring: KillRing::new(),
+            last_argument: None,
+            keyboard_macro: KeyboardMacro::new(),
+            key_bindings: Vec::new(),
+            clipboard: self.clipboard,
+            bracketed_paste: self.bracketed_paste,
+            paste_func: self.paste_func,
+            #[cfg(feature = "normalize")]
+            normalize_input: self.normalize_input,
+            line_ending: self.line_ending,
+            after_cr: false,
+            idle_timeout: self.idle_timeout,
+            history_expansion: self.history_expansion,
+            echo_history_expansion: self.echo_history_expansion,
+            history_ignore_space: self.history_ignore_space,
+            search_prefix: None,
+            numeric_arg: None,
+            next_history_line: None,
+            initial_input: None,
+            rendered_row: 0,
+            rendered_rows: 1,
+            wrap_indicator: self.wrap_indicator,
+            padded_continuation: self.continuation_prompt.is_none(),
+            status: None,
+            status_position: self.status_position,
+            continuation_prompt: self
+                .continuation_prompt
+                .unwrap_or_else(|| padding(&self.prompt)),
+            prompt: self.prompt,
+            color_depth,
+            banner: self.banner,
+            welcome_msg: self.welcome_msg,
+            transcript,
+            session: self.record_session.then(String::new),
+            scrollback: self.scrollback.map(Scrollback::new),
+            replay,
+        };
+        repl.sync_history();
+        if repl.numbered {
+            repl.set_prompt(numbered_prompt(repl.execution_count));
+        }
+
+        Ok(repl)
+    }
+}
+
+impl Default for ReplBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repl {
+    /// Create a new REPL instance.
+    ///
+    /// ### Arguments
+    ///
+    /// * `prompt` - The prompt string to display
+    /// * `banner` - Startup banner to display.
+    /// * `welcome_msg` - Welcome message to display.
+    /// * `process_line` - Function to process completed lines
+    /// * `validator` - Function to check input when Enter is pressed
+    pub fn new(
+        prompt: String,
+        banner: String,
+        welcome_msg: String,
+        process_line: ProcessLineFunc,
+        validator: ValidatorFunc,
+    ) -> Result<Self> {
+        ReplBuilder::new()
+            .prompt(prompt)
+            .banner(banner)
+            .welcome_msg(welcome_msg)
+            .process_line(process_line)
+            .validator(validator)
+            .build()
+    }
+
+    /// Create a `ReplBuilder` for configuring a new REPL.
+    pub fn builder() -> ReplBuilder {
+        ReplBuilder::new()
+    }
+}
+
+impl<T> Repl<T> {
+    /// Returns `true` if the REPL is attached to a terminal, replaying a script or reading
+    /// fed input, `false` in batch mode.
+    pub fn is_interactive(&self) -> bool {
+        self.tmanager.is_some() || self.replay.is_some()
+    }
+
+    /// Returns a handle that stops a read in progress from another thread, e.g. to shut the
+    /// REPL down. The read returns `InternalError::Cancelled`, which ends `run` and `lines`.
+    ///
+    /// Fails in batch mode, where reads can't be cancelled.
+    pub fn canceller(&mut self) -> Result<Canceller> {
+        self.tmanager_mut()?.canceller().map_err(|e| {
+            Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+                "unable to create a canceller",
+                e,
+            )))
+        })
+    }
+
+    /// Prints the welcome banner and message. Skipped in batch mode.
+    pub fn print_welcome(&mut self) {
+        if self.is_interactive() || self.dumb {
+            let welcome = format!("{}\n{}\n", self.banner, self.welcome_msg);
+            let _ = self.write(&welcome);
+        }
+    }
+
+    /// Prints the REPL prompt. Skipped in batch mode.
+    pub fn print_prompt(&mut self) {
+        self.refresh_prompt();
+        self.rendered_rows = 1;
+        if self.is_interactive() || self.dumb {
+            let _ = self.write(&self.render(&self.prompt));
+        }
+    }
+
+    /// Repaint the prompt and the input being edited, e.g. after changing the prompt or
+    /// writing to the terminal directly. Does nothing unless input is being read from a
+    /// terminal.
+    pub fn redraw(&mut self) -> Result<()> {
+        if !self.editing || self.tmanager.is_none() {
+            return Ok(());
+        }
+        self.redraw_current_line()?;
+        self.flush()
+    }
+
+    /// Print `text` followed by a newline, e.g. a notification from a hook.
+    ///
+    /// While input is being read from a terminal, the text goes above the prompt and the
+    /// prompt and input are drawn again below it, so they aren't garbled.
+    pub fn print(&mut self, text: impl Into<StyledText>) -> Result<()> {
+        let text = self.render(&text.into());
+        let editing = self.editing && self.tmanager.is_some();
+        let mut out = String::new();
+        if editing {
+            out.push_str(&format!(
+                "{}\r{}",
+                cursor::up(self.rendered_row),
+                clear::TO_END_OF_SCREEN
+            ));
+        }
+        out.push_str(&text);
+        if !text.ends_with('\n') {
+            out.push('\n');
+        }
+        self.write(&out)?;
+        if editing {
+            self.rendered_row = 0;
+            self.redraw_current_line()?;
+        }
+        self.flush()
+    }
+
+    /// Set the terminal window title, e.g. to the interpreter name or current file.
+    ///
+    /// Does nothing in batch mode.
+    pub fn set_title(&mut self, title: &str) -> Result<()> {
+        if let Some(tmanager) = self.tmanager.as_mut() {
+            tmanager.set_title(title).map_err(|e| {
+                Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                    "unable to set title",
+                    e,
+                )))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Gets a line by index from the history.
+    pub fn get_line(&self, index: usize) -> Option<&Line> {
+        self.lines.get(index)
+    }
+
+    /// Register a meta-command such as `:load`, intercepted before `process_line`.
+    ///
+    /// Once a command is registered, `:help` lists all commands with their descriptions.
+    pub fn register_command(&mut self, name: &str, description: &str, handler: MetaCommandFunc) {
+        self.commands.register(name, description, handler);
+    }
+
+    /// Returns the meta-commands matching a partially typed command, for completion.
+    pub fn command_completions(&self, input: &str) -> Vec<String> {
+        self.commands.completions(input)
+    }
+
+    /// Change the prompt shown from the next read, e.g. to reflect interpreter state.
+    ///
+    /// A default continuation prompt is resized to stay aligned with the new prompt.
+    pub fn set_prompt(&mut self, prompt: impl Into<StyledText>) {
+        self.prompt = prompt.into();
+        if self.padded_continuation {
+            self.continuation_prompt = padding(&self.prompt);
+        }
+    }
+
+    /// Change the prompt shown before continuation lines of multi-line input.
+    pub fn set_continuation_prompt(&mut self, prompt: impl Into<StyledText>) {
+        self.continuation_prompt = prompt.into();
+        self.padded_continuation = false;
+    }
+
+    /// Change how keys edit the input from the next read.
+    pub fn set_edit_mode(&mut self, mode: EditMode) {
+        self.edit_mode = mode;
+    }
+
+    /// Pre-fill the next input with `text`, with the cursor at byte offset `cursor`.
+    ///
+    /// The text can be edited before it's submitted, e.g. to fix a line that failed.
+    /// Ignored in batch mode.
+    pub fn set_initial_input(&mut self, text: &str, cursor: usize) {
+        self.initial_input = Some(Line::with_cursor(text, cursor));
+    }
+
+    /// Returns the number of the next line passed to `process_line`, starting at 1.
+    pub fn execution_count(&self) -> usize {
+        self.execution_count
+    }
+
+    /// Returns how long the last `process_line` call took.
+    pub fn last_eval_duration(&self) -> Option<Duration> {
+        self.last_eval_duration
+    }
+
+    /// Returns the history entries, oldest first.
+    pub fn history(&self) -> std::vec::IntoIter<HistoryEntry> {
+        self.history.lock().entries().to_vec().into_iter()
+    }
+
+    /// Returns the history entries matching `pattern` in `mode`, oldest first, e.g. for a
+    /// `:history /pattern/` meta-command.
+    ///
+    /// Fails with a user error if `pattern` isn't a valid regular expression.
+    pub fn history_search(&self, pattern: &str, mode: SearchMode) -> Result<Vec<HistoryEntry>> {
+        let matches = history::matcher(pattern, mode)?;
+        let history = self.history.lock();
+        Ok(history
+            .entries()
+            .iter()
+            .filter(|entry| matches(&entry.text))
+            .cloned()
+            .collect())
+    }
+
+    /// Add `text` to history as if it had been submitted. Blank text is ignored.
+    ///
+    /// The entry is saved to the history store with the next save.
+    pub fn add_history_entry(&mut self, text: &str) {
+        self.history.lock().push(text);
+        self.sync_history();
+    }
+
+    /// Returns up to `limit` distinct history entries starting with `prefix`, best first.
+    ///
+    /// Entries are ranked by how often they were submitted, with recent submissions counting
+    /// more than old ones, so a command used every session outranks one typed once just
+    /// before. `ReplBuilder::autosuggest` shows the top suggestion while typing.
+    pub fn history_suggestions(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let mut suggestions = self.history.lock().suggestions(prefix);
+        suggestions.truncate(limit);
+        suggestions
+    }
+
+    /// Remove all history entries, in this and every `Repl` sharing the history.
+    ///
+    /// Entries already saved to the history store are kept there.
+    pub fn clear_history(&mut self) {
+        self.history.lock().clear();
+        self.sync_history();
+    }
+
+    /// Save new history entries to the history store, if one is configured.
+    ///
+    /// This also happens when the `Repl` is dropped, ignoring errors.
+    pub fn save_history(&mut self) -> Result<()> {
+        self.history.lock().save()
+    }
+
+    /// Shut down: run the exit hooks, remove the status line, save history and restore the
+    /// terminal's original settings, returning the first error.
+    ///
+    /// Dropping the `Repl` does the same except for the exit hooks, ignoring errors.
+    pub fn close(mut self) -> Result<()> {
+        let hooks = self.run_exit_hooks();
+        let status = self.clear_status().and_then(|_| self.flush());
+        let history = self.save_history();
+        self.save_mode_histories();
+        let terminal = match self.tmanager.take() {
+            Some(mut tmanager) => tmanager.restore().map_err(|e| {
+                Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                    "unable to restore the terminal",
+                    e,
+                )))
+            }),
+            None => Ok(()),
+        };
+        hooks.and(status).and(history).and(terminal)
+    }
+
+    /// Read and process input until a complete line is entered, returning the value from
+    /// `process_line`.
+    ///
+    /// Meta-commands print their output and input is read again after a new prompt. Values
+    /// aren't printed or recorded in the transcript; that's left to the application.
+    ///
+    /// Returns `InternalError::Eof` once input is exhausted in batch mode, when Ctrl-D is
+    /// pressed on empty input, or when a meta-command asks to quit, `InternalError::Cancelled`
+    /// if a `Canceller` stops the read, and `InternalError::TimedOut` after the idle timeout.
+    pub fn process_input(&mut self) -> Result<T> {
+        loop {
+            let line = self.read_input()?;
+            match self.eval(line)? {
+                Evaluation::Value(value) => return Ok(value),
+                Evaluation::Command => self.print_prompt(),
+                Evaluation::Quit => return Err(Error::Internal(InternalError::Eof)),
+            }
+        }
+    }
+
+    /// Returns an iterator over completed input lines.
+    ///
+    /// Each call to `next` prints the prompt and reads one complete line without passing it
+    /// to `process_line`. Iteration ends at end of input or when the exit condition is met.
+    pub fn lines(&mut self) -> Lines<'_, T> {
+        Lines {
+            repl: self,
+            done: false,
+        }
+    }
+
+    /// Evaluate a complete line, dispatching meta-commands before `process_line`.
+    ///
+    /// Meta-command output is printed here, since it isn't a `T`.
+    fn eval(&mut self, line: String) -> Result<Evaluation<T>> {
+        let line = if self.history_expansion {
+            self.expand_history(line)?
+        } else {
+            line
+        };
+
+        if self.commands.is_command(&line) {
+            let history = &self.lines[..self.lines.len().saturating_sub(1)];
+            return match self.commands.dispatch(&line, history)? {
+                CommandAction::Output(output) => {
+                    self.record_output(&output);
+                    self.print_output(&output)?;
+                    Ok(Evaluation::Command)
+                }
+                CommandAction::Quit => Ok(Evaluation::Quit),
+            };
+        }
+
+        // Evaluation gets a normal terminal, e.g. for subprocesses that read input.
+        self.flush()?;
+        self.set_raw_mode(false)?;
+        let interrupt = self
+            .tmanager
+            .as_ref()
+            .map(|_| InterruptGuard::new())
+            .transpose()
+            .map_err(|e| {
+                Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                    "unable to catch Ctrl-C",
+                    e,
+                )))
+            })?;
+        let spinner =
+            (self.spinner && self.tmanager.is_some()).then(|| Spinner::start(self.color_depth));
+        let mut ctx = EvalContext::new(
+            self.tmanager.as_mut(),
+            interrupt.as_ref(),
+            self.execution_count,
+            self.color_depth,
+            self.transcript.is_some() || self.session.is_some() || self.scrollback.is_some(),
+            spinner,
+        );
+        let start = Instant::now();
+        let result = (self.process_line)(line, &mut ctx).map(Evaluation::Value);
+        self.last_eval_duration = Some(start.elapsed());
+        self.last_failed = result.is_err();
+        self.correction = match &result {
+            Err(Error::User(e)) => e.suggestions.first().cloned(),
+            _ => None,
+        };
+        if let Some(streamed) = ctx.into_recorded()
+            && !streamed.is_empty()
+        {
+            self.record_output(streamed.trim_end_matches('\n'));
+        }
+        if interrupt
+            .as_ref()
+            .is_some_and(InterruptGuard::is_interrupted)
+            && let Some(tmanager) = self.tmanager.as_mut()
+        {
+            // Keys typed while the cancelled command ran were meant for it. This is best
+            // effort, so a failure doesn't lose the result or leave the terminal cooked.
+            let _ = tmanager.discard_input();
+        }
+        drop(interrupt);
+        self.execution_count += 1;
+        if self.numbered && self.modes.is_empty() {
+            self.set_prompt(numbered_prompt(self.execution_count));
+        }
+        self.set_raw_mode(true)?;
+        result
+    }
+
+    /// Leave or re-enter raw mode. Does nothing in batch mode.
+    fn set_raw_mode(&mut self, raw: bool) -> Result<()> {
+        let Some(tmanager) = self.tmanager.as_mut() else {
+            return Ok(());
+        };
+        let res = if raw {
+            tmanager.resume_raw_mode()
+        } else {
+            tmanager.suspend_raw_mode()
+        };
+        res.map_err(|e| {
+            Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                "unable to set terminal mode",
+                e,
+            )))
+        })
+    }
+
+    /// Expand history references in the line just read, replacing its history entry.
+    fn expand_history(&mut self, line: String) -> Result<String> {
+        let history = self.history.lock();
+        let end = self.last_entry.unwrap_or(history.entries().len());
+        let expanded = history::expand(&line, &history.entries()[..end])?;
+        drop(history);
+        if expanded == line {
+            return Ok(line);
+        }
+
+        if self.echo_history_expansion {
+            self.record_output(&expanded);
+            self.write(&format!("{}\n", expanded))?;
+        }
+        if let Some(committed) = self.lines.get_mut(self.committed_line) {
+            committed.cursor_pos = expanded.len();
+            committed.text = expanded.clone();
+        }
+        if let Some(index) = self.last_entry {
+            self.history.lock().replace(index, &expanded);
+        }
+        Ok(expanded)
+    }
+
+    /// Returns `true` if the exit condition is set and matches the line.
+    fn should_exit(&mut self, line: &str) -> bool {
+        self.exit_condition
+            .as_mut()
+            .is_some_and(|exit_condition| exit_condition(line))
+    }
+
+    /// Read input until a complete line is entered, without processing it.
+    fn read_input(&mut self) -> Result<String> {
+        self.sync_history();
+        if !self.is_interactive() {
+            return self.read_batch_input();
+        }
+        if let Some(index) = self.next_history_line.take() {
+            self.current_line = index;
+            self.redraw_current_line()?;
+        }
+        if let Some(line) = self.initial_input.take() {
+            self.current_line = self.lines.len() - 1;
+            self.lines[self.current_line] = line;
+            self.redraw_current_line()?;
+        } else if self.correction.is_some() {
+            self.redraw_current_line()?;
+        }
+        self.flush()?;
+
+        let bracketed_paste = self.bracketed_paste
+            && self
+                .tmanager
+                .as_ref()
+                .is_some_and(|tmanager| tmanager.caps().bracketed_paste);
+        if bracketed_paste {
+            self.write(screen::BRACKETED_PASTE_ON)?;
+        }
+        if self.overwrite {
+            self.write(cursor::BLOCK)?;
+        }
+        self.editing = true;
+        let finished_line = self.edit_until_submitted();
+        self.editing = false;
+        // Each input starts in vi insert mode.
+        self.set_vi_command(false)?;
+        // Output and other programs get the usual cursor and paste.
+        if bracketed_paste {
+            self.write(screen::BRACKETED_PASTE_OFF)?;
+        }
+        if self.overwrite {
+            self.write(cursor::DEFAULT_SHAPE)?;
+        }
+        if bracketed_paste || self.overwrite || self.edit_mode == EditMode::Vi {
+            self.flush()?;
+        }
+        finished_line
+    }
+
+    /// Handle keys until the input is submitted, returning it.
+    fn edit_until_submitted(&mut self) -> Result<String> {
+        loop {
+            let event = match self.read_event() {
+                Err(Error::Internal(InternalError::TimedOut)) => {
+                    self.leave_input()?;
+                    return Err(Error::Internal(InternalError::TimedOut));
+                }
+                event => event?,
+            };
+            let sequence = match event {
+                Event::Key { sequence, .. } => sequence,
+                Event::Paste(text) => {
+                    self.paste(&text)?;
+                    continue;
+                }
+                Event::FocusGained | Event::FocusLost => {
+                    self.focused = matches!(event, Event::FocusGained);
+                    self.run_focus_hooks()?;
+                    // Hooks may have changed the prompt.
+                    self.redraw_current_line()?;
+                    continue;
+                }
+                // The line is laid out for the current width each time it's drawn.
+                Event::Resize { .. } => continue,
+                Event::Cancelled => {
+                    self.leave_input()?;
+                    return Err(Error::Internal(InternalError::Cancelled));
+                }
+            };
+            let had_message = self.validation_message.take().is_some();
+            if self.completion_doc.take().is_some() || had_message {
+                self.redraw_current_line()?;
+            }
+            if let ReplState::Break = self.handle_sequence(&sequence)? {
+                let finished_line = self
+                    .get_line(self.current_line)
+                    .map(|l| l.text.clone())
+                    .unwrap_or_default();
+                let finished_line = self.normalize(finished_line);
+                self.commit_line(&finished_line);
+                self.correction = None;
+                if self.bidi_explicit {
+                    self.write(screen::BIDI_IMPLICIT)?;
+                    self.bidi_explicit = false;
+                }
+                self.flush()?;
+                return Ok(finished_line);
+            }
+        }
+    }
+
+    /// Move below the input when reading stops without it being submitted, leaving it on
+    /// screen with output starting on the next row.
+    fn leave_input(&mut self) -> Result<()> {
+        let below = self.rows_below_cursor();
+        self.write(&format!("{}\r\n", cursor::down(below)))?;
+        self.clear_signature()?;
+        self.flush()
+    }
+
+    /// Read lines from stdin until a complete line is formed, without any terminal handling.
+    ///
+    /// On a dumb terminal the continuation prompt is printed before each further line.
+    fn read_batch_input(&mut self) -> Result<String> {
+        let mut input = String::new();
+
+        loop {
+            if self.dumb {
+                if !input.is_empty() {
+                    self.write(&self.continuation_prompt.render(self.color_depth))?;
+                }
+                self.flush()?;
+            }
+            let mut buf = String::new();
+            let bytes_read = io::stdin().lock().read_line(&mut buf).map_err(|e| {
+                Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+                    "error reading from stdin",
+                    e,
+                )))
+            })?;
+
+            if bytes_read == 0 {
+                // Feed any trailing incomplete input before reporting EOF.
+                if input.is_empty() {
+                    return Err(Error::Internal(InternalError::Eof));
+                }
+                break;
+            }
+
+            if !input.is_empty() {
+                input.push('\n');
+            }
+            input.push_str(buf.trim_end_matches(['\n', '\r']));
+
+            match (self.validator)(&input) {
+                Validation::Complete => break,
+                Validation::Incomplete => {}
+                Validation::Invalid { message } => {
+                    return Err(Error::User(UserError::new(message)));
+                }
+            }
+        }
+
+        let input = self.normalize(input);
+        if let Some(line) = self.lines.get_mut(self.current_line) {
+            line.text = input.clone();
+            line.cursor_pos = line.text.len();
+        }
+        self.commit_line(&input);
+
+        Ok(input)
+    }
+
+    /// Returns submitted `text` in NFC if `normalize_input` is enabled.
+    fn normalize(&self, text: String) -> String {
+        #[cfg(feature = "normalize")]
+        if self.normalize_input {
+            return text.nfc().collect();
+        }
+        text
+    }
+
+    /// Record the finished line in history and the transcript, and start a new empty line.
+    fn commit_line(&mut self, text: &str) {
+        self.record_input(text);
+
+        // A recalled line is submitted as a new entry, in place of the line being typed.
+        let last = self.lines.len() - 1;
+        if self.current_line != last {
+            self.lines[last] = Line::from(text);
+            self.current_line = last;
+        }
+        self.sync_history();
+        self.revert_recalled_lines();
+
+        let mut history = self.history.lock();
+        self.last_entry = if self.history_ignore_space && text.starts_with(' ') {
+            None
+        } else {
+            history.push(text)
+        };
+        self.history_seen = history.entries().len();
+        drop(history);
+
+        self.committed_line = self.lines.len() - 1;
+        if self.last_entry.is_some() {
+            self.lines.push(Line::new());
+        } else {
+            // Blank and ignored lines aren't recorded, so they aren't recalled either.
+            self.lines[self.committed_line] = Line::new();
+        }
+        self.current_line = self.lines.len() - 1;
+    }
+
+    /// Undo edits to recalled lines, which only change a working copy of the history entry
+    /// until they're submitted.
+    fn revert_recalled_lines(&mut self) {
+        let history = self.history.lock();
+        for (line, entry) in self.lines.iter_mut().zip(history.entries()) {
+            if line.text != entry.text {
+                *line = Line::from(entry.text.as_str());
+            }
+        }
+    }
+
+    /// Mirror entries added to the shared history by other `Repl`s into `lines`.
+    ///
+    /// New entries are inserted before the line being edited. Does nothing while
+    /// navigating history, so indices into `lines` stay valid.
+    fn sync_history(&mut self) {
+        let last = self.lines.len() - 1;
+        if self.current_line != last {
+            return;
+        }
+
+        let history = self.history.lock();
+        if history.entries().len() < self.history_seen {
+            // The history was cleared; drop the mirrored lines.
+            self.lines.drain(..last);
+            self.history_seen = 0;
+            self.last_entry = None;
+            self.committed_line = 0;
+        }
+        let last = self.lines.len() - 1;
+        let new_lines = history.entries()[self.history_seen..]
+            .iter()
+            .map(|e| Line::from(e.text.as_str()));
+        self.lines.splice(last..last, new_lines);
+        self.history_seen = history.entries().len();
+        drop(history);
+
+        self.current_line = self.lines.len() - 1;
+    }
+
+    /// Returns the terminal manager, failing in batch mode.
+    fn tmanager_mut(&mut self) -> Result<&mut TermManager> {
+        self.tmanager
+            .as_mut()
+            .ok_or_else(|| Error::Internal(InternalError::IoRead("stdin is not a terminal".into())))
+    }
+
+    /// Read one key of keyboard input or the replay script, with escape sequences whole.
+    /// Other events are skipped.
+    fn read_sequence(&mut self) -> Result<Vec<u8>> {
+        loop {
+            match self.read_event()? {
+                Event::Key { sequence, .. } => return Ok(sequence),
+                Event::Cancelled => return Err(Error::Internal(InternalError::Cancelled)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Read the next key, paste, resize or focus change from the terminal, or the next key or
+    /// paste of the replay script.
+    fn read_event(&mut self) -> Result<Event> {
+        let sequence = match self.unread.pop_front() {
+            Some(sequence) => sequence,
+            None => loop {
+                self.flush_before_read()?;
+                let sequence = if let Some(replay) = self.replay_input() {
+                    let first = replay
+                        .next_byte()
+                        .ok_or(Error::Internal(InternalError::Eof))?;
+                    term_manager::complete_sequence(first, || Ok(replay.next_byte_of_key()))?
+                } else {
+                    let event = self.wait_event()?;
+                    if let Event::Key { sequence, .. } = &event
+                        && self.skip_line_ending(sequence)
+                    {
+                        continue;
+                    }
+                    self.record_event(&event);
+                    return Ok(event);
+                };
+                if self.skip_line_ending(&sequence) {
+                    continue;
+                }
+                // Keys read again were recorded the first time.
+                self.keyboard_macro.record(&sequence);
+                break sequence;
+            },
+        };
+        if sequence == paste::PASTE_START {
+            return self.read_paste().map(Event::Paste);
+        }
+        Ok(Event::Key {
+            key: input::decode_sequence(&sequence),
+            sequence,
+        })
+    }
+
+    /// Wait for the next event from the terminal, running tick hooks as they come due.
+    /// Returns `InternalError::TimedOut` once the idle timeout passes.
+    fn wait_event(&mut self) -> Result<Event> {
+        let idle_deadline = self.idle_timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let deadline = match (idle_deadline, self.next_tick()) {
+                (Some(idle), Some(tick)) => Some(idle.min(tick)),
+                (idle, tick) => idle.or(tick),
+            };
+            let timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let event = self.tmanager_mut()?.poll_event(timeout).map_err(|e| {
+                Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+                    "error reading from stdin",
+                    e,
+                )))
+            })?;
+            if let Some(event) = event {
+                return Ok(event);
+            }
+            if idle_deadline.is_some_and(|idle| Instant::now() >= idle) {
+                return Err(Error::Internal(InternalError::TimedOut));
+            }
+            self.run_tick_hooks()?;
+        }
+    }
+
+    /// Write queued output before waiting for input, so each key is drawn with one write.
+    ///
+    /// While more keys are already waiting, e.g. during a paste, output keeps queuing so only
+    /// the final state is drawn.
+    fn flush_before_read(&mut self) -> Result<()> {
+        if let Some(tmanager) = self.tmanager.as_ref()
+            && tmanager.queued_len() < MAX_QUEUED_OUTPUT
+            && tmanager.input_pending().unwrap_or(false)
+        {
+            return Ok(());
+        }
+        self.flush()
+    }
+
+    /// Read one byte of keyboard input, or the next byte of the replay script.
+    fn read_byte(&mut self) -> Result<u8> {
+        if let Some(mut sequence) = self.unread.pop_front() {
+            let b = sequence.remove(0);
+            if !sequence.is_empty() {
+                self.unread.push_front(sequence);
+            }
+            return Ok(b);
+        }
+        loop {
+            self.flush_before_read()?;
+            let b = if let Some(replay) = self.replay_input() {
+                replay
+                    .next_byte()
+                    .ok_or(Error::Internal(InternalError::Eof))?
+            } else {
+                self.tmanager_mut()?.read_byte().map_err(|e| {
+                    Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+                        "error reading from stdin",
+                        e,
+                    )))
+                })?
+            };
+            if self.skip_line_ending(&[b]) {
+                continue;
+            }
+            self.keyboard_macro.record(&[b]);
+            return Ok(b);
+        }
+    }
+
+    /// Read printable text that's already waiting, e.g. the rest of a string committed by an
+    /// input method. The first key that isn't text is left to be read next.
+    fn read_pending_text(&mut self) -> Result<String> {
+        let mut text = String::new();
+        while self.unread.is_empty()
+            && self.replay_input().is_none()
+            && let Some(tmanager) = self.tmanager.as_ref()
+            && tmanager.input_pending().unwrap_or(false)
+        {
+            let sequence = self.read_sequence()?;
+            match std::str::from_utf8(&sequence) {
+                Ok(s) if !s.chars().any(char::is_control) => text.push_str(s),
+                _ => {
+                    self.unread.push_back(sequence);
+                    break;
+                }
+            }
+        }
+        Ok(text)
+    }
+
+    /// Queue `text` for the terminal, or write it to stdout in batch mode.
+    ///
+    /// Queued output is written by `flush`, one frame at a time.
+    fn write(&mut self, text: &str) -> Result<()> {
+        if let Some(tmanager) = self.tmanager.as_mut() {
+            tmanager.queue(text);
+            return Ok(());
+        }
+        #[cfg(test)]
+        if let Some(output) = self.output.as_mut() {
+            output.extend_from_slice(text.as_bytes());
+            return Ok(());
+        }
+        io::stdout().write_all(text.as_bytes()).map_err(|e| {
+            Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                "unable to write to stdout",
+                e,
+            )))
+        })
+    }
+
+    /// Returns `text` rendered for the terminal, with links clickable if it supports them.
+    fn render(&self, text: &StyledText) -> String {
+        let hyperlinks = self.color_depth != ColorDepth::NoColor
+            && self
+                .tmanager
+                .as_ref()
+                .is_some_and(|tmanager| tmanager.caps().hyperlinks);
+        if hyperlinks {
+            text.render_hyperlinks(self.color_depth)
+        } else {
+            text.render(self.color_depth)
+        }
+    }
+
+    /// Returns the output written since the last call by a REPL built with `build_detached`.
+    #[cfg(test)]
+    pub(crate) fn take_output(&mut self) -> String {
+        let output = self.output.as_mut().map(std::mem::take).unwrap_or_default();
+        String::from_utf8_lossy(&output).into_owned()
+    }
+
+    /// Flush rendered output to the terminal.
+    fn flush(&mut self) -> Result<()> {
+        let res = match self.tmanager.as_mut() {
+            Some(tmanager) => tmanager.flush(),
+            None => io::stdout().flush().map_err(term_manager::Error::Io),
+        };
+        res.map_err(|e| {
+            Error::Internal(InternalError::IoFlush(ErrorContext::with_source(
+                "unable to flush stdout",
+                e,
+            )))
+        })
+    }
+
+    /// Handles one key: a byte, a UTF-8 character, or a whole escape sequence.
+    fn handle_sequence(&mut self, sequence: &[u8]) -> Result<ReplState> {
+        if let Some(legacy) = key::kitty_to_legacy(sequence) {
+            // Kitty keyboard protocol: re-read the key in the legacy encoding, so modes and
+            // chords see it as they would without the protocol.
+            if !legacy.is_empty() {
+                self.unread.push_front(legacy);
+            }
+            return Ok(ReplState::Continue);
+        }
+        if let InputType::Quoted = self.input_state {
+            // The key after Ctrl-V is inserted as sent, control characters and all, without
+            // its binding.
+            self.input_state = InputType::Normal;
+            self.search_prefix = None;
+            self.kill_ring.last_yank = None;
+            let text =
+                String::from_utf8_lossy(sequence).repeat(self.numeric_arg.take().unwrap_or(1));
+            return self.insert_text(&text);
+        }
+        if let InputType::CharSearch { backward } = self.input_state {
+            self.input_state = InputType::Normal;
+            let count = self.numeric_arg.take().unwrap_or(1);
+            self.search_char(sequence, backward, count)?;
+            return Ok(ReplState::Continue);
+        }
+        if let InputType::CtrlX = self.input_state {
+            self.input_state = InputType::Normal;
+            let count = self.numeric_arg.take().unwrap_or(1);
+            match sequence {
+                // Ctrl-X Ctrl-E = edit in $EDITOR
+                [0x05] => self.edit_in_editor()?,
+                // Ctrl-X Ctrl-O = view recent output
+                [0x0F] => self.view_scrollback()?,
+                // Ctrl-X ( and Ctrl-X ) = record a keyboard macro, Ctrl-X e = replay it
+                [b'('] => self.start_macro(),
+                [b')'] => self.end_macro()?,
+                [b'e'] => self.play_macro(count)?,
+                // Ctrl-G = abort the chord
+                [0x07] => self.bell()?,
+                _ => {}
+            }
+            return Ok(ReplState::Continue);
+        }
+        if self.edit_mode == EditMode::Vi
+            && let Some(state) = self.handle_vi_key(sequence)?
+        {
+            return Ok(state);
+        }
+        // Alt-. walks back through history only while pressed again and again.
+        if sequence != b"\x1b." {
+            self.last_argument = None;
+        }
+        if self.clear_line_chord(sequence)? || self.run_key_binding(sequence)? {
+            return Ok(ReplState::Continue);
+        }
+        if let Some(state) = self.handle_selection(sequence)? {
+            return Ok(state);
+        }
+        self.handle_key(sequence)
+    }
+
+    /// Handles a key with its usual binding.
+    fn handle_key(&mut self, sequence: &[u8]) -> Result<ReplState> {
+        match *sequence {
+            [0x1B, b'[', .., final_byte] if (0x40..=0x7E).contains(&final_byte) => {
+                self.handle_csi_sequence(&sequence[1..])?;
+            }
+            [0x1B, b'O', c] => {
+                // SS3 arrow keys, sent by some terminals
+                for _ in 0..self.numeric_arg.take().unwrap_or(1) {
+                    self.handle_escape_sequence(c)?;
+                }
+            }
+            [0x1B, 0x1D] => {
+                // Alt-Ctrl-] = move back to a character, keeping any numeric argument
+                self.input_state = InputType::CharSearch { backward: true };
+            }
+            [0x1B, c @ b'0'..=b'9'] => {
+                // Alt-digit = numeric argument
+                let digit = (c - b'0') as usize;
+                let arg = self.numeric_arg.unwrap_or(0) * 10 + digit;
+                self.numeric_arg = Some(arg.min(MAX_NUMERIC_ARG));
+            }
+            [0x1B, c, ..] => {
+                for _ in 0..self.numeric_arg.take().unwrap_or(1) {
+                    self.handle_alt_input(c)?;
+                }
+            }
+            // A lone Escape has no binding.
+            [] | [0x1B] => {}
+            // Ctrl-G = abort, before any numeric argument repeats it
+            [0x07] => self.abort()?,
+            // Ctrl-X, Ctrl-V and Ctrl-] leave a numeric argument for the key after them
+            [c @ (0x18 | 0x16 | 0x1D)] => return self.handle_normal_input(c),
+            // A byte that isn't a whole character, such as a UTF-8 lead byte whose character
+            // was cut short, is dropped rather than inserted as Latin-1 or a C1 control.
+            [c] if !c.is_ascii() => {}
+            [c] => return self.handle_repeated_input(c),
+            _ => {
+                // A multi-byte UTF-8 character, dropped like a lone byte if cut short
+                let Ok(text) = std::str::from_utf8(sequence) else {
+                    return Ok(ReplState::Continue);
+                };
+                self.search_prefix = None;
+                self.kill_ring.last_yank = None;
+                let mut text = text.repeat(self.numeric_arg.take().unwrap_or(1));
+                // Input methods commit whole words at once; insert them with one redraw.
+                text.push_str(&self.read_pending_text()?);
+                return self.insert_text(&text);
+            }
+        }
+        Ok(ReplState::Continue)
+    }
+
+    /// Move the cursor to the `count`th occurrence of the character typed after Ctrl-], or
+    /// before the cursor after Alt-Ctrl-], like readline's `character-search`. Rings the bell
+    /// if there aren't that many, or the key isn't a character.
+    fn search_char(&mut self, sequence: &[u8], backward: bool, count: usize) -> Result<()> {
+        self.search_prefix = None;
+        self.kill_ring.last_yank = None;
+        let nth = count.max(1) - 1;
+        let target = std::str::from_utf8(sequence)
+            .ok()
+            .filter(|s| s.chars().count() == 1 && !s.starts_with(char::is_control));
+        let line = self.current_line_mut()?;
+        let cursor = line.cursor_pos;
+        let found = target.and_then(|target| {
+            if backward {
+                let mut matches = line.text[..cursor].rmatch_indices(target);
+                matches.nth(nth).map(|(i, _)| i)
+            } else {
+                let after = line.text[cursor..]
+                    .chars()
+                    .next()
+                    .map_or(cursor, |c| cursor + c.len_utf8());
+                let mut matches = line.text[after..].match_indices(target);
+                matches.nth(nth).map(|(i, _)| after + i)
+            }
+        });
+        match found {
+            Some(pos) => {
+                line.cursor_pos = pos;
+                self.redraw_current_line()
+            }
+            None => self.bell(),
+        }
+    }
+
+    /// Insert `text` at the cursor, or type it over the characters after the cursor in
+    /// overwrite mode. Rings the bell instead if the input would get too long.
+    fn insert_text(&mut self, text: &str) -> Result<ReplState> {
+        if self.overwrite {
+            return self.overwrite(text);
+        }
+        let max_length = self.max_length;
+        let line = self.current_line_mut()?;
+        if exceeds(max_length, &line.text, text) {
+            return self.bell().map(|_| ReplState::Continue);
+        }
+        line.insert_str(text);
+        self.redraw_current_line()?;
+        Ok(ReplState::Continue)
+    }
+
+    /// Type `text` over the characters after the cursor, in overwrite mode.
+    fn overwrite(&mut self, text: &str) -> Result<ReplState> {
+        let max_length = self.max_length;
+        let line = self.current_line_mut()?;
+        let mut edited = line.clone();
+        edited.overwrite_str(text);
+        if max_length.is_some_and(|max| edited.text.chars().count() > max) {
+            self.bell()?;
+            return Ok(ReplState::Continue);
+        }
+        *line = edited;
+        self.redraw_current_line()?;
+        Ok(ReplState::Continue)
+    }
+
+    /// Abort a history search or numeric argument and return to plain editing, like
+    /// readline's Ctrl-G. The input being typed before a history search is restored.
+    fn abort(&mut self) -> Result<()> {
+        self.numeric_arg = None;
+        self.kill_ring.last_yank = None;
+        self.correction = None;
+        self.selection = None;
+        if self.search_prefix.take().is_some() {
+            self.current_line = self.lines.len() - 1;
+        }
+        self.redraw_current_line()?;
+        self.bell()
+    }
+
+    /// Track progress through the clear-line keys, clearing the input once they've all been
+    /// pressed. Returns `true` if `sequence` was part of the chord.
+    ///
+    /// Keys leading up to the last one are swallowed; a key that breaks the chord is handled
+    /// normally.
+    fn clear_line_chord(&mut self, sequence: &[u8]) -> Result<bool> {
+        let esc = KeyEvent::new(KeyCode::Esc);
+        let keys = match sequence {
+            [0x1B] => vec![esc],
+            // Escape pressed twice quickly arrives as one sequence.
+            [0x1B, 0x1B] => vec![esc; 2],
+            _ => {
+                let mut bytes = sequence.iter().copied();
+                input::decode(|| bytes.next().ok_or(Error::Internal(InternalError::Eof)))
+                    .into_iter()
+                    .collect()
+            }
+        };
+        let start = self.clear_line_progress;
+        let expected = self.clear_line_keys.get(start..start + keys.len());
+        if keys.is_empty() || expected != Some(&keys[..]) {
+            self.clear_line_progress = 0;
+            return Ok(false);
+        }
+        self.clear_line_progress += keys.len();
+        if self.clear_line_progress < self.clear_line_keys.len() {
+            return Ok(true);
+        }
+
+        self.clear_line_progress = 0;
+        self.numeric_arg = None;
+        self.search_prefix = None;
+        self.current_line = self.lines.len() - 1;
+        self.current_line_mut()?.clear();
+        self.redraw_current_line()?;
+        Ok(true)
+    }
+
+    /// Dispatches a complete CSI sequence (`[`, parameters, final byte).
+    fn handle_csi_sequence(&mut self, sequence: &[u8]) -> Result<()> {
+        let final_byte = sequence[sequence.len() - 1];
+        let body = &sequence[1..sequence.len() - 1];
+        let (mouse, params) = match body.strip_prefix(b"<") {
+            Some(params) => (true, params),
+            None => (false, body),
+        };
+        // Sub-parameters after `:` are dropped.
+        let params: Vec<usize> = params
+            .split(|&b| b == b';')
+            .filter_map(|p| {
+                let p = p.split(|&b| b == b':').next()?;
+                std::str::from_utf8(p).ok()?.parse().ok()
             })
-        })?;
+            .collect();
+
+        match final_byte {
+            #[cfg(feature = "mouse")]
+            b'M' | b'm' if mouse => self.handle_mouse(&params, final_byte == b'M'),
+            _ if body.is_empty() => {
+                for _ in 0..self.numeric_arg.take().unwrap_or(1) {
+                    self.handle_escape_sequence(final_byte)?;
+                }
+                Ok(())
+            }
+            b'~' if !mouse && params == [2] => {
+                // Insert = toggle overwrite mode, shown with a block cursor
+                self.overwrite = !self.overwrite;
+                let shape = if self.overwrite {
+                    cursor::BLOCK
+                } else {
+                    cursor::DEFAULT_SHAPE
+                };
+                self.write(shape)
+            }
+            b'~' if !mouse && matches!(params[..], [5, ..] | [6, ..]) => {
+                // PageUp / PageDown = jump to the oldest history entry or back to the input
+                self.jump_history(params[0] == 5)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Recall the oldest history entry, or return to the input being typed, like readline's
+    /// `beginning-of-history` and `end-of-history`. Rings the bell if already there.
+    fn jump_history(&mut self, oldest: bool) -> Result<()> {
+        self.kill_ring.last_yank = None;
+        self.search_prefix = None;
+        self.sync_history();
+        let target = if oldest { 0 } else { self.lines.len() - 1 };
+        if self.current_line == target {
+            return self.bell();
+        }
+        self.current_line = target;
+        self.redraw_current_line()
+    }
+
+    /// Handles ANSI escape sequences (arrow keys).
+    fn handle_escape_sequence(&mut self, c: u8) -> Result<()> {
+        self.kill_ring.last_yank = None;
+        if !matches!(c, b'A' | b'B') {
+            self.search_prefix = None;
+        }
+
+        match c {
+            b'A' if self.current_line_mut()?.move_up() => {
+                // Up arrow within multi-line input
+                self.redraw_current_line()?;
+            }
+            b'B' if self.current_line_mut()?.move_down() => {
+                // Down arrow within multi-line input
+                self.redraw_current_line()?;
+            }
+            b'A' => {
+                // Up arrow: recall previous line in history, filtered by the typed prefix
+                self.sync_history();
+                let prefix = self.history_search_prefix();
+                if !prefix.is_empty() {
+                    if let Some(i) = history::search_prev(&self.lines, self.current_line, &prefix) {
+                        self.current_line = i;
+                        self.redraw_current_line()?;
+                    } else {
+                        self.bell()?;
+                    }
+                } else if self.current_line > 0 {
+                    self.current_line -= 1;
+                    self.redraw_current_line()?;
+                } else {
+                    self.bell()?;
+                }
+            }
+            b'B' => {
+                // Down arrow: recall next line in history
+                let prefix = self.history_search_prefix();
+                if !prefix.is_empty() {
+                    // Return to the line being typed once no newer entry matches.
+                    self.current_line =
+                        history::search_next(&self.lines, self.current_line, &prefix)
+                            .unwrap_or(self.lines.len() - 1);
+                    self.redraw_current_line()?;
+                } else if self.current_line + 1 < self.lines.len() {
+                    self.current_line += 1;
+                    self.redraw_current_line()?;
+                } else {
+                    // Already at the line being typed; every line before it mirrors a
+                    // history entry, so there's nowhere further to go.
+                    self.bell()?;
+                }
+            }
+            b'C' => {
+                // Right arrow, accepting the history suggestion at the end of the input
+                if self.suggestion.is_some() {
+                    self.accept_suggestion()?;
+                } else if let Some(line) = self.lines.get_mut(self.current_line) {
+                    line.move_right();
+                    self.redraw_current_line()?;
+                }
+            }
+            b'D' => {
+                // Left arrow
+                if let Some(line) = self.lines.get_mut(self.current_line) {
+                    line.move_left();
+                    self.redraw_current_line()?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Returns the text to suggest after the cursor: a correction offered by the last error
+    /// while the input is empty, or else the rest of the best history suggestion if
+    /// autosuggestions are on. Only the end of the input being typed gets a suggestion.
+    fn autosuggestion(&self) -> Option<String> {
+        let line = self.lines.get(self.current_line)?;
+        if self.current_line != self.lines.len() - 1 || line.cursor_pos != line.text.len() {
+            return None;
+        }
+        if line.text.is_empty() {
+            return self.correction.clone();
+        }
+        if !self.autosuggest {
+            return None;
+        }
+        let suggestions = self.history.lock().suggestions(&line.text);
+        let best = suggestions
+            .into_iter()
+            .find(|s| s.len() > line.text.len())?;
+        Some(best[line.text.len()..].to_string())
+    }
+
+    /// Append the shown history suggestion to the input.
+    fn accept_suggestion(&mut self) -> Result<()> {
+        let Some(suggestion) = self.suggestion.take() else {
+            return Ok(());
+        };
+        let max_length = self.max_length;
+        let line = self.current_line_mut()?;
+        if exceeds(max_length, &line.text, &suggestion) {
+            return self.bell();
+        }
+        line.insert_str(&suggestion);
+        self.redraw_current_line()
+    }
+
+    /// Signal a rejected edit according to the bell style.
+    fn bell(&mut self) -> Result<()> {
+        match self.bell_style {
+            BellStyle::None => Ok(()),
+            BellStyle::Audible => self.write(screen::BELL),
+            BellStyle::Visible if self.tmanager.is_some() => {
+                self.write(screen::REVERSE_VIDEO)?;
+                self.flush()?;
+                thread::sleep(Duration::from_millis(VISIBLE_BELL_MS));
+                self.write(screen::NORMAL_VIDEO)
+            }
+            BellStyle::Visible => Ok(()),
+        }
+    }
+
+    /// Print a result or meta-command output, through the pager if enabled.
+    fn print_output(&mut self, output: &str) -> Result<()> {
+        if self.pager && self.page(output)? {
+            return Ok(());
+        }
+        self.write(&format!("{}\n", output))
+    }
+
+    /// Print the duration of the last evaluation, dimmed and right-aligned on its own line.
+    fn print_timing(&mut self) -> Result<()> {
+        let Some(duration) = self.last_eval_duration else {
+            return Ok(());
+        };
+        let text = format_duration(duration);
+        let cols = self
+            .tmanager
+            .as_ref()
+            .and_then(|tmanager| tmanager.size().ok())
+            .map_or(0, |(_, cols)| cols as usize);
+        let styled = StyledText::new().span(&text, Style::new().attr(Attribute::Dim));
+        let padding = cols.saturating_sub(text_width(&text) + 1);
+        self.write(&format!(
+            "{}{}\n",
+            " ".repeat(padding),
+            styled.render(self.color_depth)
+        ))
+    }
+
+    /// Store killed text in the kill ring, and the system clipboard if enabled.
+    fn kill(&mut self, text: String) -> Result<()> {
+        if self.clipboard
+            && !text.is_empty()
+            && let Some(tmanager) = self.tmanager.as_mut()
+        {
+            tmanager.set_clipboard(&text).map_err(|e| {
+                Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                    "unable to copy to clipboard",
+                    e,
+                )))
+            })?;
+        }
+        self.kill_ring.kill(text);
+        Ok(())
+    }
+
+    /// Handles a key pressed with Alt (sent as Escape followed by the key).
+    fn handle_alt_input(&mut self, c: u8) -> Result<()> {
+        self.search_prefix = None;
+        let last_yank = self.kill_ring.last_yank.take();
+
+        if c == b'y' {
+            // Alt-Y = replace the text just yanked with an older kill
+            if let Some(range) = last_yank
+                && let Some(text) = self.kill_ring.yank_pop().map(str::to_string)
+            {
+                let max_length = self.max_length;
+                let line = self.current_line_mut()?;
+                let rest = format!("{}{}", &line.text[..range.start], &line.text[range.end..]);
+                if exceeds(max_length, &rest, &text) {
+                    self.kill_ring.last_yank = Some(range);
+                    return self.bell();
+                }
+                line.text.replace_range(range.clone(), &text);
+                line.cursor_pos = range.start + text.len();
+                self.kill_ring.last_yank = Some(range.start..line.cursor_pos);
+                self.redraw_current_line()?;
+            } else {
+                self.bell()?;
+            }
+            return Ok(());
+        }
+
+        if c == b'r' {
+            // Alt-R = browse history in a filterable list
+            return self.browse_history();
+        }
+
+        if c == b'.' {
+            // Alt-. = insert the last word of the previous entry, or older ones when repeated
+            return self.insert_last_argument();
+        }
+
+        let words = self.word_chars.clone().unwrap_or_default();
+        let line = self.current_line_mut()?;
+        match c {
+            // Alt-B / Alt-F = move to the start of the previous word or the end of the next
+            b'b' => line.cursor_pos = line.word_start_with(&words),
+            b'f' => line.cursor_pos = line.word_end_with(&words),
+            // Alt-T = transpose words
+            b't' => line.transpose_words_with(&words),
+            // Alt-U / Alt-L / Alt-C = uppercase, lowercase or capitalize word
+            b'u' => line.change_word_case(&words, |word| word.to_uppercase()),
+            b'l' => line.change_word_case(&words, |word| word.to_lowercase()),
+            b'c' => line.capitalize_word_with(&words),
+            _ => return Ok(()),
+        }
+        self.redraw_current_line()?;
+
+        Ok(())
+    }
+
+    /// Insert the last word of the previous history entry at the cursor, like readline's
+    /// `yank-last-arg`. Pressed again, it replaces that word with the last word of the entry
+    /// before, skipping entries without words. Rings the bell once history runs out.
+    fn insert_last_argument(&mut self) -> Result<()> {
+        let previous = self.last_argument.take();
+        if previous.is_none() {
+            self.sync_history();
+        }
+        let (range, skip) = match &previous {
+            Some((range, back)) => (Some(range.clone()), *back),
+            None => (None, 0),
+        };
+        let input = self.lines.len() - 1;
+        let found = self.lines[..input]
+            .iter()
+            .rev()
+            .enumerate()
+            .skip(skip)
+            .find_map(|(i, line)| Some((i + 1, line.text.split_whitespace().last()?.to_string())));
+        let Some((back, word)) = found else {
+            self.last_argument = previous;
+            return self.bell();
+        };
+
+        let max_length = self.max_length;
+        let line = self.current_line_mut()?;
+        let range = range.unwrap_or(line.cursor_pos..line.cursor_pos);
+        let rest = format!("{}{}", &line.text[..range.start], &line.text[range.end..]);
+        if exceeds(max_length, &rest, &word) {
+            self.last_argument = previous;
+            return self.bell();
+        }
+        line.text.replace_range(range.clone(), &word);
+        line.cursor_pos = range.start + word.len();
+        self.last_argument = Some((range.start..line.cursor_pos, back));
+        self.redraw_current_line()
+    }
+
+    /// Returns the prefix history navigation is filtered by, capturing it on first use.
+    fn history_search_prefix(&mut self) -> String {
+        let current_text = self
+            .lines
+            .get(self.current_line)
+            .map(|l| l.text.clone())
+            .unwrap_or_default();
+        self.search_prefix.get_or_insert(current_text).clone()
+    }
+
+    /// Handles normal character input and control characters.
+    fn handle_normal_input(&mut self, c: u8) -> Result<ReplState> {
+        self.search_prefix = None;
+        self.kill_ring.last_yank = None;
+        if matches!(c, b'\n' | b'\r') && self.expand_abbreviation() {
+            self.redraw_current_line()?;
+        } else if c == b' ' {
+            // Redrawn along with the space.
+            self.expand_abbreviation();
+        }
+
+        let last = self.lines.len() - 1;
+        let current_line = self
+            .lines
+            .get_mut(self.current_line)
+            .ok_or_else(|| Error::User(UserError::new("no active line")))?;
 
         match c {
-            b'\n' | b'\r' => {
-                // Newline/enter line
-                if (self.is_line_complete)(current_line.text.clone()) {
-                    println!();
+            b'\n' | b'\r' | 0x0F => {
+                // Newline/enter line. Ctrl-O also queues the history line after a recalled one.
+                let validation = (self.validator)(&current_line.text);
+                if let Validation::Invalid { message } = validation {
+                    self.validation_message = Some(message);
+                    self.redraw_current_line()?;
+                    self.bell()?;
+                    Ok(ReplState::Continue)
+                } else if validation == Validation::Complete {
+                    if c == 0x0F && self.current_line + 1 < last {
+                        self.next_history_line = Some(self.current_line + 1);
+                    }
+                    // Leave the cursor below the whole block, wherever it was edited.
+                    let below = self.rows_below_cursor();
+                    if self.suggestion.take().is_some() {
+                        self.write(clear::TO_END_OF_LINE)?;
+                    }
+                    self.write(&format!("{}\n", cursor::down(below)))?;
+                    self.clear_signature()?;
+                    self.rendered_row = 0;
                     Ok(ReplState::Break)
                 } else {
+                    if exceeds(self.max_length, &current_line.text, "\n") {
+                        self.bell()?;
+                        return Ok(ReplState::Continue);
+                    }
                     current_line.insert_char('\n');
+                    if let Some(indent) = self.indent.as_mut() {
+                        for c in indent(&current_line.text[..current_line.cursor_pos]).chars() {
+                            if exceeds(
+                                self.max_length,
+                                &current_line.text,
+                                c.encode_utf8(&mut [0; 4]),
+                            ) {
+                                break;
+                            }
+                            current_line.insert_char(c);
+                        }
+                    }
+                    self.redraw_current_line()?;
                     Ok(ReplState::Continue)
                 }
             }
+            0x03 => {
+                // Ctrl-C = discard the input and start over below it
+                let below = self.rows_below_cursor();
+                if self.suggestion.take().is_some() {
+                    self.write(clear::TO_END_OF_LINE)?;
+                }
+                self.write(&format!("{}^C\r\n", cursor::down(below)))?;
+                self.clear_signature()?;
+                self.rendered_row = 0;
+                self.lines[last] = Line::new();
+                self.current_line = last;
+                self.redraw_current_line()?;
+                Ok(ReplState::Continue)
+            }
+            0x1A => {
+                // Ctrl-Z = suspend the process, redrawing the input once continued
+                if self.tmanager.is_some() {
+                    let below = self.rows_below_cursor();
+                    self.write(&format!("{}\r\n", cursor::down(below)))?;
+                    if self.status.is_some() {
+                        self.write(screen::RESET_SCROLL_REGION)?;
+                    }
+                    self.flush()?;
+                    self.tmanager_mut()?.suspend_process().map_err(|e| {
+                        Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                            "unable to suspend",
+                            e,
+                        )))
+                    })?;
+                    self.rendered_row = 0;
+                    self.redraw_current_line()?;
+                }
+                Ok(ReplState::Continue)
+            }
+            0x0B => {
+                // Ctrl-K = kill to end of line
+                let killed = current_line.kill_to_end();
+                self.redraw_current_line()?;
+                self.kill(killed)?;
+                Ok(ReplState::Continue)
+            }
+            0x15 => {
+                // Ctrl-U = kill to start of line
+                let killed = current_line.kill_to_start();
+                self.redraw_current_line()?;
+                self.kill(killed)?;
+                Ok(ReplState::Continue)
+            }
+            0x17 => {
+                // Ctrl-W = kill previous word
+                let killed = match &self.word_chars {
+                    Some(words) => current_line.kill_word_back_with(words),
+                    None => current_line.kill_word_back(),
+                };
+                self.redraw_current_line()?;
+                self.kill(killed)?;
+                Ok(ReplState::Continue)
+            }
+            0x19 => {
+                // Ctrl-Y = yank the most recent kill
+                if let Some(text) = self.kill_ring.yank() {
+                    if exceeds(self.max_length, &current_line.text, text) {
+                        self.bell()?;
+                        return Ok(ReplState::Continue);
+                    }
+                    let start = current_line.cursor_pos;
+                    current_line.insert_str(text);
+                    self.kill_ring.last_yank = Some(start..current_line.cursor_pos);
+                    self.redraw_current_line()?;
+                } else {
+                    self.bell()?;
+                }
+                Ok(ReplState::Continue)
+            }
+            0x0C => {
+                // Ctrl-L = clear screen and redraw the input at the top
+                self.write(clear::SCREEN)?;
+                self.rendered_row = 0;
+                self.redraw_current_line()?;
+                Ok(ReplState::Continue)
+            }
+            0x14 => {
+                // Ctrl-T = transpose characters
+                current_line.transpose_chars();
+                self.redraw_current_line()?;
+                Ok(ReplState::Continue)
+            }
             0x7F => {
                 // Backspace
-                current_line.backspace();
+                if current_line.cursor_pos == 0 {
+                    self.bell()?;
+                    return Ok(ReplState::Continue);
+                }
+                if !current_line.backspace_pair(&self.auto_pairs) {
+                    current_line.backspace();
+                }
                 self.redraw_current_line()?;
                 Ok(ReplState::Continue)
             }
@@ -331,19 +3352,83 @@ impl Repl {
                 self.redraw_current_line()?;
                 Ok(ReplState::Continue)
             }
+            0x04 if current_line.text.is_empty() => {
+                // Ctrl-D on empty input = end of input, as in readline
+                self.leave_input()?;
+                Err(Error::Internal(InternalError::Eof))
+            }
+            0x04 => {
+                // Ctrl-D = delete character under cursor
+                if current_line.cursor_pos == current_line.text.len() {
+                    self.bell()?;
+                    return Ok(ReplState::Continue);
+                }
+                current_line.delete();
+                self.redraw_current_line()?;
+                Ok(ReplState::Continue)
+            }
+            0x05 if self.suggestion.is_some() => {
+                // Ctrl-E at the end of the input = accept the history suggestion
+                self.accept_suggestion()?;
+                Ok(ReplState::Continue)
+            }
             0x05 => {
                 // Ctrl-E = move to line end
                 current_line.cursor_pos = current_line.text.len();
                 self.redraw_current_line()?;
                 Ok(ReplState::Continue)
             }
-            0x1B => {
-                // Escape
-                self.input_state = InputType::Escape;
+            b'\t' if self.completer.is_some() => {
+                // Tab = complete the word before the cursor
+                if !self.complete()? {
+                    self.bell()?;
+                }
+                Ok(ReplState::Continue)
+            }
+            b'\t' => {
+                // Tab
+                let text = match self.tab_mode {
+                    TabMode::Ignore => {
+                        self.bell()?;
+                        return Ok(ReplState::Continue);
+                    }
+                    TabMode::Literal => "\t".to_string(),
+                    TabMode::Spaces(n) => " ".repeat(n),
+                };
+                if exceeds(self.max_length, &current_line.text, &text) {
+                    self.bell()?;
+                    return Ok(ReplState::Continue);
+                }
+                current_line.insert_str(&text);
+                self.redraw_current_line()?;
+                Ok(ReplState::Continue)
+            }
+            0x18 => {
+                // Ctrl-X = start a chord
+                self.input_state = InputType::CtrlX;
+                Ok(ReplState::Continue)
+            }
+            0x16 => {
+                // Ctrl-V = insert the next key literally
+                self.input_state = InputType::Quoted;
+                Ok(ReplState::Continue)
+            }
+            0x1D => {
+                // Ctrl-] = move forward to the next character typed
+                self.input_state = InputType::CharSearch { backward: false };
                 Ok(ReplState::Continue)
             }
             c if c.is_ascii_control() => Ok(ReplState::Continue),
+            c if self.overwrite => self.overwrite(&(c as char).to_string()),
             c => {
+                if current_line.insert_paired(c as char, &self.auto_pairs, self.max_length) {
+                    self.redraw_current_line()?;
+                    return Ok(ReplState::Continue);
+                }
+                if exceeds(self.max_length, &current_line.text, " ") {
+                    self.bell()?;
+                    return Ok(ReplState::Continue);
+                }
                 current_line.insert_char(c as char);
                 self.redraw_current_line()?;
                 Ok(ReplState::Continue)
@@ -351,22 +3436,417 @@ impl Repl {
         }
     }
 
+    /// Handles a key, repeated according to any pending numeric argument.
+    fn handle_repeated_input(&mut self, c: u8) -> Result<ReplState> {
+        for _ in 1..self.numeric_arg.take().unwrap_or(1) {
+            if let ReplState::Break = self.handle_normal_input(c)? {
+                return Ok(ReplState::Break);
+            }
+        }
+        self.handle_normal_input(c)
+    }
+
+    /// Returns the line being edited.
+    fn current_line_mut(&mut self) -> Result<&mut Line> {
+        self.lines
+            .get_mut(self.current_line)
+            .ok_or_else(|| Error::User(UserError::new("no active line")))
+    }
+
     /// Redraws the current line with proper cursor positioning.
+    ///
+    /// Multi-line input is drawn as a block, with the continuation prompt before each line
+    /// after the first. The output is queued and written before the next key is read.
     fn redraw_current_line(&mut self) -> Result<()> {
+        self.refresh_prompt();
+        self.suggestion = self.autosuggestion();
+        let signature = self.update_signature();
+        let physical_lines = self.highlighted_lines();
         let line = self.lines.get(self.current_line).ok_or_else(|| {
             Error::Internal(InternalError::IoWrite("no active line for redraw".into()))
         })?;
 
-        print!("\r{}{}\x1b[K", self.prompt, line.text);
-        let right_after_prompt = self.prompt.len() + line.cursor_pos;
-        let total_len = self.prompt.len() + line.text.len();
-        if total_len > right_after_prompt {
-            print!("\x1b[{}D", total_len - right_after_prompt);
+        let mut out = format!(
+            "{}\r{}",
+            cursor::up(self.rendered_row),
+            clear::TO_END_OF_SCREEN
+        );
+        let reordered: Vec<Option<(StyledText, Vec<usize>)>> =
+            physical_lines.iter().map(bidi::reorder).collect();
+        if reordered.iter().any(Option::is_some) && !self.bidi_explicit {
+            // Keep terminals with BiDi support from reordering the line a second time.
+            out.insert_str(0, screen::BIDI_EXPLICIT);
+            self.bidi_explicit = true;
+        }
+        let rows = physical_lines.len();
+        let wrap_width = self.wrap_width();
+        // The column each screen row of each physical line starts at, for placing the cursor.
+        let mut row_starts: Vec<Vec<usize>> = Vec::with_capacity(rows);
+        for (i, physical) in physical_lines.iter().enumerate() {
+            if i > 0 {
+                out.push_str("\r\n");
+            }
+            let margin = self.margin(i, rows);
+            out.push_str(&self.render(&margin));
+            let mut col = margin.width();
+            let shown = reordered[i].as_ref().map_or(physical, |(visual, _)| visual);
+            let mut pieces = Vec::new();
+            for span in shown.spans() {
+                pieces.push(Span::new(display_text(&span.text, col), span.style));
+                col = display_column(&span.text, col);
+            }
+            if i == rows - 1
+                && let Some(suggestion) = self.suggestion.as_deref()
+            {
+                // Multi-line entries are suggested up to their first line break.
+                let first = suggestion.split('\n').next().unwrap_or_default();
+                pieces.push(Span::new(first, Style::new().attr(Attribute::Dim)));
+            }
+            match wrap_width {
+                Some(width) => {
+                    let (rendered, starts) = self.wrap_line(&pieces, margin.width(), width);
+                    out.push_str(&rendered);
+                    row_starts.push(starts);
+                }
+                None => {
+                    for piece in pieces {
+                        out.push_str(&StyledText::from(piece).render(self.color_depth));
+                    }
+                    row_starts.push(vec![0]);
+                }
+            }
         }
+        let input_rows: usize = row_starts.iter().map(Vec::len).sum();
 
-        self.tmanager.flush().map_err(|_| {
-            Error::Internal(InternalError::IoFlush("unable to flush stdout".into()))
-        })?;
+        let mut last_row = input_rows - 1;
+        if let Some(hint) = signature {
+            let cols = self.tmanager.as_ref().and_then(|t| t.size().ok());
+            let width = cols.map_or(80, |(_, cols)| cols as usize).saturating_sub(1);
+            out.push_str("\r\n");
+            out.push_str(&self.render(&hint.truncate(width)));
+            last_row += 1;
+        }
+        if let Some(message) = self.validation_message.as_deref() {
+            let style = Style::new().fg(Color::Red);
+            out.push_str("\r\n");
+            out.push_str(
+                &StyledText::new()
+                    .span(message, style)
+                    .render(self.color_depth),
+            );
+            last_row += 1;
+        }
+        if let Some(doc) = self.completion_doc.as_deref() {
+            let cols = self.tmanager.as_ref().and_then(|t| t.size().ok());
+            let width = cols.map_or(80, |(_, cols)| cols as usize).saturating_sub(1);
+            for doc_line in doc.lines().take(DOC_ROWS) {
+                let shown = StyledText::new()
+                    .span(doc_line, Style::new().attr(Attribute::Dim))
+                    .truncate(width);
+                out.push_str("\r\n");
+                out.push_str(&self.render(&shown));
+                last_row += 1;
+            }
+        }
+        let (row, _) = line.cursor_row_col();
+        let margin_width = self.margin(row, rows).width();
+        let before_cursor = line.text[..line.cursor_pos]
+            .rsplit('\n')
+            .next()
+            .unwrap_or("");
+        let column = match reordered.get(row).and_then(Option::as_ref) {
+            Some((visual, positions)) => {
+                // The cursor covers the character after it, wherever that's displayed.
+                let position = positions
+                    .get(before_cursor.chars().count())
+                    .copied()
+                    .unwrap_or(positions.len());
+                let shown: String = visual.text().chars().take(position).collect();
+                display_column(&shown, margin_width)
+            }
+            None => display_column(before_cursor, margin_width),
+        };
+        let starts = &row_starts[row];
+        let segment = starts.partition_point(|&start| start <= column) - 1;
+        let screen_row = row_starts[..row].iter().map(Vec::len).sum::<usize>() + segment;
+        out.push_str(&cursor::up(last_row - screen_row));
+        out.push('\r');
+        out.push_str(&cursor::right(column - starts[segment]));
+        // Clearing to the end of the screen may have erased a bottom status line.
+        out.push_str(&self.status_sequence());
+        self.write(&out)?;
+        self.rendered_row = screen_row;
+        self.rendered_rows = input_rows;
         Ok(())
     }
+
+    /// Returns the number of screen rows of input below the cursor.
+    fn rows_below_cursor(&self) -> usize {
+        self.rendered_rows.saturating_sub(self.rendered_row + 1)
+    }
+
+    /// Returns the columns input may fill before wrapping with the wrap indicator, or `None`
+    /// if the terminal wraps it.
+    fn wrap_width(&self) -> Option<usize> {
+        let indicator = self.wrap_indicator.as_ref()?;
+        let (_, cols) = self.tmanager.as_ref()?.size().ok()?;
+        let width = (cols as usize).checked_sub(indicator.width())?;
+        // Leave room for at least one character of the widest kind.
+        (width >= 2).then_some(width)
+    }
+
+    /// Render `pieces` starting at screen column `start`, moving to a new row with the wrap
+    /// indicator before any character that would pass `width`. Returns the output and the
+    /// column of the unwrapped line each screen row starts at.
+    fn wrap_line(&self, pieces: &[Span], start: usize, width: usize) -> (String, Vec<usize>) {
+        let mut out = String::new();
+        let mut starts = vec![0];
+        let (mut col, mut unwrapped_col) = (start, start);
+        for piece in pieces {
+            let mut text = String::new();
+            for c in piece.text.chars() {
+                let w = char_width(c);
+                if col + w > width && col > 0 {
+                    out.push_str(
+                        &StyledText::from(Span::new(text, piece.style)).render(self.color_depth),
+                    );
+                    text = String::new();
+                    if let Some(indicator) = self.wrap_indicator.as_ref() {
+                        out.push_str(&indicator.render(self.color_depth));
+                    }
+                    out.push_str("\r\n");
+                    col = 0;
+                    starts.push(unwrapped_col);
+                }
+                text.push(c);
+                col += w;
+                unwrapped_col += w;
+            }
+            out.push_str(&StyledText::from(Span::new(text, piece.style)).render(self.color_depth));
+        }
+        (out, starts)
+    }
+
+    /// Returns what's drawn before physical line `row` of `rows`: the prompt or continuation
+    /// prompt, then the line number if enabled.
+    fn margin(&self, row: usize, rows: usize) -> StyledText {
+        let mut margin = if row == 0 {
+            self.prompt.clone()
+        } else {
+            self.continuation_prompt.clone()
+        };
+        if self.line_numbers && rows > 1 {
+            let width = rows.to_string().len();
+            let number = format!("{:>width$} ", row + 1);
+            margin.push(number, Style::new().attr(Attribute::Dim));
+        }
+        margin
+    }
+}
+
+/// Returns `text` as displayed from the given screen column: tabs become spaces up to the next
+/// tab stop, and other control characters are shown in caret notation, e.g. `^C`, rather than
+/// sent to the terminal.
+fn display_text(text: &str, start_col: usize) -> String {
+    let mut col = start_col;
+    let mut shown = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\t' {
+            let width = TAB_WIDTH - col % TAB_WIDTH;
+            shown.extend(std::iter::repeat_n(' ', width));
+            col += width;
+        } else if let Some(caret) = caret_notation(c) {
+            col += caret.len();
+            shown.push_str(&caret);
+        } else {
+            shown.push(c);
+            col += char_width(c);
+        }
+    }
+    shown
+}
+
+/// Returns the screen column reached after displaying `text` from `start_col`.
+fn display_column(text: &str, start_col: usize) -> usize {
+    text.chars().fold(start_col, |col, c| {
+        if c == '\t' {
+            col + TAB_WIDTH - col % TAB_WIDTH
+        } else if let Some(caret) = caret_notation(c) {
+            col + caret.len()
+        } else {
+            col + char_width(c)
+        }
+    })
+}
+
+/// Returns the caret notation of a control character, `^@` through `^_` and `^?`, with C1
+/// controls prefixed by `M-` as `cat -v` shows them. Returns `None` for other characters.
+fn caret_notation(c: char) -> Option<String> {
+    match c as u32 {
+        n @ (0x00..=0x1F | 0x7F) => Some(format!("^{}", char::from(n as u8 ^ 0x40))),
+        n @ 0x80..=0x9F => Some(format!("M-^{}", char::from((n - 0x80) as u8 ^ 0x40))),
+        _ => None,
+    }
+}
+
+/// Returns the "did you mean" hint listing `suggestions`.
+fn did_you_mean(suggestions: &[String]) -> String {
+    let quoted: Vec<String> = suggestions.iter().map(|s| format!("`{}`", s)).collect();
+    match quoted.as_slice() {
+        [only] => format!("did you mean {}?", only),
+        [rest @ .., last] => format!("did you mean {} or {}?", rest.join(", "), last),
+        [] => String::new(),
+    }
+}
+
+/// Returns the `In [n]: ` prompt of numbered mode.
+fn numbered_prompt(count: usize) -> String {
+    format!("In [{}]: ", count)
+}
+
+/// Format a duration with a unit suited to its size, e.g. `850 µs`, `12.3 ms` or `1.25 s`.
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros < 1000 {
+        format!("{} µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.1} ms", micros as f64 / 1000.0)
+    } else {
+        format!("{:.2} s", duration.as_secs_f64())
+    }
+}
+
+/// Adapt a `ProcessLineFunc` to the `EvalFunc` a `Repl` calls.
+fn wrap_process_line<T: 'static>(mut process_line: ProcessLineFunc<T>) -> EvalFunc<T> {
+    Box::new(move |line, _| process_line(line))
+}
+
+/// Returns `true` if inserting `inserted` into `text` would exceed `max_length` characters.
+fn exceeds(max_length: Option<usize>, text: &str, inserted: &str) -> bool {
+    max_length.is_some_and(|max| text.chars().count() + inserted.chars().count() > max)
+}
+
+/// Returns spaces as wide as `prompt`, the default continuation prompt.
+fn padding(prompt: &StyledText) -> StyledText {
+    StyledText::from(" ".repeat(prompt.width()))
+}
+
+/// Returns the leading whitespace of the line before the last newline in `input`.
+fn previous_indentation(input: &str) -> String {
+    input
+        .strip_suffix('\n')
+        .unwrap_or(input)
+        .rsplit('\n')
+        .next()
+        .unwrap_or("")
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect()
+}
+
+impl<T: Display> Repl<T> {
+    /// Run the welcome/prompt/read/eval/print loop until input ends or the exit condition is met.
+    ///
+    /// User errors from `process_line` are printed and the loop continues. Internal errors
+    /// stop the loop and are returned.
+    pub fn run(&mut self) -> Result<()> {
+        self.print_welcome();
+
+        loop {
+            self.run_pre_input_hooks()?;
+            self.print_prompt();
+            let line = match self.read_input() {
+                Ok(line) => line,
+                Err(Error::Internal(InternalError::Eof | InternalError::Cancelled)) => {
+                    return Ok(());
+                }
+                // Input rejected by the validator in batch mode.
+                Err(Error::User(e)) => {
+                    let message = format!("error: {}", e.error);
+                    self.record_output(&message);
+                    eprintln!("{}", message);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            if self.should_exit(&line) {
+                return Ok(());
+            }
+
+            match self.eval(line) {
+                Ok(Evaluation::Value(value)) => {
+                    let output = if self.numbered {
+                        format!("Out[{}]: {}", self.execution_count - 1, value)
+                    } else {
+                        value.to_string()
+                    };
+                    self.record_output(&output);
+                    self.print_output(&output)?;
+                    if self.show_timing {
+                        self.print_timing()?;
+                    }
+                    self.run_post_output_hooks()?;
+                }
+                Ok(Evaluation::Command) => {}
+                Ok(Evaluation::Quit) => return Ok(()),
+                Err(Error::User(e)) => {
+                    let mut message = format!("error: {}", e.error);
+                    if !e.suggestions.is_empty() {
+                        message.push('\n');
+                        message.push_str(&did_you_mean(&e.suggestions));
+                    }
+                    self.record_output(&message);
+                    eprintln!("{}", message);
+                    self.run_post_output_hooks()?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<T> Drop for Repl<T> {
+    fn drop(&mut self) {
+        let _ = self.clear_status();
+        let _ = self.history.lock().save();
+        self.save_mode_histories();
+    }
+}
+
+/// Iterator over completed input lines, created by `Repl::lines`.
+pub struct Lines<'a, T = String> {
+    repl: &'a mut Repl<T>,
+    done: bool,
+}
+
+impl<T> Iterator for Lines<'_, T> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if let Err(e) = self.repl.run_pre_input_hooks() {
+            self.done = true;
+            return Some(Err(e));
+        }
+        self.repl.print_prompt();
+        match self.repl.read_input() {
+            Ok(line) if self.repl.should_exit(&line) => {
+                self.done = true;
+                None
+            }
+            Ok(line) => Some(Ok(line)),
+            Err(Error::Internal(InternalError::Eof | InternalError::Cancelled)) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
 }
@@ -0,0 +1,93 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::mem;
+
+use crate::Repl;
+
+/// Which carriage returns (`\r`) and line feeds (`\n`) read from the terminal end a line.
+///
+/// Terminals send `\r` for Enter, but PTY bridges and serial links may send `\r\n` or a
+/// bare `\n`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// `\r`, `\n` and `\r\n` each end one line.
+    #[default]
+    Auto,
+    /// Every `\r` and `\n` ends a line, with `\r` translated by the terminal as it's set up.
+    /// A `\r\n` pair ends two lines.
+    Each,
+    /// Only `\r` ends a line, and `\n` is ignored.
+    Cr,
+    /// Only `\n` ends a line, and `\r` is ignored. Enter doesn't end a line on a terminal,
+    /// so this is only for input from a bridge that sends `\n`.
+    Lf,
+}
+
+impl<T> Repl<T> {
+    /// Returns `true` if the key or byte read as `sequence` should be skipped: a line feed
+    /// completing a `\r\n` pair, or a line ending `line_ending` ignores.
+    ///
+    /// Called once for each key or byte read from the terminal or replay script, so it sees
+    /// every `\r` and `\n` in order.
+    pub(crate) fn skip_line_ending(&mut self, sequence: &[u8]) -> bool {
+        let after_cr = mem::replace(&mut self.after_cr, sequence == b"\r");
+        match (self.line_ending, sequence) {
+            (LineEnding::Auto, b"\n") => after_cr,
+            (LineEnding::Cr, b"\n") | (LineEnding::Lf, b"\r") => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, InternalError, ReplBuilder};
+
+    fn repl(line_ending: LineEnding) -> Repl<String> {
+        ReplBuilder::new()
+            .line_ending(line_ending)
+            .build_detached()
+            .unwrap()
+    }
+
+    /// Feed `input` and return every line it submits.
+    fn lines(line_ending: LineEnding, input: &[u8]) -> Vec<String> {
+        let mut repl = repl(line_ending);
+        repl.feed_bytes(input);
+        let mut lines = Vec::new();
+        loop {
+            match repl.process_input() {
+                Ok(line) => lines.push(line),
+                Err(Error::Internal(InternalError::Eof)) => return lines,
+                Err(e) => panic!("unexpected error: {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn auto_ends_lines_with_cr_lf_or_crlf() {
+        assert_eq!(lines(LineEnding::Auto, b"a\r"), ["a"]);
+        assert_eq!(lines(LineEnding::Auto, b"a\n"), ["a"]);
+        assert_eq!(lines(LineEnding::Auto, b"a\r\nb\r\n"), ["a", "b"]);
+        assert_eq!(lines(LineEnding::Auto, b"a\n\nb\r\r"), ["a", "", "b", ""]);
+    }
+
+    #[test]
+    fn auto_skips_lf_of_crlf_split_across_reads() {
+        let mut repl = repl(LineEnding::Auto);
+        repl.feed_bytes(b"a\r");
+        assert_eq!(repl.process_input().unwrap(), "a");
+        repl.feed_bytes(b"\nb\r");
+        assert_eq!(repl.process_input().unwrap(), "b");
+    }
+
+    #[test]
+    fn other_modes() {
+        assert_eq!(lines(LineEnding::Each, b"a\r\nb\n"), ["a", "", "b"]);
+        assert_eq!(lines(LineEnding::Cr, b"a\nb\r"), ["ab"]);
+        assert_eq!(lines(LineEnding::Lf, b"a\rb\n"), ["ab"]);
+    }
+}
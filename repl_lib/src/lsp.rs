@@ -0,0 +1,421 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! Completion and hover hints from a language server, spoken to over the Language Server
+//! Protocol on its stdin and stdout.
+//!
+//! The input is sent to the server as the whole text of a single untitled document, so
+//! servers that need a project on disk may answer with less than they would in an editor.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        Arc, Mutex, MutexGuard,
+        mpsc::{self, Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    CompleterFunc, Completions, Error, ErrorContext, InternalError, Result, SignatureFunc,
+    StyledText,
+};
+
+/// URI of the document holding the input.
+const DOCUMENT_URI: &str = "untitled:repl";
+/// How long to wait for the server to start.
+const INIT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long to wait for completions by default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+/// How long to wait for a hover hint, which is requested each time the input is drawn.
+const HOVER_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Handle to a running language server.
+///
+/// Pass `completer` to `ReplBuilder::completer` and `hover_hint` to
+/// `ReplBuilder::signature_help`. Clones share the server, and it's asked to shut down when
+/// the last handle is dropped.
+#[derive(Clone)]
+pub struct LspClient {
+    inner: Arc<Mutex<Connection>>,
+}
+
+struct Connection {
+    child: Child,
+    stdin: ChildStdin,
+    messages: Receiver<Value>,
+    next_id: u64,
+    /// Version of the document, increased with each change.
+    version: u64,
+    /// Text of the document as last sent.
+    text: String,
+    timeout: Duration,
+    /// The input and cursor position of the last hover, and its result.
+    last_hover: Option<(String, usize, Option<String>)>,
+}
+
+impl LspClient {
+    /// Start the server with `command` and open a document in the language with the LSP
+    /// identifier `language_id`, e.g. `python` or `sql`.
+    ///
+    /// Returns an error if the server can't be started or doesn't initialize within ten
+    /// seconds.
+    pub fn spawn(mut command: Command, language_id: &str) -> Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "unable to start language server",
+                    e,
+                )))
+            })?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(Error::Internal(InternalError::InitFail(
+                "language server has no stdio".into(),
+            )));
+        };
+        let (sender, messages) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Some(message) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let mut connection = Connection {
+            child,
+            stdin,
+            messages,
+            next_id: 0,
+            version: 0,
+            text: String::new(),
+            timeout: DEFAULT_TIMEOUT,
+            last_hover: None,
+        };
+        let params = json!({
+            "processId": std::process::id(),
+            "rootUri": null,
+            "capabilities": {
+                "textDocument": {
+                    "completion": {
+                        "completionItem": {
+                            "snippetSupport": false,
+                            "documentationFormat": ["plaintext"],
+                        },
+                    },
+                    "hover": { "contentFormat": ["plaintext"] },
+                },
+            },
+        });
+        connection
+            .request("initialize", params, INIT_TIMEOUT)
+            .map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    "language server failed to initialize",
+                    e,
+                )))
+            })?;
+        connection.notify("initialized", json!({}))?;
+        let document = json!({
+            "textDocument": {
+                "uri": DOCUMENT_URI,
+                "languageId": language_id,
+                "version": 0,
+                "text": "",
+            },
+        });
+        connection.notify("textDocument/didOpen", document)?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Set how long to wait for completions before giving up (default 500ms).
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.lock().timeout = timeout;
+        self
+    }
+
+    /// Returns the server's completions for `text` at the byte offset `cursor`, with each
+    /// item's detail and documentation as its doc.
+    pub fn completions(&self, text: &str, cursor: usize) -> Result<Completions> {
+        let mut connection = self.lock();
+        connection.sync(text)?;
+        let timeout = connection.timeout;
+        let result = connection.request(
+            "textDocument/completion",
+            position_params(text, cursor),
+            timeout,
+        )?;
+        let items = match &result {
+            Value::Array(items) => items,
+            Value::Object(list) => match list.get("items") {
+                Some(Value::Array(items)) => items,
+                _ => return Ok(Completions::default()),
+            },
+            _ => return Ok(Completions::default()),
+        };
+
+        let mut start = word_start(text, cursor);
+        let mut completions = Completions::default();
+        for item in items {
+            let edit = item.get("textEdit");
+            let range_start = edit
+                .and_then(|edit| edit.get("range").or_else(|| edit.get("replace")))
+                .and_then(|range| offset_of(text, range.get("start")?));
+            // Candidates all replace the same text, so the first edit decides where it
+            // starts.
+            if completions.candidates.is_empty()
+                && let Some(range_start) = range_start.filter(|&s| s <= cursor)
+            {
+                start = range_start;
+            }
+            if range_start.is_some_and(|s| s != start) {
+                continue;
+            }
+            let candidate = edit
+                .and_then(|edit| edit.get("newText"))
+                .or_else(|| item.get("insertText"))
+                .or_else(|| item.get("label"))
+                .and_then(Value::as_str);
+            let Some(candidate) = candidate else {
+                continue;
+            };
+            let doc = [item.get("detail"), item.get("documentation")]
+                .into_iter()
+                .filter_map(|part| markup_text(part?))
+                .collect::<Vec<_>>()
+                .join("\n");
+            completions.candidates.push(candidate.to_string());
+            completions.docs.push((!doc.is_empty()).then_some(doc));
+        }
+        completions.start = start;
+        Ok(completions)
+    }
+
+    /// Returns the server's hover text for `text` at the byte offset `cursor`, e.g. the
+    /// signature of the function under the cursor.
+    pub fn hover(&self, text: &str, cursor: usize) -> Result<Option<String>> {
+        let mut connection = self.lock();
+        if let Some((last_text, last_cursor, hover)) = &connection.last_hover
+            && last_text == text
+            && *last_cursor == cursor
+        {
+            return Ok(hover.clone());
+        }
+        connection.sync(text)?;
+        let result = connection.request(
+            "textDocument/hover",
+            position_params(text, cursor),
+            HOVER_TIMEOUT,
+        )?;
+        let contents = result.get("contents");
+        let hover = match contents {
+            Some(Value::Array(parts)) => {
+                let parts: Vec<String> = parts.iter().filter_map(markup_text).collect();
+                (!parts.is_empty()).then(|| parts.join("\n"))
+            }
+            Some(contents) => markup_text(contents),
+            None => None,
+        };
+        connection.last_hover = Some((text.to_string(), cursor, hover.clone()));
+        Ok(hover)
+    }
+
+    /// Returns a completer asking the server for completions. Errors, such as the server
+    /// taking too long, give no candidates.
+    pub fn completer(&self) -> CompleterFunc {
+        let client = self.clone();
+        Box::new(move |ctx| client.completions(ctx.line, ctx.cursor).unwrap_or_default())
+    }
+
+    /// Returns a signature hint showing the first line of the server's hover text for the
+    /// input at the cursor, for `ReplBuilder::signature_help`.
+    pub fn hover_hint(&self) -> SignatureFunc {
+        let client = self.clone();
+        Box::new(move |line, cursor| {
+            let hover = client.hover(line, cursor).ok()??;
+            let first = hover.lines().find(|l| !l.trim().is_empty())?;
+            Some(StyledText::from(first.trim()))
+        })
+    }
+
+    /// Lock the connection, recovering it if another thread panicked while holding the lock.
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Connection {
+    /// Send the input as the document's new text if it changed.
+    fn sync(&mut self, text: &str) -> Result<()> {
+        if self.text == text {
+            return Ok(());
+        }
+        self.version += 1;
+        self.text = text.to_string();
+        let params = json!({
+            "textDocument": { "uri": DOCUMENT_URI, "version": self.version },
+            "contentChanges": [{ "text": text }],
+        });
+        self.notify("textDocument/didChange", params)
+    }
+
+    /// Send a request and wait up to `timeout` for its result.
+    fn request(&mut self, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let message = match self.messages.recv_timeout(remaining) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(Error::Internal(InternalError::IoRead(
+                        format!("language server didn't answer {} in time", method).into(),
+                    )));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(Error::Internal(InternalError::IoRead(
+                        "language server exited".into(),
+                    )));
+                }
+            };
+            match (message.get("id"), message.get("method")) {
+                // A request from the server, e.g. for configuration. Nothing is supported,
+                // but it's answered so the server doesn't wait.
+                (Some(request_id), Some(_)) => {
+                    let reply = json!({ "jsonrpc": "2.0", "id": request_id, "result": null });
+                    self.send(&reply)?;
+                }
+                (Some(response_id), None) if response_id.as_u64() == Some(id) => {
+                    if let Some(error) = message.get("error") {
+                        let text = error.get("message").and_then(Value::as_str);
+                        return Err(Error::Internal(InternalError::IoRead(
+                            format!("language server error: {}", text.unwrap_or("unknown")).into(),
+                        )));
+                    }
+                    return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+                }
+                // Notifications and responses to requests that timed out.
+                _ => {}
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    fn send(&mut self, message: &Value) -> Result<()> {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| {
+                Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+                    "unable to write to language server",
+                    e,
+                )))
+            })
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.next_id += 1;
+        let shutdown = json!({ "jsonrpc": "2.0", "id": self.next_id, "method": "shutdown" });
+        let _ = self.send(&shutdown);
+        let _ = self.notify("exit", Value::Null);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Read one message, or `None` once the server closes its output or sends something that
+/// isn't a message.
+fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; length?];
+    reader.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+/// Returns the document and position parameters for the byte offset `cursor` in `text`.
+fn position_params(text: &str, cursor: usize) -> Value {
+    let before = &text[..cursor];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    // Positions count UTF-16 code units.
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    json!({
+        "textDocument": { "uri": DOCUMENT_URI },
+        "position": { "line": line, "character": character },
+    })
+}
+
+/// Returns the byte offset in `text` of an LSP position, or `None` if it's outside it.
+fn offset_of(text: &str, position: &Value) -> Option<usize> {
+    let line = usize::try_from(position.get("line")?.as_u64()?).ok()?;
+    let character = usize::try_from(position.get("character")?.as_u64()?).ok()?;
+    let line_start = if line == 0 {
+        0
+    } else {
+        text.match_indices('\n').nth(line - 1)?.0 + 1
+    };
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return (units == character).then_some(line_start + i);
+        }
+        units += c.len_utf16();
+    }
+    (units == character).then_some(text.len())
+}
+
+/// Returns the byte offset where the identifier before `cursor` starts.
+fn word_start(text: &str, cursor: usize) -> usize {
+    text[..cursor]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+        .last()
+        .map_or(cursor, |(i, _)| i)
+}
+
+/// Returns the text of a string, `MarkupContent` or `MarkedString`, or `None` if it's empty.
+fn markup_text(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(text) => text.as_str(),
+        Value::Object(markup) => markup.get("value")?.as_str()?,
+        _ => return None,
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
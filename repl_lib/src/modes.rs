@@ -0,0 +1,127 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::mem;
+
+use crate::{CompleterFunc, Line, PromptFunc, Repl, Result, SharedHistory, StyledText, padding};
+
+/// Prompt, completer and history of a sub-mode such as a debugger or transaction shell,
+/// entered with `Repl::push_mode`.
+pub struct SubMode {
+    prompt: StyledText,
+    continuation_prompt: Option<StyledText>,
+    completer: Option<CompleterFunc>,
+    history: SharedHistory,
+}
+
+impl SubMode {
+    /// Create a sub-mode showing `prompt`, without completion, and with its own empty
+    /// history.
+    pub fn new(prompt: impl Into<StyledText>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            continuation_prompt: None,
+            completer: None,
+            history: SharedHistory::new(),
+        }
+    }
+
+    /// Set the prompt shown before continuation lines. Defaults to padding matching the
+    /// prompt's width.
+    pub fn continuation_prompt(mut self, prompt: impl Into<StyledText>) -> Self {
+        self.continuation_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Complete with `completer` in this mode.
+    pub fn completer(mut self, completer: CompleterFunc) -> Self {
+        self.completer = Some(completer);
+        self
+    }
+
+    /// Use `history` instead of a new empty one, e.g. a clone of a handle kept so the mode's
+    /// history lasts between visits, or one opened with a store to save it.
+    pub fn history(mut self, history: SharedHistory) -> Self {
+        self.history = history;
+        self
+    }
+}
+
+/// State of the mode a `SubMode` was entered from, restored when it's left.
+pub(crate) struct SavedMode {
+    prompt: StyledText,
+    continuation_prompt: StyledText,
+    padded_continuation: bool,
+    prompt_func: Option<PromptFunc>,
+    completer: Option<CompleterFunc>,
+    history: SharedHistory,
+    lines: Vec<Line>,
+    history_seen: usize,
+    last_entry: Option<usize>,
+    committed_line: usize,
+}
+
+impl<T> Repl<T> {
+    /// Enter `mode`, switching to its prompt, completer and history from the next read until
+    /// `pop_mode`. Modes nest; call from code that runs between reads, such as a
+    /// post-output hook or a `process_input` loop.
+    ///
+    /// A `prompt_func` and numbered prompts are suspended while in a sub-mode.
+    pub fn push_mode(&mut self, mode: SubMode) {
+        let continuation_prompt = match mode.continuation_prompt {
+            Some(prompt) => prompt,
+            None => padding(&mode.prompt),
+        };
+        let saved = SavedMode {
+            prompt: mem::replace(&mut self.prompt, mode.prompt),
+            continuation_prompt: mem::replace(&mut self.continuation_prompt, continuation_prompt),
+            padded_continuation: mem::replace(&mut self.padded_continuation, false),
+            prompt_func: self.prompt_func.take(),
+            completer: mem::replace(&mut self.completer, mode.completer),
+            history: mem::replace(&mut self.history, mode.history),
+            lines: mem::replace(&mut self.lines, vec![Line::new()]),
+            history_seen: mem::take(&mut self.history_seen),
+            last_entry: self.last_entry.take(),
+            committed_line: mem::take(&mut self.committed_line),
+        };
+        self.modes.push(saved);
+        self.current_line = 0;
+        self.sync_history();
+    }
+
+    /// Leave the innermost sub-mode, restoring the prompt, completer and history it was
+    /// entered from. Its history is saved if it has a store. Returns `false` if not in a
+    /// sub-mode.
+    pub fn pop_mode(&mut self) -> Result<bool> {
+        let Some(saved) = self.modes.pop() else {
+            return Ok(false);
+        };
+        let result = self.history.lock().save();
+        self.prompt = saved.prompt;
+        self.continuation_prompt = saved.continuation_prompt;
+        self.padded_continuation = saved.padded_continuation;
+        self.prompt_func = saved.prompt_func;
+        self.completer = saved.completer;
+        self.history = saved.history;
+        self.lines = saved.lines;
+        self.history_seen = saved.history_seen;
+        self.last_entry = saved.last_entry;
+        self.committed_line = saved.committed_line;
+        self.current_line = self.lines.len() - 1;
+        self.sync_history();
+        result.map(|_| true)
+    }
+
+    /// Returns the number of sub-modes entered and not yet left.
+    pub fn mode_depth(&self) -> usize {
+        self.modes.len()
+    }
+
+    /// Save the histories of the modes sub-modes were entered from.
+    pub(crate) fn save_mode_histories(&mut self) {
+        for saved in &self.modes {
+            let _ = saved.history.lock().save();
+        }
+    }
+}
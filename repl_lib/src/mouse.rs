@@ -0,0 +1,72 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use crate::{Repl, Result, TAB_WIDTH, char_width};
+
+/// SGR mouse button code for a left click without modifiers.
+const LEFT_BUTTON: usize = 0;
+/// SGR mouse button codes for the scroll wheel.
+const WHEEL_UP: usize = 64;
+const WHEEL_DOWN: usize = 65;
+
+impl<T> Repl<T> {
+    /// Handles an SGR mouse report (`ESC [ < button ; column ; row M`).
+    ///
+    /// The screen row of the input isn't tracked, so the terminal is asked for the cursor
+    /// position to find which row of the input the event happened on.
+    pub(crate) fn handle_mouse(&mut self, params: &[usize], pressed: bool) -> Result<()> {
+        let [button, mouse_col, mouse_row] = *params else {
+            return Ok(());
+        };
+        if !pressed || !matches!(button, LEFT_BUTTON | WHEEL_UP | WHEEL_DOWN) {
+            return Ok(());
+        }
+        let Ok((cursor_row, _)) = self.tmanager_mut()?.cursor_position() else {
+            return Ok(());
+        };
+
+        // Mouse reports count from 1, cursor positions from 0.
+        let Some(row) = (self.rendered_row + mouse_row).checked_sub(cursor_row as usize + 1) else {
+            return Ok(());
+        };
+        let rows = self.current_line_mut()?.text().matches('\n').count() + 1;
+        match button {
+            _ if row >= rows => Ok(()),
+            // The wheel over the input moves through history like the arrow keys.
+            WHEEL_UP => self.handle_escape_sequence(b'A'),
+            WHEEL_DOWN => self.handle_escape_sequence(b'B'),
+            _ => self.click(row, mouse_col),
+        }
+    }
+
+    /// Moves the cursor to screen column `click_col` on physical line `row` of the input.
+    fn click(&mut self, row: usize, click_col: usize) -> Result<()> {
+        let prompt_width = if row == 0 {
+            self.prompt.width()
+        } else {
+            self.continuation_prompt.width()
+        };
+        let line = self.current_line_mut()?;
+        let physical = line.text().split('\n').nth(row).unwrap_or("");
+
+        let target = click_col.saturating_sub(1);
+        let mut screen_col = prompt_width;
+        let mut col = 0;
+        for c in physical.chars() {
+            let width = if c == '\t' {
+                TAB_WIDTH - screen_col % TAB_WIDTH
+            } else {
+                char_width(c)
+            };
+            if screen_col + width > target {
+                break;
+            }
+            screen_col += width;
+            col += 1;
+        }
+
+        line.set_row_col(row, col);
+        self.redraw_current_line()
+    }
+}
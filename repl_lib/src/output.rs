@@ -0,0 +1,185 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! Formatting for common kinds of results: status lines, aligned key/value pairs and tables.
+//!
+//! Each helper returns `StyledText`, which `EvalContext::print` writes with the colors the
+//! terminal supports, or none in screen reader mode.
+
+use term_manager::{Attribute, Color};
+
+use crate::{Style, StyledText, text_width};
+
+/// Spaces between table columns.
+const COLUMN_GAP: usize = 2;
+/// Narrowest a table column is made to fit the width.
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// Severity of a status line, shown as a colored label.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Level {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn label(self) -> (&'static str, Color) {
+        match self {
+            Level::Info => ("info", Color::Blue),
+            Level::Success => ("ok", Color::Green),
+            Level::Warning => ("warning", Color::Yellow),
+            Level::Error => ("error", Color::Red),
+        }
+    }
+}
+
+/// Returns `message` after a bold label colored by `level`, e.g. `warning: disk almost full`.
+pub fn status(level: Level, message: &str) -> StyledText {
+    let (label, color) = level.label();
+    StyledText::new()
+        .span(label, Style::new().fg(color).attr(Attribute::Bold))
+        .plain(format!(": {}", message))
+}
+
+/// Returns `text` underlined as a link to `url`, clickable in terminals with hyperlinks and
+/// plain elsewhere.
+pub fn link(text: &str, url: &str) -> StyledText {
+    StyledText::new().link(text, url, Style::new().attr(Attribute::Underline))
+}
+
+/// Returns one `key: value` line per pair, with the values aligned after the widest key.
+pub fn key_values<K: AsRef<str>, V: AsRef<str>>(pairs: &[(K, V)]) -> StyledText {
+    let key_width = pairs
+        .iter()
+        .map(|(key, _)| text_width(key.as_ref()))
+        .max()
+        .unwrap_or(0);
+    let mut text = StyledText::new();
+    for (i, (key, value)) in pairs.iter().enumerate() {
+        if i > 0 {
+            text.push("\n", Style::new());
+        }
+        let key = key.as_ref();
+        text.push(format!("{}:", key), Style::new().attr(Attribute::Bold));
+        let padding = key_width - text_width(key) + 1;
+        text.push(
+            format!("{}{}", " ".repeat(padding), value.as_ref()),
+            Style::new(),
+        );
+    }
+    text
+}
+
+/// Rows of text in aligned columns under a bold header.
+///
+/// ```text
+/// name   size  modified
+/// ─────  ────  ──────────
+/// a.txt  12    2026-10-16
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Create a table with the given column headers.
+    pub fn new<I, S>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            headers: headers.into_iter().map(Into::into).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a row. Missing cells are left blank and extra cells are ignored.
+    pub fn row<I, S>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.push_row(cells);
+        self
+    }
+
+    /// Add a row to a table being filled in a loop.
+    pub fn push_row<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut cells: Vec<String> = cells.into_iter().map(Into::into).collect();
+        cells.resize(self.headers.len(), String::new());
+        self.rows.push(cells);
+    }
+
+    /// Returns the table laid out to fit in `width` columns, e.g. `EvalContext::width`.
+    ///
+    /// When it's too wide, the widest columns are narrowed and their cells cut short with
+    /// `…`. Without a width, columns are as wide as their contents.
+    pub fn render(&self, width: Option<usize>) -> StyledText {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| text_width(h)).collect();
+        for row in &self.rows {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(text_width(cell));
+            }
+        }
+        if let Some(max) = width {
+            let gaps = COLUMN_GAP * widths.len().saturating_sub(1);
+            while widths.iter().sum::<usize>() + gaps > max {
+                let Some(widest) = widths.iter_mut().filter(|w| **w > MIN_COLUMN_WIDTH).max()
+                else {
+                    break;
+                };
+                *widest -= 1;
+            }
+        }
+
+        let mut text = StyledText::new();
+        push_cells(
+            &mut text,
+            &self.headers,
+            &widths,
+            Style::new().attr(Attribute::Bold),
+        );
+        let rules: Vec<String> = widths.iter().map(|&w| "─".repeat(w)).collect();
+        text.push("\n", Style::new());
+        push_cells(
+            &mut text,
+            &rules,
+            &widths,
+            Style::new().attr(Attribute::Dim),
+        );
+        for row in &self.rows {
+            text.push("\n", Style::new());
+            push_cells(&mut text, row, &widths, Style::new());
+        }
+        text
+    }
+}
+
+/// Append one line of cells, padded or cut to the column widths.
+fn push_cells(text: &mut StyledText, cells: &[String], widths: &[usize], style: Style) {
+    let last = widths.len().saturating_sub(1);
+    for (i, (cell, &width)) in cells.iter().zip(widths).enumerate() {
+        let mut cell = StyledText::from(cell.as_str());
+        if cell.width() > width {
+            cell = cell.truncate(width.saturating_sub(1));
+            cell.push("…", Style::new());
+        }
+        let mut shown = cell.text();
+        // The last column isn't padded, so lines don't end in spaces.
+        if i < last {
+            let padding = width - cell.width() + COLUMN_GAP;
+            shown.push_str(&" ".repeat(padding));
+        }
+        text.push(shown, style);
+    }
+}
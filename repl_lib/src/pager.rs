@@ -0,0 +1,137 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use term_manager::{Attribute, clear, screen};
+
+use crate::{
+    Error, ErrorContext, InternalError, Repl, Result, Style, StyledText, TAB_WIDTH, char_width,
+};
+
+impl<T> Repl<T> {
+    /// Show `text` a screen at a time on the alternate screen if it's taller than the
+    /// terminal. Returns `false` without printing anything if it fits or can't be paged.
+    pub(crate) fn page(&mut self, text: &str) -> Result<bool> {
+        let Some(tmanager) = self.tmanager.as_ref() else {
+            return Ok(false);
+        };
+        let Ok((rows, cols)) = tmanager.size() else {
+            return Ok(false);
+        };
+        if !tmanager.caps().alternate_screen || rows < 2 || cols == 0 {
+            return Ok(false);
+        }
+        let lines = wrap(text, cols as usize);
+        // The bottom row holds the status line.
+        if lines.len() < rows as usize {
+            return Ok(false);
+        }
+        self.show_pages(&lines, 0)?;
+        Ok(true)
+    }
+
+    /// Show `lines` on the alternate screen starting at `top`, scrolling until the viewer is
+    /// closed. The caller checks the terminal supports the alternate screen.
+    pub(crate) fn show_pages(&mut self, lines: &[String], top: usize) -> Result<()> {
+        let (rows, cols) = self.tmanager_mut()?.size().map_err(pager_error)?;
+        let height = (rows as usize).saturating_sub(1).max(1);
+        self.flush()?;
+        self.tmanager_mut()?
+            .enter_alternate_screen()
+            .map_err(pager_error)?;
+        if self.status.is_some() {
+            self.write(screen::RESET_SCROLL_REGION)?;
+        }
+        let last_top = lines.len().saturating_sub(height);
+        let mut top = top.min(last_top);
+        loop {
+            self.draw_page(lines, top, height, cols as usize)?;
+            let sequence = self.read_sequence()?;
+            top = match sequence.as_slice() {
+                b" " | b"f" | b"\x1b[6~" if top == last_top => break,
+                b" " | b"f" | b"\x1b[6~" => (top + height).min(last_top),
+                b"\r" | b"\n" | b"j" | b"\x1b[B" | b"\x1bOB" if top == last_top => break,
+                b"\r" | b"\n" | b"j" | b"\x1b[B" | b"\x1bOB" => top + 1,
+                b"b" | b"\x1b[5~" => top.saturating_sub(height),
+                b"k" | b"\x1b[A" | b"\x1bOA" => top.saturating_sub(1),
+                b"g" => 0,
+                b"G" => last_top,
+                b"q" | b"Q" | b"\x1b" | b"\x03" => break,
+                _ => top,
+            };
+        }
+        self.tmanager_mut()?
+            .leave_alternate_screen()
+            .map_err(pager_error)?;
+        let sequence = self.status_sequence();
+        self.write(&sequence)
+    }
+
+    /// Draw `height` lines starting at `top`, followed by the status line cut to `cols`.
+    fn draw_page(
+        &mut self,
+        lines: &[String],
+        top: usize,
+        height: usize,
+        cols: usize,
+    ) -> Result<()> {
+        let mut out = String::from(clear::SCREEN);
+        let bottom = (top + height).min(lines.len());
+        for line in &lines[top..bottom] {
+            out.push_str(line);
+            out.push('\n');
+        }
+        // Keep the status line at the bottom when there are fewer lines than rows.
+        out.push_str(&"\n".repeat(top + height - bottom));
+        let status = format!(
+            "lines {}-{} of {} (space: page, enter: line, b: back, q: quit)",
+            top + 1,
+            bottom,
+            lines.len()
+        );
+        let status: String = status.chars().take(cols.saturating_sub(1)).collect();
+        let style = Style::new().attr(Attribute::Reverse);
+        out.push_str(
+            &StyledText::new()
+                .span(status, style)
+                .render(self.color_depth),
+        );
+        self.write(&out)?;
+        self.flush()
+    }
+}
+
+/// Split `text` into screen lines no wider than `cols`, expanding tabs.
+pub(crate) fn wrap(text: &str, cols: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let mut current = String::new();
+        let mut width = 0;
+        for c in line.chars() {
+            let w = if c == '\t' {
+                TAB_WIDTH - width % TAB_WIDTH
+            } else {
+                char_width(c)
+            };
+            if width + w > cols && width > 0 {
+                lines.push(std::mem::take(&mut current));
+                width = 0;
+            }
+            if c == '\t' {
+                current.extend(std::iter::repeat_n(' ', w.min(cols)));
+            } else {
+                current.push(c);
+            }
+            width += w;
+        }
+        lines.push(current);
+    }
+    lines
+}
+
+fn pager_error(e: term_manager::Error) -> Error {
+    Error::Internal(InternalError::IoWrite(ErrorContext::with_source(
+        "unable to switch screens for the pager",
+        e,
+    )))
+}
@@ -0,0 +1,111 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{iter::Peekable, str::Chars};
+
+use crate::{Repl, Result, exceeds};
+
+/// Function type for reviewing a bracketed paste before it's inserted.
+///
+/// Receives the pasted text and returns the text to insert, or `None` to discard it.
+pub type PasteFunc = Box<dyn FnMut(&str) -> Option<String>>;
+
+/// Sent by the terminal before and after pasted text while bracketed paste is enabled.
+pub(crate) const PASTE_START: &[u8] = b"\x1b[200~";
+pub(crate) const PASTE_END: &[u8] = b"\x1b[201~";
+
+impl<T> Repl<T> {
+    /// Read the text of a bracketed paste from the replay script or keys read again, after
+    /// its `ESC [ 200 ~` start has been read.
+    pub(crate) fn read_paste(&mut self) -> Result<String> {
+        let mut pasted = Vec::new();
+        while !pasted.ends_with(PASTE_END) {
+            pasted.push(self.read_byte()?);
+        }
+        pasted.truncate(pasted.len() - PASTE_END.len());
+        Ok(String::from_utf8_lossy(&pasted).into_owned())
+    }
+
+    /// Insert pasted text.
+    ///
+    /// The whole paste is inserted at once, with escape sequences and control characters
+    /// removed so it can't act as keys or reach the terminal when redrawn. A `paste_func` can
+    /// replace or discard it first.
+    pub(crate) fn paste(&mut self, pasted: &str) -> Result<()> {
+        let mut text = sanitize(pasted);
+        if let Some(paste_func) = self.paste_func.as_mut() {
+            match paste_func(&text) {
+                // What it returns is cleaned too, as it may be built from other input.
+                Some(replacement) => text = sanitize(&replacement),
+                None => return Ok(()),
+            }
+        }
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        self.search_prefix = None;
+        self.kill_ring.last_yank = None;
+        if self.overwrite {
+            return self.overwrite(&text).map(|_| ());
+        }
+        let max_length = self.max_length;
+        let line = self.current_line_mut()?;
+        if exceeds(max_length, &line.text, &text) {
+            return self.bell();
+        }
+        line.insert_str(&text);
+        self.redraw_current_line()
+    }
+}
+
+/// Returns pasted `text` with line endings as `\n`, and escape sequences and control
+/// characters other than `\n` and `\t` removed.
+fn sanitize(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut clean = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' | '\t' => clean.push(c),
+            '\x1b' => match chars.next() {
+                Some('[') => skip_csi(&mut chars),
+                Some(']' | 'P' | 'X' | '^' | '_') => skip_string(&mut chars),
+                // Other escapes are intermediate bytes and one final byte, e.g. `ESC ( B`.
+                Some(' '..='/') => {
+                    while chars.next_if(|c| matches!(c, ' '..='/')).is_some() {}
+                    chars.next();
+                }
+                _ => {}
+            },
+            // The 8-bit forms of CSI, and of OSC, DCS and the other string controls
+            '\u{9b}' => skip_csi(&mut chars),
+            '\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}' => skip_string(&mut chars),
+            c if c.is_control() => {}
+            c => clean.push(c),
+        }
+    }
+    clean
+}
+
+/// Skip the parameters, intermediate bytes and final byte of a CSI sequence.
+fn skip_csi(chars: &mut Peekable<Chars>) {
+    while chars
+        .next_if(|c| matches!(c, '0'..='?' | ' '..='/'))
+        .is_some()
+    {}
+    chars.next_if(|c| matches!(c, '@'..='~'));
+}
+
+/// Skip the body of an OSC, DCS or other string sequence, up to and including the BEL or
+/// string terminator that ends it.
+fn skip_string(chars: &mut Peekable<Chars>) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\x07' | '\u{9c}' => return,
+            '\x1b' if chars.next_if_eq(&'\\').is_some() => return,
+            _ => {}
+        }
+    }
+}
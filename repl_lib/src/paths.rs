@@ -0,0 +1,53 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! Default locations for an application's files, following each platform's conventions.
+//!
+//! On macOS files go in `~/Library/Application Support/<app>`. On Linux and other Unix
+//! systems they follow the XDG base directory specification: configuration in
+//! `$XDG_CONFIG_HOME/<app>` (default `~/.config/<app>`) and history in
+//! `$XDG_STATE_HOME/<app>` (default `~/.local/state/<app>`).
+//!
+//! Directories aren't created; `FileHistory` creates the history file's directory when it
+//! first saves.
+
+use std::{env, path::PathBuf};
+
+/// Returns the default configuration file of `app`, `config.toml` in its configuration
+/// directory, or `None` if the home directory is unknown.
+pub fn config_file(app: &str) -> Option<PathBuf> {
+    Some(config_dir(app)?.join("config.toml"))
+}
+
+/// Returns the default history file of `app`, `history` in its state directory, or `None` if
+/// the home directory is unknown.
+pub fn history_file(app: &str) -> Option<PathBuf> {
+    Some(state_dir(app)?.join("history"))
+}
+
+/// Returns the directory for the configuration of `app`.
+pub fn config_dir(app: &str) -> Option<PathBuf> {
+    base_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join(app))
+}
+
+/// Returns the directory for state `app` keeps between runs, such as history.
+pub fn state_dir(app: &str) -> Option<PathBuf> {
+    base_dir("XDG_STATE_HOME", ".local/state").map(|dir| dir.join(app))
+}
+
+/// Returns the platform's base directory: Application Support on macOS, otherwise the XDG
+/// directory named by `var`, or `default` under the home directory if it's unset or relative.
+fn base_dir(var: &str, default: &str) -> Option<PathBuf> {
+    let home = env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from);
+    if cfg!(target_os = "macos") {
+        return home.map(|home| home.join("Library/Application Support"));
+    }
+    // The specification says relative paths are invalid and should be ignored.
+    match env::var_os(var).map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => Some(dir),
+        _ => home.map(|home| home.join(default)),
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use crate::{Repl, Result};
+
+/// A reusable bundle of REPL behavior, such as a set of meta-commands with the hooks that
+/// support them.
+///
+/// A plugin registers its parts through the `Repl` methods available to applications, e.g.
+/// `Repl::register_command`, `Repl::register_completer`, `Repl::bind_key` and
+/// `Repl::register_pre_input_hook`, so anything an application can add to a REPL can be
+/// packaged as a plugin.
+pub trait ReplPlugin<T = String> {
+    /// Add the plugin's meta-commands, completers, key bindings, hooks, and settings to
+    /// `repl`.
+    fn register(self, repl: &mut Repl<T>) -> Result<()>;
+}
+
+impl<T> Repl<T> {
+    /// Add a plugin's behavior to the REPL.
+    ///
+    /// Plugins registered later take precedence where they overlap, e.g. two plugins
+    /// registering a meta-command with the same name.
+    pub fn register_plugin(&mut self, plugin: impl ReplPlugin<T>) -> Result<()> {
+        plugin.register(self)
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use crate::{Repl, StyledText};
+
+/// Function type for rendering the prompt from the REPL's state.
+pub type PromptFunc = Box<dyn FnMut(&PromptContext) -> StyledText>;
+
+/// State passed to a `PromptFunc`.
+#[derive(Copy, Clone, Debug)]
+pub struct PromptContext {
+    /// The last `process_line` call returned an error.
+    pub last_failed: bool,
+    /// The input continues past its first line, e.g. after the validator reported it
+    /// incomplete.
+    pub incomplete: bool,
+    /// Number of the next line passed to `process_line`, starting at 1.
+    pub execution_count: usize,
+}
+
+impl<T> Repl<T> {
+    /// Render the prompt with the `PromptFunc`, if one is set.
+    pub(crate) fn refresh_prompt(&mut self) {
+        let Some(prompt_func) = self.prompt_func.as_mut() else {
+            return;
+        };
+        let ctx = PromptContext {
+            last_failed: self.last_failed,
+            incomplete: self
+                .lines
+                .get(self.current_line)
+                .is_some_and(|line| line.text.contains('\n')),
+            execution_count: self.execution_count,
+        };
+        let prompt = prompt_func(&ctx);
+        self.set_prompt(prompt);
+    }
+}
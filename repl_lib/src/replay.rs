@@ -0,0 +1,300 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{collections::VecDeque, fs, path::Path, thread, time::Duration};
+
+use term_manager::input::{self, KeyEvent};
+
+use crate::{Error, ErrorContext, InternalError, Repl, Result};
+
+/// Scripted keyboard input, fed to the line editor one key at a time.
+///
+/// Scripts are plain text where each newline presses Enter. Other keys are written as
+/// `<Name>`: `<Enter>`, `<Tab>`, `<Esc>`, `<Backspace>`, `<Up>`, `<Down>`, `<Left>`,
+/// `<Right>`, `<Home>`, `<End>`, `<Delete>` and `<C-x>` for Ctrl + `x`. A backslash
+/// escapes the next character (`\<`, `\\`), and `\xHH` inserts a raw byte.
+///
+/// Input fed with `Repl::feed_bytes` is queued the same way.
+pub(crate) struct Replay {
+    keys: VecDeque<Vec<u8>>,
+    pending: VecDeque<u8>,
+    delay: Duration,
+    /// Input ends with the script, rather than going on from the terminal.
+    script: bool,
+}
+
+impl Replay {
+    /// Load the script at `path`, pausing for `delay` before each key.
+    pub(crate) fn open(path: &Path, delay: Duration) -> Result<Self> {
+        let script = fs::read_to_string(path).map_err(|e| {
+            Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                format!("unable to read replay script {}", path.display()),
+                e,
+            )))
+        })?;
+        Ok(Self {
+            keys: parse(&script)?,
+            pending: VecDeque::new(),
+            delay,
+            script: true,
+        })
+    }
+
+    /// Create an empty queue for fed input, read without pauses.
+    pub(crate) fn new() -> Self {
+        Self {
+            keys: VecDeque::new(),
+            pending: VecDeque::new(),
+            delay: Duration::ZERO,
+            script: false,
+        }
+    }
+
+    /// Queue `bytes` after the rest of the input.
+    pub(crate) fn feed(&mut self, bytes: &[u8]) {
+        if !bytes.is_empty() {
+            self.keys.push_back(bytes.to_vec());
+        }
+    }
+
+    /// Returns whether this is a script, whose end ends the input.
+    pub(crate) fn is_script(&self) -> bool {
+        self.script
+    }
+
+    /// Returns whether all input has been read.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.pending.is_empty()
+    }
+
+    /// Returns the next byte of input, or `None` once the script is exhausted.
+    pub(crate) fn next_byte(&mut self) -> Option<u8> {
+        if self.pending.is_empty() {
+            self.pending.extend(self.keys.pop_front()?);
+            if !self.delay.is_zero() {
+                thread::sleep(self.delay);
+            }
+        }
+        self.pending.pop_front()
+    }
+
+    /// Returns the next byte of the key being read, or `None` at its end. Like a terminal
+    /// read, keys queued separately don't run together, so `<Esc>` followed by `x` isn't
+    /// taken for Alt-X.
+    pub(crate) fn next_byte_of_key(&mut self) -> Option<u8> {
+        self.pending.pop_front()
+    }
+}
+
+impl<T> Repl<T> {
+    /// Queue `bytes` as if typed, to be read by the line editor before the terminal.
+    ///
+    /// They go through the same decoding and editing as input from the terminal, bracketed
+    /// pastes included, so tests and fuzz targets can drive the editor deterministically.
+    /// Without a terminal the REPL becomes interactive, as with `ReplBuilder::replay`, and
+    /// reads return `InternalError::Eof` once everything fed has been read. On a terminal,
+    /// reading goes on from it.
+    pub fn feed_bytes(&mut self, bytes: &[u8]) {
+        self.replay.get_or_insert_with(Replay::new).feed(bytes);
+    }
+
+    /// Queue `keys` as if typed, encoded as a terminal sends them. See `feed_bytes`.
+    pub fn feed_keys(&mut self, keys: &[KeyEvent]) {
+        for &key in keys {
+            self.feed_bytes(&input::encode(key));
+        }
+    }
+
+    /// Returns the script or fed input to read next, or `None` to read from the terminal.
+    pub(crate) fn replay_input(&mut self) -> Option<&mut Replay> {
+        let terminal = self.tmanager.is_some();
+        self.replay
+            .as_mut()
+            .filter(|replay| replay.is_script() || !terminal || !replay.is_empty())
+    }
+}
+
+/// Split a script into the bytes sent for each key.
+fn parse(script: &str) -> Result<VecDeque<Vec<u8>>> {
+    let mut keys = VecDeque::new();
+    let mut chars = script.chars();
+
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\n' => vec![b'\r'],
+            '\\' => match chars.next() {
+                Some('x') => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    let byte = u8::from_str_radix(&hex, 16)
+                        .map_err(|_| invalid_script(&format!("\\x{}", hex)))?;
+                    vec![byte]
+                }
+                Some(escaped) => escaped.to_string().into_bytes(),
+                None => return Err(invalid_script("\\")),
+            },
+            '<' => {
+                let name: String = chars.by_ref().take_while(|&c| c != '>').collect();
+                named_key(&name).ok_or_else(|| invalid_script(&format!("<{}>", name)))?
+            }
+            c => c.to_string().into_bytes(),
+        };
+        keys.push_back(key);
+    }
+
+    Ok(keys)
+}
+
+/// Returns the bytes a terminal sends for the key called `name`.
+fn named_key(name: &str) -> Option<Vec<u8>> {
+    let bytes: &[u8] = match name {
+        "Enter" => b"\r",
+        "Tab" => b"\t",
+        "Esc" => b"\x1b",
+        "Backspace" => b"\x7f",
+        "Up" => b"\x1b[A",
+        "Down" => b"\x1b[B",
+        "Right" => b"\x1b[C",
+        "Left" => b"\x1b[D",
+        "Home" => b"\x1b[H",
+        "End" => b"\x1b[F",
+        "Delete" => b"\x1b[3~",
+        _ => {
+            let c = name.strip_prefix("C-")?;
+            let [c] = c.as_bytes() else {
+                return None;
+            };
+            return c
+                .is_ascii_alphabetic()
+                .then(|| vec![c.to_ascii_lowercase() - b'a' + 1]);
+        }
+    };
+    Some(bytes.to_vec())
+}
+
+fn invalid_script(token: &str) -> Error {
+    Error::Internal(InternalError::InitFail(
+        format!("invalid key '{}' in replay script", token).into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use term_manager::input::KeyCode;
+
+    use super::*;
+    use crate::ReplBuilder;
+
+    fn repl() -> Repl<String> {
+        ReplBuilder::new().build_detached().unwrap()
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code)
+    }
+
+    #[test]
+    fn renders_to_the_output() {
+        let mut repl = ReplBuilder::new().prompt("$ ").build_detached().unwrap();
+        repl.feed_bytes(b"hi\r");
+        assert_eq!(repl.process_input().unwrap(), "hi");
+        let output = repl.take_output();
+        assert!(output.contains("$ hi"), "{:?}", output);
+        assert_eq!(repl.take_output(), "");
+    }
+
+    #[test]
+    fn cursor_moves() {
+        let mut repl = repl();
+        repl.feed_bytes(b"bd");
+        repl.feed_keys(&[key(KeyCode::Left)]);
+        repl.feed_bytes(b"c");
+        repl.feed_keys(&[KeyEvent::ctrl('a')]);
+        repl.feed_bytes(b"a");
+        repl.feed_keys(&[KeyEvent::ctrl('e')]);
+        repl.feed_bytes(b"e\r");
+        assert_eq!(repl.process_input().unwrap(), "abcde");
+
+        repl.feed_bytes(b"one three");
+        repl.feed_keys(&[KeyEvent::alt('b'), key(KeyCode::Left)]);
+        repl.feed_bytes(b" two");
+        repl.feed_keys(&[KeyEvent::ctrl('a'), KeyEvent::alt('f')]);
+        repl.feed_bytes(b",\r");
+        assert_eq!(repl.process_input().unwrap(), "one, two three");
+    }
+
+    #[test]
+    fn drops_partial_characters() {
+        let mut repl = repl();
+        repl.feed_bytes(b"a\xc3");
+        repl.feed_bytes(b"b\x9b\xe2\x82");
+        repl.feed_bytes(b"\xe2\x82\xac\r");
+        assert_eq!(repl.process_input().unwrap(), "ab\u{20ac}");
+        assert!(!repl.take_output().contains(['\u{c3}', '\u{9b}']));
+    }
+
+    #[test]
+    fn kill_and_yank() {
+        let mut repl = repl();
+        repl.feed_bytes(b"hello world");
+        repl.feed_keys(&[
+            KeyEvent::ctrl('w'),
+            KeyEvent::ctrl('a'),
+            KeyEvent::ctrl('y'),
+        ]);
+        repl.feed_bytes(b" \r");
+        assert_eq!(repl.process_input().unwrap(), "world hello ");
+
+        // Alt-Y replaces the text just yanked with older kills in turn.
+        repl.feed_bytes(b"abc");
+        repl.feed_keys(&[
+            key(KeyCode::Left),
+            KeyEvent::ctrl('k'),
+            KeyEvent::ctrl('u'),
+            KeyEvent::ctrl('y'),
+            KeyEvent::alt('y'),
+        ]);
+        repl.feed_bytes(b"\r");
+        assert_eq!(repl.process_input().unwrap(), "c");
+
+        repl.feed_keys(&[KeyEvent::ctrl('y'), KeyEvent::alt('y'), KeyEvent::alt('y')]);
+        repl.feed_bytes(b"\r");
+        assert_eq!(repl.process_input().unwrap(), "world");
+    }
+
+    #[test]
+    fn history_recall() {
+        let mut repl = repl();
+        repl.feed_bytes(b"first\rsecond\r");
+        assert_eq!(repl.process_input().unwrap(), "first");
+        assert_eq!(repl.process_input().unwrap(), "second");
+
+        repl.feed_keys(&[key(KeyCode::Up), key(KeyCode::Up)]);
+        repl.feed_bytes(b"!\r");
+        assert_eq!(repl.process_input().unwrap(), "first!");
+
+        repl.feed_bytes(b"new");
+        repl.feed_keys(&[key(KeyCode::Up), key(KeyCode::Down)]);
+        repl.feed_bytes(b"\r");
+        assert_eq!(repl.process_input().unwrap(), "new");
+    }
+
+    #[test]
+    fn down_at_the_newest_line_keeps_history_in_place() {
+        let mut repl = repl();
+        repl.feed_bytes(b"a\r");
+        assert_eq!(repl.process_input().unwrap(), "a");
+        repl.feed_keys(&[key(KeyCode::Down)]);
+        repl.feed_bytes(b"b\r");
+        assert_eq!(repl.process_input().unwrap(), "b");
+        repl.feed_bytes(b"c\r");
+        assert_eq!(repl.process_input().unwrap(), "c");
+
+        repl.feed_keys(&[key(KeyCode::Up); 3]);
+        repl.feed_bytes(b"\r");
+        assert_eq!(repl.process_input().unwrap(), "a");
+        repl.feed_keys(&[key(KeyCode::Up); 3]);
+        repl.feed_bytes(b"\r");
+        assert_eq!(repl.process_input().unwrap(), "b");
+    }
+}
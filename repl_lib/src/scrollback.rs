@@ -0,0 +1,75 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::collections::VecDeque;
+
+use crate::{Repl, Result, pager::wrap};
+
+/// Recent input and output, kept for viewing with Ctrl-X Ctrl-O on terminals with little
+/// scrollback of their own.
+pub(crate) struct Scrollback {
+    lines: VecDeque<String>,
+    max_lines: usize,
+}
+
+impl Scrollback {
+    pub(crate) fn new(max_lines: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            max_lines,
+        }
+    }
+
+    /// Add each line of `text`, dropping the oldest lines past the limit.
+    fn push(&mut self, text: &str) {
+        self.lines.extend(text.split('\n').map(str::to_string));
+        let excess = self.lines.len().saturating_sub(self.max_lines);
+        self.lines.drain(..excess);
+    }
+}
+
+impl<T> Repl<T> {
+    /// Keep a line of input, after the prompts it was typed at, for the scrollback viewer.
+    pub(crate) fn scrollback_input(&mut self, line: &str) {
+        let prompt = self.prompt.text();
+        let continuation_prompt = self.continuation_prompt.text();
+        if let Some(scrollback) = self.scrollback.as_mut() {
+            for (i, line) in line.split('\n').enumerate() {
+                let prompt = if i == 0 {
+                    &prompt
+                } else {
+                    &continuation_prompt
+                };
+                scrollback.push(&format!("{}{}", prompt, line));
+            }
+        }
+    }
+
+    /// Keep output for the scrollback viewer.
+    pub(crate) fn scrollback_output(&mut self, output: &str) {
+        if let Some(scrollback) = self.scrollback.as_mut() {
+            scrollback.push(output);
+        }
+    }
+
+    /// Show the kept input and output on the alternate screen, starting at the most recent,
+    /// then redraw the input. Rings the bell if there's nothing to show or the terminal
+    /// can't show it.
+    pub(crate) fn view_scrollback(&mut self) -> Result<()> {
+        let size = self.tmanager.as_ref().and_then(|tmanager| {
+            let (rows, cols) = tmanager.size().ok()?;
+            (tmanager.caps().alternate_screen && rows >= 2 && cols > 0).then_some(cols)
+        });
+        let (Some(cols), Some(scrollback)) = (size, self.scrollback.as_ref()) else {
+            return self.bell();
+        };
+        if scrollback.lines.is_empty() {
+            return self.bell();
+        }
+        let text = Vec::from(scrollback.lines.clone()).join("\n");
+        let lines = wrap(&text, cols as usize);
+        self.show_pages(&lines, lines.len())?;
+        self.redraw_current_line()
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    io::{self, BufRead, Write},
+    ptr,
+};
+
+use term_manager::{clear, cursor};
+
+use crate::{Error, ErrorContext, InternalError, Repl, Result};
+
+/// Initial capacity for secret buffers, large enough that typical input never grows them.
+const SECRET_CAPACITY: usize = 256;
+
+impl<T> Repl<T> {
+    /// Read a line of secret input such as a password.
+    ///
+    /// Each typed character is echoed as `mask`, or not at all if `mask` is `None`. The input
+    /// is never stored in history, nor in a keyboard macro being recorded. On a terminal, the buffers it passed through are zeroed
+    /// before returning, apart from the returned `String`; in batch mode it's read through
+    /// stdin's buffer, which isn't.
+    pub fn read_secret(&mut self, prompt: &str, mask: Option<char>) -> Result<String> {
+        let mut buf: Vec<u8> = Vec::with_capacity(SECRET_CAPACITY);
+
+        let res = if self.is_interactive() {
+            self.keyboard_macro.set_paused(true);
+            let res = self.read_secret_interactive(prompt, mask, &mut buf);
+            self.keyboard_macro.set_paused(false);
+            res
+        } else {
+            read_secret_batch(&mut buf)
+        };
+        let secret = res.and_then(|_| {
+            String::from_utf8(buf.clone()).map_err(|_| {
+                Error::Internal(InternalError::IoRead("secret is not valid UTF-8".into()))
+            })
+        });
+
+        zeroize(&mut buf);
+        if let Some(tmanager) = self.tmanager.as_mut() {
+            tmanager.wipe_read_input();
+        }
+        secret
+    }
+
+    fn read_secret_interactive(
+        &mut self,
+        prompt: &str,
+        mask: Option<char>,
+        buf: &mut Vec<u8>,
+    ) -> Result<()> {
+        // Keys typed ahead were meant for something else, not the secret.
+        if self.unread.is_empty()
+            && self.replay_input().is_none()
+            && let Some(tmanager) = self.tmanager.as_mut()
+        {
+            tmanager.discard_input().map_err(|e| {
+                Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+                    "unable to discard typed-ahead input",
+                    e,
+                )))
+            })?;
+        }
+        self.write(prompt)?;
+        self.flush()?;
+
+        loop {
+            match self.read_byte()? {
+                b'\n' | b'\r' => break,
+                0x7F | 0x08 => {
+                    // Backspace: drop a whole UTF-8 character.
+                    let mut removed = false;
+                    while let Some(last) = buf.last_mut() {
+                        let continuation = *last & 0xC0 == 0x80;
+                        *last = 0;
+                        buf.pop();
+                        removed = true;
+                        if !continuation {
+                            break;
+                        }
+                    }
+                    if removed && mask.is_some() {
+                        self.write("\x08 \x08")?;
+                    }
+                }
+                0x15 => {
+                    // Ctrl-U: discard everything typed so far.
+                    let count = String::from_utf8_lossy(buf).chars().count();
+                    if mask.is_some() && count > 0 {
+                        self.write(&format!("{}{}", cursor::left(count), clear::TO_END_OF_LINE))?;
+                    }
+                    zeroize(buf);
+                    buf.clear();
+                }
+                c if c.is_ascii_control() => {}
+                c => {
+                    push_secret(buf, c);
+                    // Echo once per character, not per UTF-8 continuation byte.
+                    if let Some(mask) = mask
+                        && c & 0xC0 != 0x80
+                    {
+                        self.write(mask.encode_utf8(&mut [0; 4]))?;
+                    }
+                }
+            }
+            self.flush()?;
+        }
+
+        self.write("\n")?;
+        Ok(())
+    }
+}
+
+/// Read one line from stdin without a terminal.
+fn read_secret_batch(buf: &mut Vec<u8>) -> Result<()> {
+    io::stdout().flush().map_err(|e| {
+        Error::Internal(InternalError::IoFlush(ErrorContext::with_source(
+            "unable to flush stdout",
+            e,
+        )))
+    })?;
+    let bytes_read = io::stdin().lock().read_until(b'\n', buf).map_err(|e| {
+        Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+            "error reading from stdin",
+            e,
+        )))
+    })?;
+    if bytes_read == 0 {
+        return Err(Error::Internal(InternalError::Eof));
+    }
+
+    while matches!(buf.last(), Some(b'\n' | b'\r')) {
+        if let Some(last) = buf.last_mut() {
+            *last = 0;
+        }
+        buf.pop();
+    }
+    Ok(())
+}
+
+/// Append `byte` to `buf`, moving to a larger buffer when it's full so the old one can be
+/// zeroed rather than freed with the secret in it.
+fn push_secret(buf: &mut Vec<u8>, byte: u8) {
+    if buf.len() == buf.capacity() {
+        let mut larger = Vec::with_capacity(buf.capacity().max(SECRET_CAPACITY) * 2);
+        larger.extend_from_slice(buf);
+        zeroize(buf);
+        *buf = larger;
+    }
+    buf.push(byte);
+}
+
+/// Overwrite the buffer with zeros in a way the compiler won't optimize out.
+fn zeroize(buf: &mut [u8]) {
+    for b in buf.iter_mut() {
+        unsafe { ptr::write_volatile(b, 0) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ReplBuilder;
+
+    #[test]
+    fn secret_is_not_recorded_in_a_keyboard_macro() {
+        let mut repl = ReplBuilder::new().build_detached().unwrap();
+        // Ctrl-X ( starts recording.
+        repl.feed_bytes(b"\x18(x\r");
+        assert_eq!(repl.process_input().unwrap(), "x");
+        repl.feed_bytes(b"hunter2\r");
+        assert_eq!(repl.read_secret("password: ", None).unwrap(), "hunter2");
+
+        // Ctrl-X ) ends recording and Ctrl-X e replays it.
+        repl.feed_bytes(b"\x18)\x18e");
+        assert_eq!(repl.process_input().unwrap(), "x");
+        repl.feed_bytes(b"y\r");
+        assert_eq!(repl.process_input().unwrap(), "y");
+        assert!(!repl.take_output().contains("hunter2"));
+    }
+}
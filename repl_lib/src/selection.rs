@@ -0,0 +1,144 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::ops::Range;
+
+use crate::{Repl, ReplState, Result, StyledText};
+
+/// A region of the input between an anchor and the cursor.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Selection {
+    anchor: usize,
+    /// Started with Ctrl-Space rather than Shift, so plain movement extends it.
+    sticky: bool,
+}
+
+impl<T> Repl<T> {
+    /// Handle a key while selecting, or one that starts a selection. Returns `None` if the
+    /// key should be handled as usual.
+    ///
+    /// Shift with Left, Right, Home, End, Up, or Down selects from the cursor, and Ctrl-Space
+    /// sets a mark that other movement extends the selection from. Ctrl-W cuts the selection
+    /// and Alt-W copies it, both into the kill ring. Any other key ends the selection, as
+    /// does editing or leaving the line after Ctrl-Space.
+    pub(crate) fn handle_selection(&mut self, sequence: &[u8]) -> Result<Option<ReplState>> {
+        let shift_move = match sequence {
+            [
+                0x1B,
+                b'[',
+                b'1',
+                b';',
+                b'2',
+                c @ (b'C' | b'D' | b'H' | b'F' | b'A' | b'B'),
+            ] => Some(*c),
+            _ => None,
+        };
+        if let Some(c) = shift_move {
+            let line = self.current_line_mut()?;
+            let cursor = line.cursor_pos;
+            match c {
+                b'D' => line.move_left(),
+                b'C' => line.move_right(),
+                b'H' => line.cursor_pos = 0,
+                b'F' => line.cursor_pos = line.text.len(),
+                b'A' => _ = line.move_up(),
+                _ => _ = line.move_down(),
+            }
+            self.selection.get_or_insert(Selection {
+                anchor: cursor,
+                sticky: false,
+            });
+            self.redraw_current_line()?;
+            return Ok(Some(ReplState::Continue));
+        }
+        if sequence == [0x00] {
+            // Ctrl-Space = set the mark
+            let anchor = self.current_line_mut()?.cursor_pos;
+            self.selection = Some(Selection {
+                anchor,
+                sticky: true,
+            });
+            self.redraw_current_line()?;
+            return Ok(Some(ReplState::Continue));
+        }
+
+        let Some(selection) = self.selection else {
+            return Ok(None);
+        };
+        let Some(range) = self.selection_range() else {
+            self.selection = None;
+            return Ok(None);
+        };
+        match sequence {
+            [0x17] | [0x1B, b'w'] => {
+                // Ctrl-W = cut the selection, Alt-W = copy it
+                self.selection = None;
+                let line = self.current_line_mut()?;
+                let text = if sequence == [0x17] {
+                    line.delete_range(range)
+                } else {
+                    line.text[range].to_string()
+                };
+                self.redraw_current_line()?;
+                self.kill(text)?;
+                return Ok(Some(ReplState::Continue));
+            }
+            _ => {}
+        }
+
+        if !selection.sticky || matches!(sequence, [b'\r' | b'\n' | 0x0F]) {
+            self.selection = None;
+            self.redraw_current_line()?;
+            return self.handle_key(sequence).map(Some);
+        }
+        let before = (self.current_line, self.current_line_mut()?.text.clone());
+        let state = self.handle_key(sequence)?;
+        let after = self.lines.get(self.current_line).map(|line| &line.text);
+        if before.0 != self.current_line || after != Some(&before.1) {
+            self.selection = None;
+            if let ReplState::Continue = state {
+                self.redraw_current_line()?;
+            }
+        }
+        Ok(Some(state))
+    }
+
+    /// Returns the byte range of the selection in the current input, if any.
+    pub(crate) fn selection_range(&self) -> Option<Range<usize>> {
+        let selection = self.selection?;
+        let line = self.lines.get(self.current_line)?;
+        let anchor = selection.anchor.min(line.text.len());
+        if !line.text.is_char_boundary(anchor) {
+            return None;
+        }
+        Some(anchor.min(line.cursor_pos)..anchor.max(line.cursor_pos))
+    }
+}
+
+/// Returns `text` with the characters in the byte range `selected` in reverse video.
+pub(crate) fn reverse_range(text: &StyledText, selected: Range<usize>) -> StyledText {
+    let mut reversed = StyledText::new();
+    let mut offset = 0;
+    for span in text.spans() {
+        let span_range = offset..offset + span.text.len();
+        offset = span_range.end;
+        let start = selected.start.clamp(span_range.start, span_range.end) - span_range.start;
+        let end = selected.end.clamp(span_range.start, span_range.end) - span_range.start;
+        let mut style = span.style;
+        for (i, part) in [
+            &span.text[..start],
+            &span.text[start..end],
+            &span.text[end..],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if !part.is_empty() {
+                style.reverse = i == 1 || span.style.reverse;
+                reversed.push(part, style);
+            }
+        }
+    }
+    reversed
+}
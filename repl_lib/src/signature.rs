@@ -0,0 +1,52 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use term_manager::clear;
+
+use crate::{Repl, Result, StyledText};
+
+/// Function type for hinting the signature of the call the cursor is in, e.g. `max(a, b)`
+/// with the current parameter in bold.
+///
+/// Called with the input and the byte offset of the cursor each time the input is drawn.
+/// The first line of the returned text is shown below the input; `None` shows nothing.
+pub type SignatureFunc = Box<dyn FnMut(&str, usize) -> Option<StyledText>>;
+
+impl<T> Repl<T> {
+    /// Update the signature hint for the input and cursor position, returning it to draw.
+    pub(crate) fn update_signature(&mut self) -> Option<StyledText> {
+        let line = &self.lines[self.current_line];
+        let hint = self
+            .signature_help
+            .as_mut()
+            .and_then(|signature_help| signature_help(&line.text, line.cursor_pos));
+        let first = hint.map(|hint| first_line(&hint));
+        self.signature_shown = first.is_some();
+        first
+    }
+
+    /// Erase the signature hint after moving below the input, so it isn't left between the
+    /// input and its output.
+    pub(crate) fn clear_signature(&mut self) -> Result<()> {
+        if !self.signature_shown {
+            return Ok(());
+        }
+        self.signature_shown = false;
+        let out = format!("{}{}", clear::TO_END_OF_SCREEN, self.status_sequence());
+        self.write(&out)
+    }
+}
+
+/// Returns the first line of `text`, keeping its styles.
+fn first_line(text: &StyledText) -> StyledText {
+    let mut first = StyledText::new();
+    for span in text.spans() {
+        if let Some((part, _)) = span.text.split_once('\n') {
+            first.push(part, span.style);
+            break;
+        }
+        first.push(span.text.as_str(), span.style);
+    }
+    first
+}
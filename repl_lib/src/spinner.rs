@@ -0,0 +1,90 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    io::{self, Write},
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use term_manager::{Attribute, ColorDepth, clear};
+
+use crate::{Style, StyledText};
+
+/// How long evaluation runs before the spinner appears, so quick results don't flicker.
+const SPINNER_DELAY: Duration = Duration::from_millis(300);
+
+/// Time between spinner frames.
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Spinner and elapsed time drawn on the current row by a background thread while a line
+/// is evaluated.
+pub(crate) struct Spinner {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    /// Start drawing the spinner once evaluation has run for `SPINNER_DELAY`.
+    ///
+    /// Pending output must be flushed first, since the thread writes to stdout directly.
+    pub(crate) fn start(color_depth: ColorDepth) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            let start = Instant::now();
+            let mut wait = SPINNER_DELAY;
+            let mut frame = 0;
+            loop {
+                match stopped.recv_timeout(wait) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ if frame == 0 => return,
+                    _ => {
+                        let _ = write_stdout(&format!("\r{}", clear::LINE));
+                        return;
+                    }
+                }
+                let text = format!(
+                    "{} {:.1}s",
+                    FRAMES[frame % FRAMES.len()],
+                    start.elapsed().as_secs_f64()
+                );
+                let styled = StyledText::new().span(text, Style::new().attr(Attribute::Dim));
+                let _ = write_stdout(&format!(
+                    "\r{}{}",
+                    styled.render(color_depth),
+                    clear::TO_END_OF_LINE
+                ));
+                frame += 1;
+                wait = SPINNER_INTERVAL;
+            }
+        });
+        Self {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+
+    /// Stop the thread and erase the spinner, so the row is free for output.
+    pub(crate) fn stop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn write_stdout(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(text.as_bytes())?;
+    stdout.flush()
+}
@@ -0,0 +1,134 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use term_manager::{clear, cursor, screen};
+
+use crate::{Error, ErrorContext, InternalError, Repl, Result, StyledText};
+
+/// Where the status line is pinned.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StatusPosition {
+    /// The top row of the terminal.
+    Top,
+    /// The bottom row of the terminal.
+    #[default]
+    Bottom,
+}
+
+impl<T> Repl<T> {
+    /// Show `text` on a status line pinned to the top or bottom row of the terminal, e.g.
+    /// for the current mode or connection state.
+    ///
+    /// The rest of the screen scrolls beneath it, so the line stays in place through output
+    /// and prompt redraws until changed or cleared. Text wider than the terminal is cut off.
+    /// In screen reader mode the text is printed on its own line when it changes instead.
+    /// Does nothing in batch mode.
+    pub fn set_status(&mut self, text: impl Into<StyledText>) -> Result<()> {
+        if self.screen_reader && self.dumb {
+            let text = text.into();
+            if self.status.as_ref() != Some(&text) {
+                self.write(&format!("{}\n", text.text()))?;
+                self.status = Some(text);
+            }
+            return self.flush();
+        }
+        if self.tmanager.is_none() {
+            return Ok(());
+        }
+        if self.status.is_none() {
+            self.make_status_room()?;
+        }
+        self.status = Some(text.into());
+        let sequence = self.status_sequence();
+        self.write(&sequence)?;
+        self.flush()
+    }
+
+    /// Remove the status line, letting output scroll over the whole screen again.
+    pub fn clear_status(&mut self) -> Result<()> {
+        if self.status.take().is_none() || self.dumb {
+            return Ok(());
+        }
+        let Some(row) = self.status_row() else {
+            return Ok(());
+        };
+        let sequence = format!(
+            "{}{}{}{}{}{}{}",
+            cursor::SAVE,
+            screen::RESET_SCROLL_REGION,
+            cursor::RESTORE,
+            cursor::SAVE,
+            cursor::move_to(row, 0),
+            clear::LINE,
+            cursor::RESTORE
+        );
+        self.write(&sequence)?;
+        self.flush()
+    }
+
+    /// Returns the escape sequences that draw the status line and keep output from scrolling
+    /// over it, leaving the cursor where it was. Empty if there's no status line.
+    pub(crate) fn status_sequence(&self) -> String {
+        let (Some(text), Some(row)) = (self.status.as_ref(), self.status_row()) else {
+            return String::new();
+        };
+        let Some(Ok((rows, cols))) = self.tmanager.as_ref().map(|t| t.size()) else {
+            return String::new();
+        };
+        let (top, bottom) = match self.status_position {
+            StatusPosition::Top => (1, rows - 1),
+            StatusPosition::Bottom => (0, rows - 2),
+        };
+        // Setting the scroll region moves the cursor to the top left.
+        format!(
+            "{}{}{}{}{}{}{}{}",
+            cursor::SAVE,
+            screen::scroll_region(top, bottom),
+            cursor::RESTORE,
+            cursor::SAVE,
+            cursor::move_to(row, 0),
+            clear::LINE,
+            text.truncate(cols as usize).render(self.color_depth),
+            cursor::RESTORE
+        )
+    }
+
+    /// Returns the screen row of the status line, or `None` if the terminal is too small.
+    fn status_row(&self) -> Option<u16> {
+        let (rows, _) = self.tmanager.as_ref()?.size().ok()?;
+        if rows < 2 {
+            return None;
+        }
+        match self.status_position {
+            StatusPosition::Top => Some(0),
+            StatusPosition::Bottom => Some(rows - 1),
+        }
+    }
+
+    /// Move the cursor off the row the status line is about to cover.
+    fn make_status_room(&mut self) -> Result<()> {
+        let sequence = match self.status_position {
+            // Scrolls the screen up a line if the cursor is on the bottom row.
+            StatusPosition::Bottom => format!("\n{}", cursor::up(1)),
+            StatusPosition::Top => {
+                let row = self
+                    .tmanager_mut()?
+                    .cursor_position()
+                    .map_err(|e| {
+                        Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+                            "unable to read cursor position",
+                            e,
+                        )))
+                    })?
+                    .0;
+                if row == 0 {
+                    "\n".to_string()
+                } else {
+                    String::new()
+                }
+            }
+        };
+        self.write(&sequence)
+    }
+}
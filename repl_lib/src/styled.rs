@@ -0,0 +1,269 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use term_manager::{Attribute, Color, ColorDepth, RESET_STYLE, screen};
+
+/// Colors and attributes applied to a span of text.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    /// Create a style that leaves text unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the foreground color.
+    pub fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Set the background color.
+    pub fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// Enable a text attribute.
+    pub fn attr(mut self, attr: Attribute) -> Self {
+        match attr {
+            Attribute::Bold => self.bold = true,
+            Attribute::Dim => self.dim = true,
+            Attribute::Italic => self.italic = true,
+            Attribute::Underline => self.underline = true,
+            Attribute::Reverse => self.reverse = true,
+        }
+        self
+    }
+
+    /// Returns `true` if the style changes nothing.
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the SGR sequences applying this style, with colors downgraded to `depth`.
+    ///
+    /// Returns an empty string for `ColorDepth::NoColor`.
+    pub fn sequence(&self, depth: ColorDepth) -> String {
+        let mut sequence = String::new();
+        if depth == ColorDepth::NoColor {
+            return sequence;
+        }
+        let attrs = [
+            (self.bold, Attribute::Bold),
+            (self.dim, Attribute::Dim),
+            (self.italic, Attribute::Italic),
+            (self.underline, Attribute::Underline),
+            (self.reverse, Attribute::Reverse),
+        ];
+        for (_, attr) in attrs.iter().filter(|(enabled, _)| *enabled) {
+            sequence.push_str(attr.sequence());
+        }
+        if let Some(fg) = self.fg {
+            sequence.push_str(&fg.downgrade(depth).fg_sequence());
+        }
+        if let Some(bg) = self.bg {
+            sequence.push_str(&bg.downgrade(depth).bg_sequence());
+        }
+        sequence
+    }
+}
+
+/// A run of text drawn in a single style.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+    /// URL the text links to, shown by `StyledText::render_hyperlinks`.
+    pub link: Option<String>,
+}
+
+impl Span {
+    /// Create a span of `text` in `style`.
+    pub fn new(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+            link: None,
+        }
+    }
+
+    /// Make the span a link to `url`.
+    pub fn with_link(mut self, url: impl Into<String>) -> Self {
+        self.link = Some(url.into());
+        self
+    }
+
+    /// Returns the number of terminal columns the span occupies.
+    pub fn width(&self) -> usize {
+        text_width(&self.text)
+    }
+}
+
+/// Text made of differently styled spans, such as a colored prompt.
+///
+/// Plain strings convert into unstyled text, so anything taking `impl Into<StyledText>`
+/// also accepts `&str` and `String`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StyledText {
+    spans: Vec<Span>,
+}
+
+impl StyledText {
+    /// Create empty styled text.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `text` in `style`.
+    pub fn span(mut self, text: impl Into<String>, style: Style) -> Self {
+        self.push(text, style);
+        self
+    }
+
+    /// Append `text` without styling.
+    pub fn plain(self, text: impl Into<String>) -> Self {
+        self.span(text, Style::new())
+    }
+
+    /// Append `text` in `style`, linking to `url`.
+    pub fn link(mut self, text: impl Into<String>, url: impl Into<String>, style: Style) -> Self {
+        self.spans.push(Span::new(text, style).with_link(url));
+        self
+    }
+
+    /// Append `text` in `style` in place.
+    pub fn push(&mut self, text: impl Into<String>, style: Style) {
+        self.spans.push(Span::new(text, style));
+    }
+
+    /// Returns the spans, in order.
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// Returns the text without styling.
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    /// Returns the number of terminal columns the text occupies.
+    pub fn width(&self) -> usize {
+        self.spans.iter().map(Span::width).sum()
+    }
+
+    /// Returns the text cut to at most `width` columns, keeping each span's style.
+    pub fn truncate(&self, width: usize) -> StyledText {
+        let mut truncated = StyledText::new();
+        let mut remaining = width;
+        for span in &self.spans {
+            let mut text = String::new();
+            for c in span.text.chars() {
+                let w = char_width(c);
+                if w > remaining {
+                    remaining = 0;
+                    break;
+                }
+                remaining -= w;
+                text.push(c);
+            }
+            truncated.spans.push(Span {
+                text,
+                style: span.style,
+                link: span.link.clone(),
+            });
+            if remaining == 0 {
+                break;
+            }
+        }
+        truncated
+    }
+
+    /// Returns the text with SGR sequences for each styled span, colors downgraded to `depth`.
+    /// Links are shown as their text.
+    pub fn render(&self, depth: ColorDepth) -> String {
+        self.render_spans(depth, false)
+    }
+
+    /// Returns the text rendered like `render`, with linked spans as clickable hyperlinks,
+    /// for terminals with `TermCaps::hyperlinks`.
+    pub fn render_hyperlinks(&self, depth: ColorDepth) -> String {
+        self.render_spans(depth, true)
+    }
+
+    fn render_spans(&self, depth: ColorDepth, hyperlinks: bool) -> String {
+        let mut rendered = String::new();
+        for span in &self.spans {
+            let sequence = span.style.sequence(depth);
+            let text = match span.link.as_deref() {
+                Some(url) if hyperlinks => screen::hyperlink(url, &span.text),
+                _ => span.text.clone(),
+            };
+            if sequence.is_empty() {
+                rendered.push_str(&text);
+            } else {
+                rendered.push_str(&sequence);
+                rendered.push_str(&text);
+                rendered.push_str(RESET_STYLE);
+            }
+        }
+        rendered
+    }
+}
+
+impl From<&str> for StyledText {
+    fn from(text: &str) -> Self {
+        Self::new().plain(text)
+    }
+}
+
+impl From<String> for StyledText {
+    fn from(text: String) -> Self {
+        Self::new().plain(text)
+    }
+}
+
+impl From<Span> for StyledText {
+    fn from(span: Span) -> Self {
+        Self { spans: vec![span] }
+    }
+}
+
+/// Returns the number of terminal columns `text` occupies, ignoring tab stops.
+pub fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Returns the number of terminal columns a character occupies: 0 for control and combining
+/// characters, 2 for wide East Asian characters and emoji, and 1 otherwise.
+pub fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x00..=0x1F | 0x7F..=0x9F => 0,
+        0x0300..=0x036F | 0x200B..=0x200F | 0x20D0..=0x20FF | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
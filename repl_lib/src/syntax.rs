@@ -0,0 +1,65 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{FontStyle, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+use term_manager::{Attribute, Color};
+
+use crate::{HighlightFunc, ReplBuilder, Style, StyledText};
+
+/// Bundled theme whose colors are used for syntax highlighting.
+const THEME: &str = "base16-ocean.dark";
+
+/// Returns a highlighter using syntect's bundled definition for `language`, looked up by
+/// name or file extension (e.g. `sql`, `json`, `sh` or `python`), or `None` if there isn't
+/// one.
+///
+/// Only foreground colors and font styles are used, so the terminal's background shows
+/// through. Colors are reduced to what the terminal supports when drawn.
+pub fn syntax_highlighter(language: &str) -> Option<HighlightFunc> {
+    let syntaxes = SyntaxSet::load_defaults_newlines();
+    let syntax = syntaxes.find_syntax_by_token(language)?.clone();
+    let theme = ThemeSet::load_defaults().themes.remove(THEME)?;
+    Some(Box::new(move |line, _cursor| {
+        let mut highlighter = HighlightLines::new(&syntax, &theme);
+        let mut styled = StyledText::new();
+        for text in LinesWithEndings::from(line) {
+            let Ok(regions) = highlighter.highlight_line(text, &syntaxes) else {
+                return StyledText::from(line);
+            };
+            for (region, part) in regions {
+                let fg = region.foreground;
+                let mut style = Style::new().fg(Color::Rgb(fg.r, fg.g, fg.b));
+                for (font, attr) in [
+                    (FontStyle::BOLD, Attribute::Bold),
+                    (FontStyle::ITALIC, Attribute::Italic),
+                    (FontStyle::UNDERLINE, Attribute::Underline),
+                ] {
+                    if region.font_style.contains(font) {
+                        style = style.attr(attr);
+                    }
+                }
+                styled.push(part, style);
+            }
+        }
+        styled
+    }))
+}
+
+impl<T> ReplBuilder<T> {
+    /// Highlight the input as `language` with `syntax_highlighter`. The input is left plain
+    /// if there's no definition for it.
+    ///
+    /// Replaces any function set with `highlighter`.
+    pub fn syntax_highlighting(self, language: &str) -> Self {
+        match syntax_highlighter(language) {
+            Some(highlighter) => self.highlighter(highlighter),
+            None => self,
+        }
+    }
+}
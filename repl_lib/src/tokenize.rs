@@ -0,0 +1,71 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+/// A word of input split shell-style by `tokenize`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Token {
+    /// The word with quotes and backslash escapes removed.
+    pub text: String,
+    /// Byte offset where the word starts in the input, including any opening quote.
+    pub start: usize,
+    /// Byte offset just past the end of the word in the input.
+    pub end: usize,
+}
+
+/// Split `line` into words shell-style.
+///
+/// Words are separated by unquoted whitespace. Single quotes keep their contents literally,
+/// double quotes allow backslash escapes, and outside quotes a backslash escapes the next
+/// character. An unterminated quote runs to the end of the line.
+pub fn tokenize(line: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current: Option<Token> = None;
+    let mut quote = None;
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c.is_whitespace() && quote.is_none() {
+            tokens.extend(current.take());
+            continue;
+        }
+        let token = current.get_or_insert_with(|| Token {
+            start: i,
+            ..Token::default()
+        });
+        match c {
+            '\\' if quote != Some('\'') => {
+                if let Some((_, escaped)) = chars.next() {
+                    token.text.push(escaped);
+                }
+            }
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            c if quote == Some(c) => quote = None,
+            c => token.text.push(c),
+        }
+        token.end = chars.peek().map_or(line.len(), |&(i, _)| i);
+    }
+    tokens.extend(current);
+    tokens
+}
+
+/// Returns the index of the word the cursor is in or just after, and the word.
+///
+/// If the cursor isn't touching a word, an empty token at the cursor is returned with the
+/// index a word typed there would get.
+pub fn token_at(line: &str, cursor: usize) -> (usize, Token) {
+    let tokens = tokenize(line);
+    match tokens
+        .iter()
+        .position(|token| token.start <= cursor && cursor <= token.end)
+    {
+        Some(index) => (index, tokens[index].clone()),
+        None => (
+            tokens.iter().filter(|token| token.end < cursor).count(),
+            Token {
+                start: cursor,
+                end: cursor,
+                ..Token::default()
+            },
+        ),
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Error, ErrorContext, InternalError, Repl, Result};
+
+/// Timestamped record of an interactive session.
+///
+/// Each line of input is written after the prompt it was typed at, and each line of output
+/// as printed. Every line starts with the UTC time it was recorded.
+pub(crate) struct Transcript {
+    file: File,
+}
+
+impl Transcript {
+    /// Open the transcript at `path`, appending to it if it exists.
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                Error::Internal(InternalError::InitFail(ErrorContext::with_source(
+                    format!("unable to open transcript {}", path.display()),
+                    e,
+                )))
+            })?;
+        Ok(Self { file })
+    }
+
+    /// Write each line of `text`, the first after `prefix` and the rest after `continuation`.
+    ///
+    /// Write errors are ignored so a full disk doesn't end the session.
+    fn write(&mut self, prefix: &str, continuation: &str, text: &str) {
+        let time = timestamp();
+        let mut record = String::new();
+        for (i, line) in text.split('\n').enumerate() {
+            let prefix = if i == 0 { prefix } else { continuation };
+            record.push_str(&format!("{} {}{}\n", time, prefix, line));
+        }
+        let _ = self.file.write_all(record.as_bytes());
+    }
+}
+
+impl<T> Repl<T> {
+    /// Record a line of input, with the prompts it was typed after, in the transcript, the
+    /// recorded session and the scrollback.
+    pub(crate) fn record_input(&mut self, line: &str) {
+        self.session_input(line);
+        self.scrollback_input(line);
+        if let Some(transcript) = self.transcript.as_mut() {
+            let prompt = self.prompt.text();
+            let continuation_prompt = self.continuation_prompt.text();
+            transcript.write(&prompt, &continuation_prompt, line);
+        }
+    }
+
+    /// Record output printed by the REPL in the transcript, the recorded session and the
+    /// scrollback.
+    pub(crate) fn record_output(&mut self, output: &str) {
+        self.session_output(output);
+        self.scrollback_output(output);
+        if let Some(transcript) = self.transcript.as_mut() {
+            transcript.write("", "", output);
+        }
+    }
+}
+
+/// Returns the current UTC time as `YYYY-MM-DDTHH:MM:SSZ`.
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
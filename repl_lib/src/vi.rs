@@ -0,0 +1,713 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::ops::Range;
+
+use term_manager::cursor;
+
+use crate::{Repl, ReplState, Result, exceeds};
+
+/// How keys edit the input.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum EditMode {
+    /// Emacs-style bindings, as in readline's default mode.
+    #[default]
+    Emacs,
+    /// Vi-style modal editing. Each input starts in insert mode, where keys have their
+    /// Emacs-style bindings; Esc switches to command mode.
+    ///
+    /// Command mode has the motions `h l 0 ^ $ w b e W B E f F t T`, the operators `d c y`
+    /// (doubled for the whole row), `x X D C s S i a I A p P r ~`, `k` and `j` for history,
+    /// and counts. Deleted and copied text goes to the kill ring.
+    Vi,
+}
+
+/// State of vi mode kept between keys.
+#[derive(Default)]
+pub(crate) struct ViState {
+    /// In command mode rather than insert mode.
+    pub(crate) command: bool,
+    /// Keys of a command typed so far, e.g. `2d` waiting for its motion.
+    pending: String,
+}
+
+/// Where a command-mode motion moves the cursor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Motion {
+    /// `h`
+    Left,
+    /// `l` or space
+    Right,
+    /// `0`
+    RowStart,
+    /// `^`
+    FirstNonBlank,
+    /// `$`
+    RowEnd,
+    /// `w`, or `W` for whitespace-separated words
+    WordForward { big: bool },
+    /// `b` or `B`
+    WordBack { big: bool },
+    /// `e` or `E`
+    WordEnd { big: bool },
+    /// `f`, `F`, `t` or `T` followed by a character
+    Find { c: char, backward: bool, till: bool },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+/// A complete command-mode command.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Command {
+    Move(Motion),
+    /// An operator over the text between the cursor and where a motion moves it.
+    Operate(Operator, Motion),
+    /// An operator doubled, e.g. `dd`, over whole rows.
+    OperateRows(Operator),
+    /// `i`, `a`, `I` or `A`: enter insert mode after moving the cursor.
+    Insert(Option<Motion>),
+    /// `p`, or `P` before the cursor
+    Put {
+        before: bool,
+    },
+    /// `r` followed by a character
+    Replace(char),
+    /// `~`
+    ToggleCase,
+    /// `k`, or `j` for newer
+    History {
+        older: bool,
+    },
+}
+
+/// Result of parsing the keys typed in command mode so far.
+#[derive(Debug, PartialEq, Eq)]
+enum Parsed {
+    Incomplete,
+    Invalid,
+    Command(usize, Command),
+}
+
+impl<T> Repl<T> {
+    /// Handles `sequence` in vi mode where it differs from Emacs mode: Esc in insert mode,
+    /// and the keys of command mode. Returns `None` for keys with their usual binding.
+    pub(crate) fn handle_vi_key(&mut self, sequence: &[u8]) -> Result<Option<ReplState>> {
+        if !self.vi.command {
+            if matches!(sequence, [0x1B] | [0x1B, 0x1B]) {
+                self.enter_vi_command_mode()?;
+                return Ok(Some(ReplState::Continue));
+            }
+            return Ok(None);
+        }
+
+        let text = match std::str::from_utf8(sequence) {
+            Ok(text) if !text.starts_with(|c: char| c.is_control()) => text,
+            _ => {
+                // Enter, Ctrl keys and escape sequences such as arrows keep their binding.
+                self.vi.pending.clear();
+                if matches!(sequence, [b'\r' | b'\n' | 0x03]) {
+                    self.set_vi_command(false)?;
+                }
+                return Ok(match sequence {
+                    [0x1B] => Some(ReplState::Continue),
+                    _ => None,
+                });
+            }
+        };
+        self.vi.pending.push_str(text);
+        match parse(&self.vi.pending) {
+            Parsed::Incomplete => {}
+            Parsed::Invalid => {
+                self.vi.pending.clear();
+                self.bell()?;
+            }
+            Parsed::Command(count, command) => {
+                self.vi.pending.clear();
+                self.search_prefix = None;
+                self.kill_ring.last_yank = None;
+                self.numeric_arg = None;
+                self.run_vi_command(count, command)?;
+            }
+        }
+        Ok(Some(ReplState::Continue))
+    }
+
+    /// Switch to command mode, moving the cursor back onto the last character typed as vi
+    /// does.
+    fn enter_vi_command_mode(&mut self) -> Result<()> {
+        self.set_vi_command(true)?;
+        let line = self.current_line_mut()?;
+        if line.cursor_pos > row_start(&line.text, line.cursor_pos) {
+            line.cursor_pos = prev_char(&line.text, line.cursor_pos);
+        }
+        self.redraw_current_line()
+    }
+
+    /// Switch between command and insert mode, showing a block cursor in command mode.
+    pub(crate) fn set_vi_command(&mut self, command: bool) -> Result<()> {
+        if self.vi.command == command {
+            return Ok(());
+        }
+        self.vi.command = command;
+        self.vi.pending.clear();
+        if self.tmanager.is_some() && !self.dumb && !self.overwrite {
+            self.write(if command {
+                cursor::BLOCK
+            } else {
+                cursor::DEFAULT_SHAPE
+            })?;
+        }
+        Ok(())
+    }
+
+    fn run_vi_command(&mut self, count: usize, command: Command) -> Result<()> {
+        match command {
+            Command::Move(motion) => {
+                let line = self.current_line_mut()?;
+                match target(&line.text, line.cursor_pos, motion, count) {
+                    Some((pos, _)) => line.cursor_pos = pos,
+                    None => return self.bell(),
+                }
+            }
+            Command::Operate(operator, motion) => {
+                let line = self.current_line_mut()?;
+                let Some(range) =
+                    motion_range(&line.text, line.cursor_pos, operator, motion, count)
+                else {
+                    return self.bell();
+                };
+                self.operate(operator, range)?;
+            }
+            Command::OperateRows(operator) => {
+                let line = self.current_line_mut()?;
+                let range = rows_range(&line.text, line.cursor_pos, operator, count);
+                self.operate(operator, range)?;
+            }
+            Command::Insert(motion) => {
+                let line = self.current_line_mut()?;
+                if let Some(motion) = motion {
+                    line.cursor_pos = target(&line.text, line.cursor_pos, motion, 1)
+                        .map_or(line.cursor_pos, |(pos, _)| pos);
+                }
+                self.set_vi_command(false)?;
+            }
+            Command::Put { before } => {
+                let Some(killed) = self.kill_ring.yank().map(str::to_string) else {
+                    return self.bell();
+                };
+                let text = killed.repeat(count);
+                let max_length = self.max_length;
+                let line = self.current_line_mut()?;
+                if exceeds(max_length, &line.text, &text) {
+                    return self.bell();
+                }
+                if !before && !at_row_end(&line.text, line.cursor_pos) {
+                    line.cursor_pos = next_char(&line.text, line.cursor_pos);
+                }
+                line.insert_str(&text);
+                line.cursor_pos = prev_char(&line.text, line.cursor_pos);
+            }
+            Command::Replace(c) => {
+                let line = self.current_line_mut()?;
+                let start = line.cursor_pos;
+                let mut end = start;
+                for _ in 0..count {
+                    if at_row_end(&line.text, end) {
+                        return self.bell();
+                    }
+                    end = next_char(&line.text, end);
+                }
+                let replacement = c.to_string().repeat(count);
+                line.text.replace_range(start..end, &replacement);
+                line.cursor_pos = prev_char(&line.text, start + replacement.len());
+            }
+            Command::ToggleCase => {
+                let line = self.current_line_mut()?;
+                let start = line.cursor_pos;
+                let mut end = start;
+                for _ in 0..count {
+                    if at_row_end(&line.text, end) {
+                        break;
+                    }
+                    end = next_char(&line.text, end);
+                }
+                let toggled: String = line.text[start..end]
+                    .chars()
+                    .flat_map(|c| {
+                        let upper: Vec<char> = c.to_uppercase().collect();
+                        if upper == [c] {
+                            c.to_lowercase().collect()
+                        } else {
+                            upper
+                        }
+                    })
+                    .collect();
+                line.text.replace_range(start..end, &toggled);
+                line.cursor_pos = start + toggled.len();
+            }
+            Command::History { older } => {
+                let key = if older { b'A' } else { b'B' };
+                for _ in 0..count {
+                    self.handle_escape_sequence(key)?;
+                }
+            }
+        }
+        if self.vi.command {
+            let line = self.current_line_mut()?;
+            line.cursor_pos = command_cursor(&line.text, line.cursor_pos);
+        }
+        self.redraw_current_line()
+    }
+
+    /// Delete, change or copy the text in `range`, saving it to the kill ring.
+    fn operate(&mut self, operator: Operator, range: Range<usize>) -> Result<()> {
+        let line = self.current_line_mut()?;
+        let text = match operator {
+            Operator::Delete | Operator::Change => line.delete_range(range.clone()),
+            Operator::Yank => {
+                line.cursor_pos = range.start;
+                line.text[range].to_string()
+            }
+        };
+        self.kill(text)?;
+        if operator == Operator::Change {
+            self.set_vi_command(false)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse the keys typed in command mode: an optional count, then a motion, an operator and
+/// its motion (itself optionally counted), or another command.
+fn parse(keys: &str) -> Parsed {
+    let mut chars = keys.chars().peekable();
+    let count = parse_count(&mut chars);
+    let Some(c) = chars.next() else {
+        return Parsed::Incomplete;
+    };
+    let operator = match c {
+        'd' => Some(Operator::Delete),
+        'c' => Some(Operator::Change),
+        'y' => Some(Operator::Yank),
+        _ => None,
+    };
+    let command = if let Some(operator) = operator {
+        let motion_count = parse_count(&mut chars);
+        let Some(m) = chars.next() else {
+            return Parsed::Incomplete;
+        };
+        let count = count.unwrap_or(1) * motion_count.unwrap_or(1);
+        if m == c {
+            return complete(chars.next(), count, Command::OperateRows(operator));
+        }
+        return match parse_motion(m, &mut chars) {
+            Ok(Some(motion)) => complete(chars.next(), count, Command::Operate(operator, motion)),
+            Ok(None) => Parsed::Incomplete,
+            Err(()) => Parsed::Invalid,
+        };
+    } else {
+        match c {
+            'x' => Command::Operate(Operator::Delete, Motion::Right),
+            'X' => Command::Operate(Operator::Delete, Motion::Left),
+            'D' => Command::Operate(Operator::Delete, Motion::RowEnd),
+            'C' => Command::Operate(Operator::Change, Motion::RowEnd),
+            's' => Command::Operate(Operator::Change, Motion::Right),
+            'S' => Command::OperateRows(Operator::Change),
+            'i' => Command::Insert(None),
+            'a' => Command::Insert(Some(Motion::Right)),
+            'I' => Command::Insert(Some(Motion::FirstNonBlank)),
+            'A' => Command::Insert(Some(Motion::RowEnd)),
+            'p' => Command::Put { before: false },
+            'P' => Command::Put { before: true },
+            '~' => Command::ToggleCase,
+            'k' => Command::History { older: true },
+            'j' => Command::History { older: false },
+            'r' => match chars.next() {
+                Some(r) => Command::Replace(r),
+                None => return Parsed::Incomplete,
+            },
+            m => match parse_motion(m, &mut chars) {
+                Ok(Some(motion)) => Command::Move(motion),
+                Ok(None) => return Parsed::Incomplete,
+                Err(()) => return Parsed::Invalid,
+            },
+        }
+    };
+    complete(chars.next(), count.unwrap_or(1), command)
+}
+
+/// Returns `command` if nothing follows it.
+fn complete(rest: Option<char>, count: usize, command: Command) -> Parsed {
+    match rest {
+        Some(_) => Parsed::Invalid,
+        None => Parsed::Command(count, command),
+    }
+}
+
+/// Parse a count, which can't start with `0` as that moves to the start of the row.
+fn parse_count(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<usize> {
+    let mut count: Option<usize> = None;
+    while let Some(&c) = chars.peek() {
+        let Some(digit) = c.to_digit(10) else {
+            break;
+        };
+        if count.is_none() && digit == 0 {
+            break;
+        }
+        chars.next();
+        count = Some(
+            count
+                .unwrap_or(0)
+                .saturating_mul(10)
+                .saturating_add(digit as usize),
+        );
+    }
+    count
+}
+
+/// Parse the motion starting with `c`. Returns `Ok(None)` if it needs another key, and an
+/// error if `c` isn't a motion.
+fn parse_motion(
+    c: char,
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> std::result::Result<Option<Motion>, ()> {
+    let motion = match c {
+        'h' => Motion::Left,
+        'l' | ' ' => Motion::Right,
+        '0' => Motion::RowStart,
+        '^' => Motion::FirstNonBlank,
+        '$' => Motion::RowEnd,
+        'w' | 'W' => Motion::WordForward { big: c == 'W' },
+        'b' | 'B' => Motion::WordBack { big: c == 'B' },
+        'e' | 'E' => Motion::WordEnd { big: c == 'E' },
+        'f' | 'F' | 't' | 'T' => match chars.next() {
+            Some(target) => Motion::Find {
+                c: target,
+                backward: c.is_ascii_uppercase(),
+                till: c.eq_ignore_ascii_case(&'t'),
+            },
+            None => return Ok(None),
+        },
+        _ => return Err(()),
+    };
+    Ok(Some(motion))
+}
+
+/// Returns where `motion` repeated `count` times moves the cursor from `pos`, and whether
+/// the character there is included when an operator applies to it. Returns `None` if the
+/// motion can't be made, e.g. the character of an `f` isn't found.
+fn target(text: &str, pos: usize, motion: Motion, count: usize) -> Option<(usize, bool)> {
+    let (start, end) = (row_start(text, pos), row_end(text, pos));
+    let repeat = |step: &dyn Fn(usize) -> usize| (0..count).fold(pos, |p, _| step(p));
+    let target = match motion {
+        Motion::Left => (
+            repeat(&|p| if p > start { prev_char(text, p) } else { p }),
+            false,
+        ),
+        Motion::Right => (
+            repeat(&|p| if p < end { next_char(text, p) } else { p }),
+            false,
+        ),
+        Motion::RowStart => (start, false),
+        Motion::FirstNonBlank => {
+            let blank = text[start..end].len() - text[start..end].trim_start().len();
+            (start + blank, false)
+        }
+        Motion::RowEnd => (end, false),
+        Motion::WordForward { big } => (repeat(&|p| word_forward(text, p, big)), false),
+        Motion::WordBack { big } => (repeat(&|p| word_back(text, p, big)), false),
+        Motion::WordEnd { big } => (repeat(&|p| word_end(text, p, big)), true),
+        Motion::Find { c, backward, till } => {
+            let found = if backward {
+                text[start..pos].rmatch_indices(c).nth(count - 1)?.0 + start
+            } else {
+                let after = next_char(text, pos).min(end);
+                text[after..end].match_indices(c).nth(count - 1)?.0 + after
+            };
+            // `t` and `T` stop next to the character.
+            let p = match (till, backward) {
+                (false, _) => found,
+                (true, false) => prev_char(text, found),
+                (true, true) => next_char(text, found),
+            };
+            (p, !backward)
+        }
+    };
+    Some(target)
+}
+
+/// Returns the text an operator applies to for `motion` from `pos`, within the row.
+fn motion_range(
+    text: &str,
+    pos: usize,
+    operator: Operator,
+    motion: Motion,
+    count: usize,
+) -> Option<Range<usize>> {
+    // `cw` changes to the end of the word the cursor is on, leaving the space after it.
+    let (target, inclusive) = match motion {
+        Motion::WordForward { big }
+            if operator == Operator::Change
+                && text[pos..].starts_with(|c: char| !c.is_whitespace()) =>
+        {
+            let end = (1..count).fold(current_word_end(text, pos, big), |p, _| {
+                word_end(text, p, big)
+            });
+            (end, true)
+        }
+        motion => target(text, pos, motion, count)?,
+    };
+    let range = if target >= pos {
+        let end = if inclusive && target < text.len() {
+            next_char(text, target)
+        } else {
+            target
+        };
+        pos..end.min(row_end(text, pos)).max(pos)
+    } else {
+        target..pos
+    };
+    (!range.is_empty()).then_some(range)
+}
+
+/// Returns the text of `count` rows from the one at `pos`, for `dd`, `cc` and `yy`. Rows
+/// deleted from multi-line input take a newline with them.
+fn rows_range(text: &str, pos: usize, operator: Operator, count: usize) -> Range<usize> {
+    let start = row_start(text, pos);
+    let mut end = row_end(text, pos);
+    for _ in 1..count {
+        if end == text.len() {
+            break;
+        }
+        end = row_end(text, end + 1);
+    }
+    if operator == Operator::Change {
+        start..end
+    } else if end < text.len() {
+        start..end + 1
+    } else if start > 0 && operator == Operator::Delete {
+        start - 1..end
+    } else {
+        start..end
+    }
+}
+
+/// Returns `pos` moved back onto the last character of its row if it's past it, as the
+/// cursor in command mode is always on a character.
+fn command_cursor(text: &str, pos: usize) -> usize {
+    if pos > row_start(text, pos) && at_row_end(text, pos) {
+        prev_char(text, pos)
+    } else {
+        pos
+    }
+}
+
+/// Returns the start of the next word after `pos`, or the end of the text.
+fn word_forward(text: &str, pos: usize, big: bool) -> usize {
+    let mut chars = text[pos..]
+        .char_indices()
+        .map(|(i, c)| (pos + i, c))
+        .peekable();
+    if let Some(&(_, first)) = chars.peek() {
+        let class = char_class(first, big);
+        if class != CharClass::Blank {
+            while chars
+                .next_if(|&(_, c)| char_class(c, big) == class)
+                .is_some()
+            {}
+        }
+    }
+    while chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+    chars.next().map_or(text.len(), |(i, _)| i)
+}
+
+/// Returns the start of the word before `pos`, or of the one `pos` is in.
+fn word_back(text: &str, pos: usize, big: bool) -> usize {
+    let mut chars = text[..pos].char_indices().rev().peekable();
+    while chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+    let Some((mut start, first)) = chars.next() else {
+        return 0;
+    };
+    let class = char_class(first, big);
+    while let Some((i, _)) = chars.next_if(|&(_, c)| char_class(c, big) == class) {
+        start = i;
+    }
+    start
+}
+
+/// Returns the last character of the word `pos` is on.
+fn current_word_end(text: &str, pos: usize, big: bool) -> usize {
+    let mut chars = text[pos..]
+        .char_indices()
+        .map(|(i, c)| (pos + i, c))
+        .peekable();
+    let Some((mut end, first)) = chars.next() else {
+        return pos;
+    };
+    let class = char_class(first, big);
+    while let Some((i, _)) = chars.next_if(|&(_, c)| char_class(c, big) == class) {
+        end = i;
+    }
+    end
+}
+
+/// Returns the last character of the word after `pos`, or of the one `pos` is in.
+fn word_end(text: &str, pos: usize, big: bool) -> usize {
+    let next_word = text[pos..]
+        .char_indices()
+        .skip(1)
+        .find(|&(_, c)| !c.is_whitespace());
+    next_word.map_or(pos, |(i, _)| current_word_end(text, pos + i, big))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Word,
+    Punctuation,
+}
+
+/// Returns the class of `c` for word motions. Big words are anything but whitespace.
+fn char_class(c: char, big: bool) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Blank
+    } else if big || c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+/// Returns the start of the row of multi-line input `pos` is on.
+fn row_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Returns the end of the row `pos` is on, before its newline.
+fn row_end(text: &str, pos: usize) -> usize {
+    text[pos..].find('\n').map_or(text.len(), |i| pos + i)
+}
+
+fn at_row_end(text: &str, pos: usize) -> bool {
+    pos == row_end(text, pos)
+}
+
+fn next_char(text: &str, pos: usize) -> usize {
+    text[pos..]
+        .chars()
+        .next()
+        .map_or(pos, |c| pos + c.len_utf8())
+}
+
+fn prev_char(text: &str, pos: usize) -> usize {
+    text[..pos]
+        .chars()
+        .next_back()
+        .map_or(pos, |c| pos - c.len_utf8())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReplBuilder;
+
+    #[test]
+    fn parses_counts_operators_and_motions() {
+        assert_eq!(
+            parse("w"),
+            Parsed::Command(1, Command::Move(Motion::WordForward { big: false }))
+        );
+        assert_eq!(
+            parse("0"),
+            Parsed::Command(1, Command::Move(Motion::RowStart))
+        );
+        assert_eq!(
+            parse("10l"),
+            Parsed::Command(10, Command::Move(Motion::Right))
+        );
+        assert_eq!(
+            parse("2d3w"),
+            Parsed::Command(
+                6,
+                Command::Operate(Operator::Delete, Motion::WordForward { big: false })
+            )
+        );
+        assert_eq!(
+            parse("cc"),
+            Parsed::Command(1, Command::OperateRows(Operator::Change))
+        );
+        assert_eq!(parse("d"), Parsed::Incomplete);
+        assert_eq!(parse("2f"), Parsed::Incomplete);
+        assert_eq!(parse("r"), Parsed::Incomplete);
+        assert_eq!(parse("dq"), Parsed::Invalid);
+        assert_eq!(parse("q"), Parsed::Invalid);
+    }
+
+    #[test]
+    fn word_motions() {
+        let text = "foo.bar  baz";
+        assert_eq!(word_forward(text, 0, false), 3);
+        assert_eq!(word_forward(text, 0, true), 9);
+        assert_eq!(word_back(text, 9, false), 4);
+        assert_eq!(word_back(text, 9, true), 0);
+        assert_eq!(word_end(text, 0, false), 2);
+        assert_eq!(word_end(text, 2, false), 3);
+        assert_eq!(word_end(text, 0, true), 6);
+    }
+
+    #[test]
+    fn find_motions_stay_in_the_row() {
+        let text = "a(b, c)\nd)";
+        let find = |backward, till| Motion::Find {
+            c: ')',
+            backward,
+            till,
+        };
+        assert_eq!(target(text, 0, find(false, false), 1), Some((6, true)));
+        assert_eq!(target(text, 0, find(false, true), 1), Some((5, true)));
+        assert_eq!(target(text, 0, find(false, false), 2), None);
+        let open = Motion::Find {
+            c: '(',
+            backward: true,
+            till: true,
+        };
+        assert_eq!(target(text, 6, open, 1), Some((2, false)));
+    }
+
+    #[test]
+    fn operator_ranges() {
+        let text = "foo bar baz";
+        let word = Motion::WordForward { big: false };
+        assert_eq!(motion_range(text, 0, Operator::Delete, word, 1), Some(0..4));
+        // `cw` leaves the space after the word.
+        assert_eq!(motion_range(text, 0, Operator::Change, word, 1), Some(0..3));
+        assert_eq!(motion_range(text, 0, Operator::Change, word, 2), Some(0..7));
+        assert_eq!(
+            motion_range(text, 4, Operator::Delete, Motion::RowEnd, 1),
+            Some(4..11)
+        );
+        assert_eq!(
+            motion_range(text, 0, Operator::Delete, Motion::Left, 1),
+            None
+        );
+        assert_eq!(rows_range("a\nb\nc", 2, Operator::Delete, 1), 2..4);
+        assert_eq!(rows_range("a\nb\nc", 4, Operator::Delete, 1), 3..5);
+        assert_eq!(rows_range("a\nb\nc", 2, Operator::Change, 2), 2..5);
+    }
+
+    #[test]
+    fn kitty_esc_enters_command_mode() {
+        let mut repl = ReplBuilder::new()
+            .edit_mode(EditMode::Vi)
+            .build_detached()
+            .unwrap();
+        repl.feed_bytes(b"abc\x1b[27u0x\r");
+        assert_eq!(repl.process_input().unwrap(), "bc");
+    }
+}
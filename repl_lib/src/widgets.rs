@@ -0,0 +1,213 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::io::{self, BufRead, Write};
+
+use term_manager::{clear, cursor};
+
+use crate::{Error, ErrorContext, InternalError, KeyCode, Line, Repl, Result, UserError};
+
+/// Maximum number of items `select` shows at once; longer lists scroll.
+const SELECT_MAX_ROWS: usize = 10;
+
+impl<T> Repl<T> {
+    /// Ask a yes/no question, returning `default` when Enter is pressed.
+    pub fn confirm(&mut self, question: &str, default: bool) -> Result<bool> {
+        let hint = if default { "[Y/n]" } else { "[y/N]" };
+        self.write(&format!("{} {} ", question, hint))?;
+
+        if !self.is_interactive() {
+            let answer = read_batch_line()?;
+            return Ok(match answer.trim().to_lowercase().as_str() {
+                "y" | "yes" => true,
+                "n" | "no" => false,
+                _ => default,
+            });
+        }
+
+        self.flush()?;
+        let answer = loop {
+            let key = self.read_key()?;
+            match key.code {
+                KeyCode::Char('y' | 'Y') => break true,
+                KeyCode::Char('n' | 'N') | KeyCode::Esc => break false,
+                KeyCode::Enter => break default,
+                _ => {}
+            }
+        };
+        self.write(&format!("{}\n", if answer { "yes" } else { "no" }))?;
+        Ok(answer)
+    }
+
+    /// Let the user pick one of `items` with the arrow keys (or `j`/`k`) and Enter.
+    ///
+    /// Returns the index of the chosen item, or `None` if cancelled with Escape or `q`.
+    pub fn select(&mut self, title: &str, items: &[&str]) -> Result<Option<usize>> {
+        if items.is_empty() {
+            return Ok(None);
+        }
+
+        if !self.is_interactive() {
+            self.write(&format!("{}\n", title))?;
+            for (i, item) in items.iter().enumerate() {
+                self.write(&format!("{:>3}) {}\n", i + 1, item))?;
+            }
+            let answer = read_batch_line()?;
+            return Ok(answer
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .filter(|&i| i < items.len()));
+        }
+
+        let rows = items.len().min(SELECT_MAX_ROWS);
+        let mut selected = 0;
+        let mut offset = 0;
+        self.write(&format!("{}\n", title))?;
+
+        let choice = loop {
+            if selected < offset {
+                offset = selected;
+            } else if selected >= offset + rows {
+                offset = selected + 1 - rows;
+            }
+            for (i, item) in items.iter().enumerate().skip(offset).take(rows) {
+                let marker = if i == selected { ">" } else { " " };
+                self.write(&format!(
+                    "\r{} {}{}\r\n",
+                    marker,
+                    item,
+                    clear::TO_END_OF_LINE
+                ))?;
+            }
+            self.flush()?;
+
+            let key = self.read_key()?;
+            let choice = match key.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    selected = selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    selected = (selected + 1).min(items.len() - 1);
+                    None
+                }
+                KeyCode::Home => {
+                    selected = 0;
+                    None
+                }
+                KeyCode::End => {
+                    selected = items.len() - 1;
+                    None
+                }
+                KeyCode::Enter => Some(Some(selected)),
+                KeyCode::Esc | KeyCode::Char('q') => Some(None),
+                _ => None,
+            };
+
+            // Move back to the first row so the list is redrawn (or cleared) in place.
+            self.write(&cursor::up(rows))?;
+            if let Some(choice) = choice {
+                break choice;
+            }
+        };
+
+        self.write(&format!(
+            "\r{}{}\r{}: ",
+            clear::TO_END_OF_SCREEN,
+            cursor::up(1),
+            title
+        ))?;
+        match choice {
+            Some(i) => self.write(&format!("{}{}\n", items[i], clear::TO_END_OF_LINE))?,
+            None => self.write(&format!("{}\n", clear::TO_END_OF_LINE))?,
+        }
+        self.flush()?;
+        Ok(choice)
+    }
+
+    /// Read a single value, re-prompting until `validate` accepts it.
+    ///
+    /// `validate` returns an error message for invalid input, which is shown below the
+    /// prompt while the input is kept for correction.
+    pub fn input<F>(&mut self, prompt: &str, mut validate: F) -> Result<String>
+    where
+        F: FnMut(&str) -> std::result::Result<(), String>,
+    {
+        if !self.is_interactive() {
+            self.write(prompt)?;
+            let value = read_batch_line()?;
+            return validate(&value)
+                .map(|_| value)
+                .map_err(|error| Error::User(UserError::new(error)));
+        }
+
+        let mut line = Line::new();
+        let mut error: Option<String> = None;
+        loop {
+            self.write(&format!(
+                "\r{}{}{}",
+                prompt,
+                line.text(),
+                clear::TO_END_OF_LINE
+            ))?;
+            if let Some(message) = &error {
+                self.write(&format!(
+                    "\r\n{}{}{}\r{}{}",
+                    message,
+                    clear::TO_END_OF_LINE,
+                    cursor::up(1),
+                    prompt,
+                    line.text()
+                ))?;
+            }
+            let cursor_back = line.text()[line.cursor_pos..].chars().count();
+            self.write(&cursor::left(cursor_back))?;
+            self.flush()?;
+
+            let key = self.read_key()?;
+            if key.modifiers.ctrl || key.modifiers.alt {
+                continue;
+            }
+            match key.code {
+                KeyCode::Enter => match validate(line.text()) {
+                    Ok(()) => break,
+                    Err(message) => error = Some(message),
+                },
+                KeyCode::Backspace => line.backspace(),
+                KeyCode::Left => line.move_left(),
+                KeyCode::Right => line.move_right(),
+                KeyCode::Char(c) => line.insert_char(c),
+                _ => {}
+            }
+        }
+
+        self.write(&format!("\r\n{}\n", clear::TO_END_OF_LINE))?;
+        self.write(&cursor::up(1))?;
+        self.flush()?;
+        Ok(line.text().to_string())
+    }
+}
+
+/// Read one line from stdin without a terminal, without the trailing newline.
+fn read_batch_line() -> Result<String> {
+    io::stdout().flush().map_err(|e| {
+        Error::Internal(InternalError::IoFlush(ErrorContext::with_source(
+            "unable to flush stdout",
+            e,
+        )))
+    })?;
+    let mut buf = String::new();
+    let bytes_read = io::stdin().lock().read_line(&mut buf).map_err(|e| {
+        Error::Internal(InternalError::IoRead(ErrorContext::with_source(
+            "error reading from stdin",
+            e,
+        )))
+    })?;
+    if bytes_read == 0 {
+        return Err(Error::Internal(InternalError::Eof));
+    }
+    Ok(buf.trim_end_matches(['\n', '\r']).to_string())
+}
@@ -0,0 +1,43 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+/// Which characters make up words, for word movement, Ctrl-W, and completion.
+///
+/// By default words are letters, digits, and underscores.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WordChars {
+    extra: Vec<char>,
+    separators: Vec<char>,
+}
+
+impl WordChars {
+    /// Create the default set of letters, digits, and underscores.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count `chars` as part of words too, e.g. `-` for Lisp symbols or `:` to keep Rust
+    /// paths like `std::io` whole.
+    pub fn with(mut self, chars: &str) -> Self {
+        self.extra.extend(chars.chars());
+        self.separators.retain(|c| !chars.contains(*c));
+        self
+    }
+
+    /// Treat `chars` as separators, e.g. `_` to move through `snake_case` names a part at a
+    /// time.
+    pub fn without(mut self, chars: &str) -> Self {
+        self.separators.extend(chars.chars());
+        self.extra.retain(|c| !chars.contains(*c));
+        self
+    }
+
+    /// Returns `true` if `c` is part of a word.
+    pub fn contains(&self, c: char) -> bool {
+        if self.separators.contains(&c) {
+            return false;
+        }
+        c.is_alphanumeric() || c == '_' || self.extra.contains(&c)
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+    sync::Arc,
+};
+
+use crate::Error;
+
+/// Makes `TermManager::next_event` return `Event::Cancelled`, from any thread, e.g. to shut
+/// down a REPL waiting for input.
+#[derive(Clone, Debug)]
+pub struct Canceller {
+    write: Arc<OwnedFd>,
+}
+
+impl Canceller {
+    /// Cancel the wait for input in progress, or the next one if none is.
+    pub fn cancel(&self) {
+        let byte = 1u8;
+        // A full pipe already holds a cancellation, so a failed write loses nothing.
+        unsafe { libc::write(self.write.as_raw_fd(), (&byte as *const u8).cast(), 1) };
+    }
+}
+
+/// A pipe written by `Canceller`s and polled alongside the input.
+pub(crate) struct CancelPipe {
+    read: OwnedFd,
+    write: Arc<OwnedFd>,
+}
+
+impl CancelPipe {
+    pub(crate) fn new() -> Result<Self, Error> {
+        let mut fds = [0; 2];
+        // `pipe2` is missing on Apple targets, so set the flags after creating the pipe.
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+        let (read, write) = unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        for fd in [&read, &write] {
+            set_flags(fd.as_raw_fd()).map_err(Error::Io)?;
+        }
+        Ok(Self {
+            read,
+            write: Arc::new(write),
+        })
+    }
+
+    pub(crate) fn canceller(&self) -> Canceller {
+        Canceller {
+            write: Arc::clone(&self.write),
+        }
+    }
+
+    pub(crate) fn fd(&self) -> RawFd {
+        self.read.as_raw_fd()
+    }
+
+    /// Returns `true` if cancelled since the last call.
+    pub(crate) fn take(&self) -> bool {
+        let mut buf = [0u8; 64];
+        let mut cancelled = false;
+        while unsafe { libc::read(self.read.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) } > 0 {
+            cancelled = true;
+        }
+        cancelled
+    }
+}
+
+/// Make `fd` close on exec and non-blocking.
+fn set_flags(fd: RawFd) -> io::Result<()> {
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags == -1 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
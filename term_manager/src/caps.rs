@@ -0,0 +1,68 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::env;
+
+use crate::ColorDepth;
+
+/// Features supported by the terminal, detected from `$TERM`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TermCaps {
+    /// Colors the terminal can display.
+    pub color_depth: ColorDepth,
+    /// Cursor movement and clearing escape sequences work.
+    pub cursor_control: bool,
+    /// Pasted text can be bracketed with `ESC [ 200 ~` and `ESC [ 201 ~`.
+    pub bracketed_paste: bool,
+    /// xterm mouse reporting.
+    pub mouse: bool,
+    /// The alternate screen buffer.
+    pub alternate_screen: bool,
+    /// Setting the window title with OSC sequences.
+    pub title: bool,
+    /// Clickable hyperlinks with OSC 8 sequences.
+    pub hyperlinks: bool,
+}
+
+impl TermCaps {
+    /// Detect capabilities from the `TERM` environment variable.
+    pub fn detect() -> Self {
+        Self::from_term(&env::var("TERM").unwrap_or_default())
+    }
+
+    /// Returns the capabilities of the terminal type `term`.
+    ///
+    /// Unknown terminal types are assumed to be xterm-compatible, as nearly all current
+    /// terminal emulators are.
+    pub fn from_term(term: &str) -> Self {
+        let full = Self {
+            color_depth: ColorDepth::detect(),
+            cursor_control: true,
+            bracketed_paste: true,
+            mouse: true,
+            alternate_screen: true,
+            title: true,
+            hyperlinks: true,
+        };
+        let basic = Self {
+            bracketed_paste: false,
+            mouse: false,
+            alternate_screen: false,
+            title: false,
+            hyperlinks: false,
+            ..full
+        };
+
+        match term {
+            "" | "dumb" | "emacs" => Self {
+                cursor_control: false,
+                ..basic
+            },
+            // The Linux console supports colors and cursor movement only.
+            "linux" | "cons25" => basic,
+            t if t.starts_with("vt") || t == "ansi" => basic,
+            _ => full,
+        }
+    }
+}
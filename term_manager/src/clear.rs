@@ -0,0 +1,14 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! Escape sequences for clearing the screen.
+
+/// Clear the whole screen and move the cursor to the top left.
+pub const SCREEN: &str = "\x1b[2J\x1b[H";
+/// Clear the line the cursor is on, leaving the cursor where it is.
+pub const LINE: &str = "\x1b[2K";
+/// Clear from the cursor to the end of its line.
+pub const TO_END_OF_LINE: &str = "\x1b[K";
+/// Clear from the cursor to the end of the screen.
+pub const TO_END_OF_SCREEN: &str = "\x1b[J";
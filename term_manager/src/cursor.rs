@@ -0,0 +1,54 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! Escape sequences for moving and showing the cursor.
+//!
+//! Relative moves by zero return an empty string, since terminals treat a count of zero as
+//! one.
+
+/// Hide the cursor.
+pub const HIDE: &str = "\x1b[?25l";
+/// Show the cursor.
+pub const SHOW: &str = "\x1b[?25h";
+/// Save the cursor position.
+pub const SAVE: &str = "\x1b7";
+/// Restore the cursor position saved with `SAVE`.
+pub const RESTORE: &str = "\x1b8";
+/// Show the cursor as a block, e.g. for overwrite mode (DECSCUSR).
+pub const BLOCK: &str = "\x1b[2 q";
+/// Show the cursor in the terminal's default shape.
+pub const DEFAULT_SHAPE: &str = "\x1b[0 q";
+
+/// Move to `row` and `col`, counted from 0 at the top left of the screen.
+pub fn move_to(row: u16, col: u16) -> String {
+    format!("\x1b[{};{}H", row as u32 + 1, col as u32 + 1)
+}
+
+/// Move up `n` rows.
+pub fn up(n: usize) -> String {
+    relative(n, 'A')
+}
+
+/// Move down `n` rows.
+pub fn down(n: usize) -> String {
+    relative(n, 'B')
+}
+
+/// Move right `n` columns.
+pub fn right(n: usize) -> String {
+    relative(n, 'C')
+}
+
+/// Move left `n` columns.
+pub fn left(n: usize) -> String {
+    relative(n, 'D')
+}
+
+fn relative(n: usize, direction: char) -> String {
+    if n == 0 {
+        String::new()
+    } else {
+        format!("\x1b[{}{}", n, direction)
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    io, mem, ptr,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Canceller, Error, TermManager,
+    cancel::CancelPipe,
+    input::{self, KeyEvent},
+};
+
+/// Sent by the terminal before and after pasted text while bracketed paste is enabled.
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+/// Sent when the terminal gains or loses focus while focus reporting is enabled.
+const FOCUS_IN: &[u8] = b"\x1b[I";
+const FOCUS_OUT: &[u8] = b"\x1b[O";
+
+/// Set by the SIGWINCH handler until `next_event` reports the resize.
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_resize(_: libc::c_int) {
+    RESIZED.store(true, Ordering::SeqCst);
+}
+
+/// Input from the terminal, as returned by `TermManager::next_event`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A key press, with the bytes it was read as. Sequences without a key mapping, such as
+    /// mouse reports, are `KeyCode::Unknown`.
+    Key { key: KeyEvent, sequence: Vec<u8> },
+    /// Text pasted while bracketed paste is enabled, without the markers around it.
+    Paste(String),
+    /// The window was resized.
+    Resize { rows: u16, cols: u16 },
+    /// The terminal window gained focus, reported once `enable_focus_reporting` is called.
+    FocusGained,
+    /// The terminal window lost focus.
+    FocusLost,
+    /// A `Canceller` was triggered.
+    Cancelled,
+}
+
+impl TermManager {
+    /// Wait for the next key press, paste, resize or focus change, or a cancellation.
+    ///
+    /// The first call installs a SIGWINCH handler to notice resizes, which is replaced by the
+    /// previous one when the TermManager is dropped. A resize is only reported before input
+    /// that's already waiting, while a cancellation is reported first.
+    pub fn next_event(&mut self) -> Result<Event, Error> {
+        loop {
+            if let Some(event) = self.poll_event(None)? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Wait up to `timeout` for the next event like `next_event`, or indefinitely if it's
+    /// `None`. Returns `None` if the time ran out first.
+    pub fn poll_event(&mut self, timeout: Option<Duration>) -> Result<Option<Event>, Error> {
+        self.watch_resize()?;
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            if self.cancel.as_ref().is_some_and(CancelPipe::take) {
+                return Ok(Some(Event::Cancelled));
+            }
+            if !self.pending.is_empty() {
+                break;
+            }
+            if RESIZED.swap(false, Ordering::SeqCst) {
+                let (rows, cols) = self.size()?;
+                return Ok(Some(Event::Resize { rows, cols }));
+            }
+            let timeout_ms = match deadline {
+                Some(deadline) if Instant::now() >= deadline => return Ok(None),
+                // Rounded up so a short wait isn't a busy loop.
+                Some(deadline) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_nanos()
+                    .div_ceil(1_000_000)
+                    .min(i32::MAX as u128) as i32,
+                None => -1,
+            };
+            match self.wait_input(timeout_ms) {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(Error::Io(e)) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let sequence = self.read_sequence()?;
+        Ok(Some(match sequence.as_slice() {
+            PASTE_START => Event::Paste(self.read_paste()?),
+            FOCUS_IN => Event::FocusGained,
+            FOCUS_OUT => Event::FocusLost,
+            _ => Event::Key {
+                key: input::decode_sequence(&sequence),
+                sequence,
+            },
+        }))
+    }
+
+    /// Returns a handle that makes `next_event` return `Event::Cancelled` from another
+    /// thread. All handles trigger the same cancellation.
+    pub fn canceller(&mut self) -> Result<Canceller, Error> {
+        let pipe = match self.cancel.take() {
+            Some(pipe) => pipe,
+            None => CancelPipe::new()?,
+        };
+        let canceller = pipe.canceller();
+        self.cancel = Some(pipe);
+        Ok(canceller)
+    }
+
+    /// Wait up to `timeout_ms`, or indefinitely if it's negative, until input can be read or
+    /// a `Canceller` is triggered. Returns `true` if input is ready.
+    fn wait_input(&self, timeout_ms: i32) -> Result<bool, Error> {
+        let pollfd = |fd| libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // Negative descriptors are ignored.
+        let cancel_fd = self.cancel.as_ref().map_or(-1, CancelPipe::fd);
+        let mut fds = [pollfd(self.fd), pollfd(cancel_fd)];
+        let res = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout_ms) };
+        if res < 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+        Ok(fds[0].revents != 0)
+    }
+
+    /// Read pasted text up to the end marker.
+    fn read_paste(&mut self) -> Result<String, Error> {
+        let mut pasted = Vec::new();
+        while !pasted.ends_with(PASTE_END) {
+            pasted.push(self.read_byte()?);
+        }
+        pasted.truncate(pasted.len() - PASTE_END.len());
+        Ok(String::from_utf8_lossy(&pasted).into_owned())
+    }
+
+    /// Install the SIGWINCH handler, unless it already is.
+    fn watch_resize(&mut self) -> Result<(), Error> {
+        if self.previous_winch.is_some() {
+            return Ok(());
+        }
+        // No SA_RESTART, so waiting for input returns EINTR and can report the resize.
+        let mut action: libc::sigaction = unsafe { mem::zeroed() };
+        action.sa_sigaction = on_resize as *const () as libc::sighandler_t;
+        unsafe { libc::sigemptyset(&mut action.sa_mask) };
+
+        let mut previous: libc::sigaction = unsafe { mem::zeroed() };
+        let res = unsafe { libc::sigaction(libc::SIGWINCH, &action, &mut previous) };
+        if res != 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+        self.previous_winch = Some(previous);
+        Ok(())
+    }
+
+    /// Put back the SIGWINCH handler replaced by `watch_resize`.
+    pub(crate) fn unwatch_resize(&mut self) {
+        if let Some(previous) = self.previous_winch.take() {
+            unsafe { libc::sigaction(libc::SIGWINCH, &previous, ptr::null_mut()) };
+        }
+    }
+}
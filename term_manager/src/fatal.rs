@@ -0,0 +1,73 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    io, mem, ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use libc::c_int;
+
+use crate::Error;
+
+/// Signals that end the process by default without the terminal being restored.
+const FATAL_SIGNALS: [c_int; 3] = [libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT];
+
+/// The terminal and settings to restore if the process is killed, set while a
+/// `RawModeGuard` is alive.
+///
+/// Replaced entries are leaked rather than freed, since a signal handler may be reading
+/// them.
+static SAVED: AtomicPtr<(c_int, libc::termios)> = AtomicPtr::new(ptr::null_mut());
+
+/// Remember `original` as the settings to restore on `fd` from a fatal signal.
+pub(crate) fn save(fd: c_int, original: libc::termios) {
+    SAVED.store(Box::into_raw(Box::new((fd, original))), Ordering::SeqCst);
+}
+
+/// Forget the settings saved for `fd`, once they've been restored.
+pub(crate) fn forget(fd: c_int) {
+    let saved = SAVED.load(Ordering::SeqCst);
+    if !saved.is_null() && unsafe { (*saved).0 } == fd {
+        let _ = SAVED.compare_exchange(saved, ptr::null_mut(), Ordering::SeqCst, Ordering::SeqCst);
+    }
+}
+
+extern "C" fn on_fatal_signal(signal: c_int) {
+    let saved = SAVED.load(Ordering::SeqCst);
+    if !saved.is_null() {
+        let (fd, termios) = unsafe { &*saved };
+        unsafe { libc::tcsetattr(*fd, libc::TCSANOW, termios) };
+    }
+    // Die from the signal as if it hadn't been caught, so the exit status reports it.
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+/// Restore the terminal settings in effect before raw mode if the process is killed by
+/// SIGTERM, SIGHUP, or SIGQUIT, so the shell isn't left without echo or line editing.
+///
+/// Only signals still at their default action are caught; handlers installed by the
+/// application are left alone. The process still dies from the signal afterwards.
+pub fn restore_on_fatal_signals() -> Result<(), Error> {
+    for signal in FATAL_SIGNALS {
+        let mut current: libc::sigaction = unsafe { mem::zeroed() };
+        if unsafe { libc::sigaction(signal, ptr::null(), &mut current) } != 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+        if current.sa_sigaction != libc::SIG_DFL {
+            continue;
+        }
+
+        let mut action: libc::sigaction = unsafe { mem::zeroed() };
+        action.sa_sigaction = on_fatal_signal as *const () as libc::sighandler_t;
+        unsafe { libc::sigemptyset(&mut action.sa_mask) };
+        if unsafe { libc::sigaction(signal, &action, ptr::null_mut()) } != 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+    }
+    Ok(())
+}
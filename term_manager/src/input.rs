@@ -0,0 +1,559 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! Decoding of terminal input into key presses.
+//!
+//! `decode` reads one key from a blocking byte source, and `Parser` turns chunks of bytes
+//! into keys as they arrive, keeping a key split across chunks until the rest comes.
+//! Neither depends on a terminal, so they can decode recorded or generated input.
+
+/// A key without modifiers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    Char(char),
+    Enter,
+    Tab,
+    BackTab,
+    Backspace,
+    Esc,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    F(u8),
+    /// A recognized escape sequence with no key mapping.
+    Unknown,
+}
+
+/// Modifier keys held during a key press.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+}
+
+impl KeyModifiers {
+    pub const NONE: KeyModifiers = KeyModifiers {
+        shift: false,
+        alt: false,
+        ctrl: false,
+    };
+
+    /// Decode an xterm modifier parameter (`1 + bitmask` of shift/alt/ctrl).
+    pub fn from_param(param: u16) -> Self {
+        let bits = param.saturating_sub(1);
+        Self {
+            shift: bits & 1 != 0,
+            alt: bits & 2 != 0,
+            ctrl: bits & 4 != 0,
+        }
+    }
+}
+
+/// A decoded key press.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyEvent {
+    /// Create a key event without modifiers.
+    pub fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    /// Create a key event with modifiers.
+    pub fn with_modifiers(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Create a `Ctrl` + `c` key event.
+    pub fn ctrl(c: char) -> Self {
+        Self::with_modifiers(
+            KeyCode::Char(c),
+            KeyModifiers {
+                ctrl: true,
+                ..KeyModifiers::NONE
+            },
+        )
+    }
+
+    /// Create an `Alt` + `c` key event.
+    pub fn alt(c: char) -> Self {
+        Self::with_modifiers(
+            KeyCode::Char(c),
+            KeyModifiers {
+                alt: true,
+                ..KeyModifiers::NONE
+            },
+        )
+    }
+}
+
+/// Decodes keys from chunks of input bytes.
+#[derive(Clone, Debug, Default)]
+pub struct Parser {
+    /// Bytes of a key not yet complete.
+    pending: Vec<u8>,
+}
+
+impl Parser {
+    /// Create a parser with no pending input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode the keys completed by `bytes`. Bytes of an incomplete key at the end are kept
+    /// until the next call.
+    ///
+    /// A lone Escape stays pending, since it may begin a sequence; call `flush` once no more
+    /// input arrives in time to report it.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<KeyEvent> {
+        self.pending.extend_from_slice(bytes);
+        let mut keys = Vec::new();
+        let mut start = 0;
+        while start < self.pending.len() {
+            let mut pos = start;
+            let pending = &self.pending;
+            let key = decode(|| {
+                let b = pending.get(pos).copied().ok_or(())?;
+                pos += 1;
+                Ok::<u8, ()>(b)
+            });
+            match key {
+                Ok(key) => {
+                    keys.push(key);
+                    start = pos;
+                }
+                Err(()) => break,
+            }
+        }
+        self.pending.drain(..start);
+        keys
+    }
+
+    /// Decode the pending bytes as if no more input will follow them. A lone Escape is
+    /// reported as the Escape key, and a sequence cut short as `KeyCode::Unknown`.
+    pub fn flush(&mut self) -> Option<KeyEvent> {
+        let key = match self.pending.as_slice() {
+            [] => return None,
+            [0x1B] => KeyEvent::new(KeyCode::Esc),
+            [0x1B, b, ..] if *b != b'[' && *b != b'O' => KeyEvent::alt(char::REPLACEMENT_CHARACTER),
+            [0x1B, ..] => KeyEvent::new(KeyCode::Unknown),
+            _ => KeyEvent::new(KeyCode::Char(char::REPLACEMENT_CHARACTER)),
+        };
+        self.pending.clear();
+        Some(key)
+    }
+
+    /// Returns the bytes of an incomplete key, if any.
+    pub fn pending(&self) -> &[u8] {
+        &self.pending
+    }
+}
+
+/// Decode one key press from a byte source.
+///
+/// `next` returns the following byte, blocking until one arrives; its error is passed on.
+/// A lone Escape is only reported as such once the following byte is read.
+pub fn decode<E>(mut next: impl FnMut() -> Result<u8, E>) -> Result<KeyEvent, E> {
+    match next()? {
+        0x1B => decode_escape(&mut next),
+        byte @ 0x00..=0x7F => Ok(decode_ascii(byte)),
+        byte => decode_utf8(byte, &mut next).map(|c| KeyEvent::new(KeyCode::Char(c))),
+    }
+}
+
+/// Decode the bytes of one key, e.g. as read by `TermManager::read_sequence`. Bytes after
+/// the key are ignored, and a key cut short is decoded as by `Parser::flush`.
+pub fn decode_sequence(sequence: &[u8]) -> KeyEvent {
+    let mut parser = Parser::new();
+    let key = parser.feed(sequence).into_iter().next();
+    key.or_else(|| parser.flush())
+        .unwrap_or(KeyEvent::new(KeyCode::Unknown))
+}
+
+/// Returns the bytes a terminal sends for `key`, e.g. to replay keys into a program's input.
+///
+/// Modifiers on special keys use xterm's parameters, `Alt` on other keys a leading Escape,
+/// and `Ctrl` with a letter or one of `@[\]^_?` the matching control byte. Modifiers with
+/// no encoding are dropped, and `KeyCode::Unknown` encodes as nothing.
+///
+/// `decode_sequence` turns the bytes back into `key`, except where the terminal sends the
+/// same bytes for another key, which is decoded instead: Tab for Ctrl-I, Enter for Ctrl-M and
+/// Ctrl-J, Backspace for Ctrl-H and Ctrl-?, Esc for Ctrl-[, and Ctrl-Space for Ctrl-@.
+pub fn encode(key: KeyEvent) -> Vec<u8> {
+    let KeyModifiers { shift, alt, ctrl } = key.modifiers;
+    let param = 1 + shift as u8 + 2 * alt as u8 + 4 * ctrl as u8;
+    let csi = |suffix: &str| {
+        if param > 1 {
+            format!("\x1b[1;{}{}", param, suffix)
+        } else {
+            format!("\x1b[{}", suffix)
+        }
+    };
+    let tilde = |n: u8| {
+        if param > 1 {
+            format!("\x1b[{};{}~", n, param)
+        } else {
+            format!("\x1b[{}~", n)
+        }
+    };
+    let text = match key.code {
+        KeyCode::Up => csi("A"),
+        KeyCode::Down => csi("B"),
+        KeyCode::Right => csi("C"),
+        KeyCode::Left => csi("D"),
+        KeyCode::Home => csi("H"),
+        KeyCode::End => csi("F"),
+        KeyCode::BackTab => "\x1b[Z".to_string(),
+        KeyCode::Insert => tilde(2),
+        KeyCode::Delete => tilde(3),
+        KeyCode::PageUp => tilde(5),
+        KeyCode::PageDown => tilde(6),
+        KeyCode::F(n @ 1..=4) if param > 1 => csi(&((b'P' + n - 1) as char).to_string()),
+        KeyCode::F(n @ 1..=4) => format!("\x1bO{}", (b'P' + n - 1) as char),
+        KeyCode::F(n @ 5..=12) => tilde([15, 17, 18, 19, 20, 21, 23, 24][n as usize - 5]),
+        KeyCode::F(_) | KeyCode::Unknown => String::new(),
+        code => {
+            let text = match code {
+                KeyCode::Enter => "\r".to_string(),
+                KeyCode::Tab => "\t".to_string(),
+                KeyCode::Backspace => "\x7f".to_string(),
+                KeyCode::Esc => "\x1b".to_string(),
+                KeyCode::Char(c) if ctrl => match c.to_ascii_lowercase() {
+                    c @ 'a'..='z' => ((c as u8 - b'a' + 1) as char).to_string(),
+                    ' ' | '@' => "\0".to_string(),
+                    c @ ('[' | '\\' | ']' | '^' | '_') => ((c as u8 - b'@') as char).to_string(),
+                    '?' => "\x7f".to_string(),
+                    c => c.to_string(),
+                },
+                KeyCode::Char(c) => c.to_string(),
+                _ => String::new(),
+            };
+            if alt { format!("\x1b{}", text) } else { text }
+        }
+    };
+    text.into_bytes()
+}
+
+/// Decode a single ASCII byte, mapping control bytes to their keys.
+fn decode_ascii(byte: u8) -> KeyEvent {
+    match byte {
+        b'\r' | b'\n' => KeyEvent::new(KeyCode::Enter),
+        b'\t' => KeyEvent::new(KeyCode::Tab),
+        0x7F | 0x08 => KeyEvent::new(KeyCode::Backspace),
+        0x1B => KeyEvent::new(KeyCode::Esc),
+        0x00 => KeyEvent::ctrl(' '),
+        0x01..=0x1A => KeyEvent::ctrl((b'a' + byte - 1) as char),
+        0x1C..=0x1F => KeyEvent::ctrl((byte + b'@') as char),
+        _ => KeyEvent::new(KeyCode::Char(byte as char)),
+    }
+}
+
+/// Decode the bytes following an escape: CSI (`ESC [`) and SS3 (`ESC O`) sequences, or
+/// `Alt` + key.
+fn decode_escape<E>(next: &mut impl FnMut() -> Result<u8, E>) -> Result<KeyEvent, E> {
+    match next()? {
+        b'[' => decode_csi(next),
+        b'O' => {
+            let code = match next()? {
+                b'A' => KeyCode::Up,
+                b'B' => KeyCode::Down,
+                b'C' => KeyCode::Right,
+                b'D' => KeyCode::Left,
+                b'H' => KeyCode::Home,
+                b'F' => KeyCode::End,
+                b @ b'P'..=b'S' => KeyCode::F(b - b'P' + 1),
+                _ => KeyCode::Unknown,
+            };
+            Ok(KeyEvent::new(code))
+        }
+        0x1B => Ok(KeyEvent::new(KeyCode::Esc)),
+        byte => {
+            let mut key = match byte {
+                0x80..=0xFF => KeyEvent::new(KeyCode::Char(decode_utf8(byte, next)?)),
+                _ => decode_ascii(byte),
+            };
+            key.modifiers.alt = true;
+            Ok(key)
+        }
+    }
+}
+
+/// Decode a CSI sequence after `ESC [`: numeric parameters separated by `;` and a final byte.
+///
+/// Sub-parameters after `:` are skipped.
+fn decode_csi<E>(next: &mut impl FnMut() -> Result<u8, E>) -> Result<KeyEvent, E> {
+    let mut params: Vec<u16> = vec![0];
+    let mut sub_param = false;
+    let final_byte = loop {
+        match next()? {
+            b @ b'0'..=b'9' if !sub_param => {
+                if let Some(p) = params.last_mut() {
+                    *p = p.saturating_mul(10).saturating_add((b - b'0') as u16);
+                }
+            }
+            b':' => sub_param = true,
+            b';' => {
+                params.push(0);
+                sub_param = false;
+            }
+            b @ 0x40..=0x7E => break b,
+            _ => {}
+        }
+    };
+
+    let modifiers = KeyModifiers::from_param(params.get(1).copied().unwrap_or(1));
+    let code = match final_byte {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'Z' => KeyCode::BackTab,
+        b'P'..=b'S' => KeyCode::F(final_byte - b'P' + 1),
+        b'~' => match params[0] {
+            1 | 7 => KeyCode::Home,
+            2 => KeyCode::Insert,
+            3 => KeyCode::Delete,
+            4 | 8 => KeyCode::End,
+            5 => KeyCode::PageUp,
+            6 => KeyCode::PageDown,
+            n @ 11..=15 => KeyCode::F((n - 10) as u8),
+            n @ 17..=21 => KeyCode::F((n - 11) as u8),
+            n @ 23..=24 => KeyCode::F((n - 12) as u8),
+            _ => KeyCode::Unknown,
+        },
+        // Kitty keyboard protocol: the parameter is the key's Unicode code point.
+        b'u' => match params[0] {
+            13 => KeyCode::Enter,
+            9 => KeyCode::Tab,
+            27 => KeyCode::Esc,
+            127 => KeyCode::Backspace,
+            n => char::from_u32(n as u32).map_or(KeyCode::Unknown, KeyCode::Char),
+        },
+        _ => KeyCode::Unknown,
+    };
+    Ok(KeyEvent::with_modifiers(code, modifiers))
+}
+
+/// Decode a UTF-8 character starting with `first`, reading continuation bytes as needed.
+fn decode_utf8<E>(first: u8, next: &mut impl FnMut() -> Result<u8, E>) -> Result<char, E> {
+    let len = match first {
+        0x00..=0x7F => 1,
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => return Ok(char::REPLACEMENT_CHARACTER),
+    };
+
+    let mut bytes = [first, 0, 0, 0];
+    for b in bytes.iter_mut().take(len).skip(1) {
+        *b = next()?;
+    }
+    Ok(std::str::from_utf8(&bytes[..len])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, shift: bool, alt: bool, ctrl: bool) -> KeyEvent {
+        KeyEvent::with_modifiers(code, KeyModifiers { shift, alt, ctrl })
+    }
+
+    #[test]
+    fn decodes_csi_and_ss3_keys() {
+        assert_eq!(decode_sequence(b"\x1b[A"), KeyEvent::new(KeyCode::Up));
+        assert_eq!(decode_sequence(b"\x1bOB"), KeyEvent::new(KeyCode::Down));
+        assert_eq!(decode_sequence(b"\x1b[H"), KeyEvent::new(KeyCode::Home));
+        assert_eq!(decode_sequence(b"\x1bOF"), KeyEvent::new(KeyCode::End));
+        assert_eq!(decode_sequence(b"\x1b[3~"), KeyEvent::new(KeyCode::Delete));
+        assert_eq!(decode_sequence(b"\x1b[5~"), KeyEvent::new(KeyCode::PageUp));
+        assert_eq!(decode_sequence(b"\x1bOP"), KeyEvent::new(KeyCode::F(1)));
+        assert_eq!(decode_sequence(b"\x1b[24~"), KeyEvent::new(KeyCode::F(12)));
+        assert_eq!(decode_sequence(b"\x1b[Z"), KeyEvent::new(KeyCode::BackTab));
+        assert_eq!(
+            decode_sequence(b"\x1b[99X"),
+            KeyEvent::new(KeyCode::Unknown)
+        );
+    }
+
+    #[test]
+    fn decodes_xterm_modifier_parameters() {
+        assert_eq!(
+            decode_sequence(b"\x1b[1;2C"),
+            key(KeyCode::Right, true, false, false)
+        );
+        assert_eq!(
+            decode_sequence(b"\x1b[1;3D"),
+            key(KeyCode::Left, false, true, false)
+        );
+        assert_eq!(
+            decode_sequence(b"\x1b[1;5A"),
+            key(KeyCode::Up, false, false, true)
+        );
+        assert_eq!(
+            decode_sequence(b"\x1b[3;8~"),
+            key(KeyCode::Delete, true, true, true)
+        );
+        assert_eq!(
+            decode_sequence(b"\x1b[1;5P"),
+            key(KeyCode::F(1), false, false, true)
+        );
+    }
+
+    #[test]
+    fn decodes_kitty_csi_u() {
+        assert_eq!(decode_sequence(b"\x1b[97;5u"), KeyEvent::ctrl('a'));
+        assert_eq!(
+            decode_sequence(b"\x1b[13;2u"),
+            key(KeyCode::Enter, true, false, false)
+        );
+        assert_eq!(decode_sequence(b"\x1b[27u"), KeyEvent::new(KeyCode::Esc));
+        assert_eq!(
+            decode_sequence(b"\x1b[127;3u"),
+            key(KeyCode::Backspace, false, true, false)
+        );
+        // The shifted key after `:` is a sub-parameter and skipped.
+        assert_eq!(
+            decode_sequence(b"\x1b[97:65;2u"),
+            key(KeyCode::Char('a'), true, false, false)
+        );
+    }
+
+    #[test]
+    fn decodes_control_bytes() {
+        assert_eq!(decode_sequence(b"\x01"), KeyEvent::ctrl('a'));
+        assert_eq!(decode_sequence(b"\x00"), KeyEvent::ctrl(' '));
+        assert_eq!(decode_sequence(b"\x1c"), KeyEvent::ctrl('\\'));
+        assert_eq!(decode_sequence(b"\x1d"), KeyEvent::ctrl(']'));
+        assert_eq!(decode_sequence(b"\x1e"), KeyEvent::ctrl('^'));
+        assert_eq!(decode_sequence(b"\x1f"), KeyEvent::ctrl('_'));
+        assert_eq!(decode_sequence(b"\x1bx"), KeyEvent::alt('x'));
+    }
+
+    #[test]
+    fn keeps_utf8_split_across_feeds() {
+        let mut parser = Parser::new();
+        let bytes = "aé€".as_bytes();
+        assert_eq!(
+            parser.feed(&bytes[..2]),
+            vec![KeyEvent::new(KeyCode::Char('a'))]
+        );
+        assert_eq!(parser.pending(), &bytes[1..2]);
+        assert_eq!(
+            parser.feed(&bytes[2..4]),
+            vec![KeyEvent::new(KeyCode::Char('é'))]
+        );
+        assert_eq!(
+            parser.feed(&bytes[4..]),
+            vec![KeyEvent::new(KeyCode::Char('€'))]
+        );
+        assert!(parser.pending().is_empty());
+    }
+
+    #[test]
+    fn keeps_escape_sequence_split_across_feeds() {
+        let mut parser = Parser::new();
+        assert!(parser.feed(b"\x1b[1;").is_empty());
+        assert_eq!(
+            parser.feed(b"5A"),
+            vec![key(KeyCode::Up, false, false, true)]
+        );
+    }
+
+    #[test]
+    fn flushes_lone_escape() {
+        let mut parser = Parser::new();
+        assert!(parser.feed(b"\x1b").is_empty());
+        assert_eq!(parser.pending(), b"\x1b");
+        assert_eq!(parser.flush(), Some(KeyEvent::new(KeyCode::Esc)));
+        assert_eq!(parser.flush(), None);
+
+        assert!(parser.feed(b"\x1b[1").is_empty());
+        assert_eq!(parser.flush(), Some(KeyEvent::new(KeyCode::Unknown)));
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        let mut keys = vec![
+            KeyEvent::new(KeyCode::Enter),
+            KeyEvent::new(KeyCode::Tab),
+            KeyEvent::new(KeyCode::BackTab),
+            KeyEvent::new(KeyCode::Backspace),
+            KeyEvent::new(KeyCode::Esc),
+            KeyEvent::new(KeyCode::Char('é')),
+            KeyEvent::alt('é'),
+            KeyEvent::ctrl(' '),
+        ];
+        for c in ('a'..='z').filter(|c| !matches!(c, 'h' | 'i' | 'j' | 'm')) {
+            keys.extend([
+                KeyEvent::new(KeyCode::Char(c)),
+                KeyEvent::ctrl(c),
+                KeyEvent::alt(c),
+            ]);
+        }
+        keys.extend(['\\', ']', '^', '_'].map(KeyEvent::ctrl));
+        let special = [
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Home,
+            KeyCode::End,
+            KeyCode::Insert,
+            KeyCode::Delete,
+            KeyCode::PageUp,
+            KeyCode::PageDown,
+            KeyCode::F(1),
+            KeyCode::F(4),
+            KeyCode::F(5),
+            KeyCode::F(12),
+        ];
+        for code in special {
+            for param in 1..=8 {
+                keys.push(KeyEvent::with_modifiers(
+                    code,
+                    KeyModifiers::from_param(param),
+                ));
+            }
+        }
+        for key in keys {
+            assert_eq!(decode_sequence(&encode(key)), key, "{:?}", encode(key));
+        }
+    }
+
+    #[test]
+    fn encodes_keys_sharing_bytes_as_the_other_key() {
+        assert_eq!(
+            decode_sequence(&encode(KeyEvent::ctrl('i'))),
+            KeyEvent::new(KeyCode::Tab)
+        );
+        assert_eq!(
+            decode_sequence(&encode(KeyEvent::ctrl('['))),
+            KeyEvent::new(KeyCode::Esc)
+        );
+        assert_eq!(
+            decode_sequence(&encode(KeyEvent::ctrl('@'))),
+            KeyEvent::ctrl(' ')
+        );
+    }
+}
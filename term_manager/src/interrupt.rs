@@ -0,0 +1,60 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::{
+    io, mem, ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::Error;
+
+/// Set by the SIGINT handler while an `InterruptGuard` is alive.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Catches SIGINT while alive, recording it instead of terminating the process.
+///
+/// Lets Ctrl-C cancel a long-running computation with the terminal in cooked mode. The
+/// previous SIGINT disposition is restored on drop.
+pub struct InterruptGuard {
+    previous: libc::sigaction,
+}
+
+impl InterruptGuard {
+    /// Install the SIGINT handler and clear the interrupted flag.
+    pub fn new() -> Result<Self, Error> {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+
+        // No SA_RESTART, so blocking system calls return EINTR and can check the flag.
+        let mut action: libc::sigaction = unsafe { mem::zeroed() };
+        action.sa_sigaction = on_interrupt as *const () as libc::sighandler_t;
+        unsafe { libc::sigemptyset(&mut action.sa_mask) };
+
+        let mut previous: libc::sigaction = unsafe { mem::zeroed() };
+        let res = unsafe { libc::sigaction(libc::SIGINT, &action, &mut previous) };
+        if res != 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+        Ok(Self { previous })
+    }
+
+    /// Returns `true` if SIGINT was received since the guard was created.
+    pub fn is_interrupted(&self) -> bool {
+        INTERRUPTED.load(Ordering::SeqCst)
+    }
+
+    /// Returns the flag set on SIGINT, e.g. to share with worker threads.
+    pub fn flag(&self) -> &'static AtomicBool {
+        &INTERRUPTED
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        unsafe { libc::sigaction(libc::SIGINT, &self.previous, ptr::null_mut()) };
+    }
+}
@@ -3,14 +3,47 @@
 // Created: 2025-09-14
 
 use std::{
+    collections::VecDeque,
     fmt::Display,
-    io::{self, Read, Stdin, Stdout, Write},
-    os::fd::{AsRawFd, RawFd},
-    u8,
+    fs::{File, OpenOptions},
+    io::{self, IoSlice, Stdin, Stdout, Write},
+    os::fd::{AsRawFd, OwnedFd, RawFd},
+    time::{Duration, Instant},
 };
 
+mod cancel;
+mod caps;
+pub mod clear;
+pub mod cursor;
+mod event;
+mod fatal;
+pub mod input;
+mod interrupt;
+mod mode;
+pub mod screen;
+mod style;
+
+use cancel::CancelPipe;
+pub use cancel::Canceller;
+pub use caps::TermCaps;
+pub use event::Event;
+pub use fatal::restore_on_fatal_signals;
+pub use interrupt::InterruptGuard;
+pub use mode::{Mode, ModeFlags, RawModeGuard};
+pub use style::{Attribute, Color, ColorDepth, RESET_STYLE};
+
+/// How long `cursor_position` waits for the terminal to reply.
+const DSR_TIMEOUT_MS: i32 = 500;
+/// How long `read_sequence` waits for each byte after the first, which also tells a lone
+/// Escape from Alt + key.
+const ESCAPE_TIMEOUT_MS: i32 = 100;
+
+/// Most bytes of input read with one system call.
+const READ_CHUNK: usize = 4096;
+
 /// Error type for IO and UNIX errors.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     Io(io::Error),
     Errno(nix::errno::Errno),
@@ -37,12 +70,40 @@ impl Display for Error {
     }
 }
 
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Errno(e) => Some(e),
+        }
+    }
+}
+
 /// Manipulates terminal state via libc.
 pub struct TermManager {
     stdin: Stdin,
     stdout: Stdout,
+    /// Restores the terminal settings on drop, so it's declared before `_tty`.
+    raw: RawModeGuard,
+    /// Keeps `/dev/tty` open while `fd` refers to it.
+    _tty: Option<File>,
+    /// The terminal written to instead of stdout, for `from_fd`.
+    output: Option<File>,
     fd: RawFd,
-    original_termios: libc::termios,
+    mouse: bool,
+    kitty_keyboard: bool,
+    focus_reporting: bool,
+    alternate_screen: bool,
+    caps: TermCaps,
+    /// Input read but not yet returned, e.g. the rest of a chunk or keys typed while waiting
+    /// for a terminal reply.
+    pending: VecDeque<u8>,
+    /// Output queued with `queue`, written by `flush`.
+    queued: Vec<u8>,
+    /// The SIGWINCH handler replaced by `next_event`'s, once installed.
+    previous_winch: Option<libc::sigaction>,
+    /// Written by `Canceller`s, once one is created.
+    cancel: Option<CancelPipe>,
 }
 
 impl TermManager {
@@ -51,13 +112,81 @@ impl TermManager {
         let stdin = io::stdin();
         let stdout = io::stdout();
         let fd = stdin.as_raw_fd();
-        let original_termios = enable_raw_mode(fd)?;
+        let raw = RawModeGuard::new(fd)?;
 
         Ok(TermManager {
             stdin,
             stdout,
+            raw,
+            _tty: None,
+            output: None,
+            fd,
+            mouse: false,
+            kitty_keyboard: false,
+            focus_reporting: false,
+            alternate_screen: false,
+            caps: TermCaps::detect(),
+            pending: VecDeque::new(),
+            queued: Vec::new(),
+            previous_winch: None,
+            cancel: None,
+        })
+    }
+
+    /// Create a new TermManager in raw mode that reads keyboard input from `/dev/tty`.
+    ///
+    /// Useful when stdin is redirected: the piped data stays available through
+    /// `get_stdin` while interactive input comes from the controlling terminal.
+    pub fn from_tty() -> Result<TermManager, Error> {
+        let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let fd = tty.as_raw_fd();
+        let raw = RawModeGuard::new(fd)?;
+
+        Ok(TermManager {
+            stdin: io::stdin(),
+            stdout: io::stdout(),
+            raw,
+            _tty: Some(tty),
+            output: None,
+            fd,
+            mouse: false,
+            kitty_keyboard: false,
+            focus_reporting: false,
+            alternate_screen: false,
+            caps: TermCaps::detect(),
+            pending: VecDeque::new(),
+            queued: Vec::new(),
+            previous_winch: None,
+            cancel: None,
+        })
+    }
+
+    /// Create a new TermManager in raw mode that reads from and writes to the terminal `fd`,
+    /// e.g. a pseudo-terminal opened by a test harness, instead of stdin and stdout.
+    ///
+    /// The file descriptor is closed when the TermManager is dropped, after the original
+    /// settings are restored.
+    pub fn from_fd(fd: OwnedFd) -> Result<TermManager, Error> {
+        let output = File::from(fd);
+        let fd = output.as_raw_fd();
+        let raw = RawModeGuard::new(fd)?;
+
+        Ok(TermManager {
+            stdin: io::stdin(),
+            stdout: io::stdout(),
+            raw,
+            _tty: None,
+            output: Some(output),
             fd,
-            original_termios,
+            mouse: false,
+            kitty_keyboard: false,
+            focus_reporting: false,
+            alternate_screen: false,
+            caps: TermCaps::detect(),
+            pending: VecDeque::new(),
+            queued: Vec::new(),
+            previous_winch: None,
+            cancel: None,
         })
     }
 
@@ -69,65 +198,769 @@ impl TermManager {
         &self.stdout
     }
 
-    /// Flush stdout.
+    /// Restore the original terminal settings, e.g. while a child process runs.
+    ///
+    /// Output is drained first so none of it is processed under the new settings.
+    pub fn suspend_raw_mode(&mut self) -> Result<(), Error> {
+        self.drain_output()?;
+        self.raw.suspend()
+    }
+
+    /// Undo the changes made to the terminal, as dropping the TermManager does, but
+    /// returning the first error instead of ignoring it: mouse reporting, the kitty
+    /// keyboard protocol and focus reporting are disabled, the primary screen is restored, and the original
+    /// settings are applied.
+    ///
+    /// The original settings stay in effect until `resume_raw_mode`.
+    pub fn restore(&mut self) -> Result<(), Error> {
+        let mut result = Ok(());
+        if self.mouse {
+            result = result.and(self.disable_mouse());
+        }
+        if self.kitty_keyboard {
+            result = result.and(self.disable_kitty_keyboard());
+        }
+        if self.focus_reporting {
+            result = result.and(self.disable_focus_reporting());
+        }
+        if self.alternate_screen {
+            result = result.and(self.leave_alternate_screen());
+        }
+        result.and(self.suspend_raw_mode())
+    }
+
+    /// Re-enter the current mode after `suspend_raw_mode`.
+    pub fn resume_raw_mode(&mut self) -> Result<(), Error> {
+        self.raw.resume()
+    }
+
+    /// Returns the current input mode, `Mode::Raw` unless changed with `set_mode`.
+    pub fn mode(&self) -> Mode {
+        self.raw.mode()
+    }
+
+    /// Switch the terminal to `mode`. The original settings are still restored on drop.
+    pub fn set_mode(&mut self, mode: Mode) -> Result<(), Error> {
+        self.raw.set_mode(mode)
+    }
+
+    /// Enable or disable software flow control in the current mode.
+    ///
+    /// Disabled in raw mode, so Ctrl-S and Ctrl-Q are read as input instead of freezing and
+    /// resuming terminal output.
+    pub fn set_flow_control(&mut self, enabled: bool) -> Result<(), Error> {
+        let flags = ModeFlags {
+            flow_control: enabled,
+            ..self.raw.mode().flags()
+        };
+        self.set_mode(Mode::Custom(flags))
+    }
+
+    /// Keep Ctrl-C, Ctrl-Z and Ctrl-\\ sending SIGINT, SIGTSTP and SIGQUIT in the current
+    /// mode.
+    ///
+    /// Disabled in raw mode, so those keys are read as input.
+    pub fn set_signals(&mut self, enabled: bool) -> Result<(), Error> {
+        let flags = ModeFlags {
+            signals: enabled,
+            ..self.raw.mode().flags()
+        };
+        self.set_mode(Mode::Custom(flags))
+    }
+
+    /// Keep carriage returns read as `\n` in the current mode.
+    ///
+    /// Left to the terminal in every preset, where it's usually on. Turning it off tells
+    /// Enter (`\r`) apart from Ctrl-J (`\n`), and a `\r\n` pair from two line endings.
+    pub fn set_cr_to_newline(&mut self, enabled: bool) -> Result<(), Error> {
+        let flags = ModeFlags {
+            cr_to_newline: enabled,
+            ..self.raw.mode().flags()
+        };
+        self.set_mode(Mode::Custom(flags))
+    }
+
+    /// Stop the process as the terminal's suspend key would, with the original terminal
+    /// settings restored until it is continued.
+    pub fn suspend_process(&mut self) -> Result<(), Error> {
+        self.suspend_raw_mode()?;
+        unsafe { libc::raise(libc::SIGTSTP) };
+        self.resume_raw_mode()
+    }
+
+    /// Enable xterm mouse reporting.
+    ///
+    /// Clicks and wheel events then arrive on stdin as SGR sequences
+    /// (`ESC [ < button ; column ; row M`). Reporting is disabled again on drop. Does
+    /// nothing if the terminal doesn't support mouse reporting.
+    pub fn enable_mouse(&mut self) -> Result<(), Error> {
+        if !self.caps.mouse {
+            return Ok(());
+        }
+        self.write(b"\x1b[?1000h\x1b[?1006h")?;
+        self.mouse = true;
+        self.flush()
+    }
+
+    /// Disable xterm mouse reporting.
+    pub fn disable_mouse(&mut self) -> Result<(), Error> {
+        self.write(b"\x1b[?1006l\x1b[?1000l")?;
+        self.mouse = false;
+        self.flush()
+    }
+
+    /// Enable the kitty keyboard protocol's "disambiguate escape codes" enhancement.
+    ///
+    /// Keys the legacy encoding can't tell apart, such as Esc, Ctrl-I and Shift-Tab, then
+    /// arrive as `ESC [ code ; modifiers u`. Terminals without the protocol ignore the
+    /// request. The previous mode is restored on drop.
+    pub fn enable_kitty_keyboard(&mut self) -> Result<(), Error> {
+        self.write(b"\x1b[>1u")?;
+        self.kitty_keyboard = true;
+        self.flush()
+    }
+
+    /// Restore the keyboard mode in effect before `enable_kitty_keyboard`.
+    pub fn disable_kitty_keyboard(&mut self) -> Result<(), Error> {
+        self.write(b"\x1b[<u")?;
+        self.kitty_keyboard = false;
+        self.flush()
+    }
+
+    /// Enable focus reporting, so `next_event` returns `Event::FocusGained` and
+    /// `Event::FocusLost` as the terminal window gains and loses focus. Terminals without
+    /// it ignore the request. Reporting is disabled again on drop.
+    pub fn enable_focus_reporting(&mut self) -> Result<(), Error> {
+        self.write(b"\x1b[?1004h")?;
+        self.focus_reporting = true;
+        self.flush()
+    }
+
+    /// Disable focus reporting.
+    pub fn disable_focus_reporting(&mut self) -> Result<(), Error> {
+        self.write(b"\x1b[?1004l")?;
+        self.focus_reporting = false;
+        self.flush()
+    }
+
+    /// Queue text or an escape sequence, such as `cursor::up(1)`, to be written by `flush`.
+    ///
+    /// Queuing a whole frame and flushing once writes it with a single system call, so the
+    /// terminal never shows it half drawn.
+    pub fn queue(&mut self, command: impl AsRef<[u8]>) -> &mut Self {
+        let command = command.as_ref();
+        if self.raw.mode().flags().output_processing || !command.contains(&b'\n') {
+            self.queued.extend_from_slice(command);
+        } else {
+            self.queued.extend(translate_newlines(command));
+        }
+        self
+    }
+
+    /// Discard keys typed but not yet read (`tcflush`), e.g. after Ctrl-C cancels a command
+    /// or before asking for a password.
+    pub fn discard_input(&mut self) -> Result<(), Error> {
+        self.pending.clear();
+        let res = unsafe { libc::tcflush(self.fd, libc::TCIFLUSH) };
+        if res != 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    /// Overwrite input already read with zeros, e.g. after reading a password. Bytes still
+    /// waiting to be read are kept.
+    pub fn wipe_read_input(&mut self) {
+        let len = self.pending.len();
+        // Filling the buffer to capacity covers every slot earlier input passed through.
+        self.pending.resize(self.pending.capacity(), 0);
+        let (front, back) = self.pending.as_mut_slices();
+        for b in front.iter_mut().chain(back.iter_mut()).skip(len) {
+            unsafe { std::ptr::write_volatile(b, 0) };
+        }
+        self.pending.truncate(len);
+    }
+
+    /// Write any queued output and wait until the terminal has received all of it
+    /// (`tcdrain`). Only flushes if stdout isn't a terminal.
+    pub fn drain_output(&mut self) -> Result<(), Error> {
+        self.flush()?;
+        let res = unsafe { libc::tcdrain(self.output_fd()) };
+        if res != 0 {
+            let e = io::Error::last_os_error();
+            if e.raw_os_error() != Some(libc::ENOTTY) {
+                return Err(Error::Io(e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bytes queued and not yet flushed.
+    pub fn queued_len(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Flush the output, then write any queued output.
     pub fn flush(&mut self) -> Result<(), Error> {
-        match self.stdout.flush() {
-            Ok(_) => Ok(()),
-            Err(e) => Err(Error::Io(e)),
+        self.output().flush()?;
+        if self.queued.is_empty() {
+            return Ok(());
+        }
+
+        let queued = std::mem::take(&mut self.queued);
+        let mut written = 0;
+        while written < queued.len() {
+            let rest = &queued[written..];
+            let res = unsafe { libc::write(self.output_fd(), rest.as_ptr().cast(), rest.len()) };
+            if res < 0 {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err(Error::Io(e));
+                }
+            } else {
+                written += res as usize;
+            }
         }
+        Ok(())
     }
 
-    /// Write buffer to stdout.
+    /// Write buffer to stdout, or the terminal passed to `from_fd`.
+    ///
+    /// If output processing is off in the current mode, `\n` is written as `\r\n`.
     pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
-        match self.stdout.write(data) {
-            Ok(r) => {
-                if r <= 0 && data.len() <= 0 {
-                    let msg = format!("only wrote {} of {} bytes", r, data.len());
+        let translated;
+        let data = if self.raw.mode().flags().output_processing || !data.contains(&b'\n') {
+            data
+        } else {
+            translated = translate_newlines(data);
+            &translated
+        };
+        self.output().write_all(data).map_err(Error::Io)
+    }
+
+    /// Write several buffers to the output, in order, with as few system calls as possible.
+    ///
+    /// If output processing is off in the current mode, `\n` is written as `\r\n`.
+    pub fn write_many(&mut self, bufs: &[IoSlice]) -> Result<(), Error> {
+        if !self.raw.mode().flags().output_processing && bufs.iter().any(|b| b.contains(&b'\n')) {
+            let data: Vec<u8> = bufs.iter().flat_map(|b| b.iter().copied()).collect();
+            return self.write(&data);
+        }
+
+        let mut bufs: Vec<IoSlice> = bufs.iter().filter(|b| !b.is_empty()).copied().collect();
+        let mut remaining = &mut bufs[..];
+        while !remaining.is_empty() {
+            match self.output().write_vectored(remaining) {
+                Ok(0) => {
+                    let msg = "failed to write whole buffer";
                     return Err(Error::Io(io::Error::new(io::ErrorKind::WriteZero, msg)));
                 }
-
-                Ok(())
+                Ok(n) => IoSlice::advance_slices(&mut remaining, n),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(Error::Io(e)),
             }
-            Err(e) => Err(Error::Io(e)),
         }
+        Ok(())
+    }
+
+    /// Returns the terminal capabilities detected from the environment.
+    pub fn caps(&self) -> &TermCaps {
+        &self.caps
+    }
+
+    /// Override the detected capabilities.
+    pub fn set_caps(&mut self, caps: TermCaps) {
+        self.caps = caps;
+    }
+
+    /// Returns the color depth detected from the environment.
+    pub fn color_depth(&self) -> ColorDepth {
+        self.caps.color_depth
+    }
+
+    /// Override the detected color depth.
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.caps.color_depth = depth;
+    }
+
+    /// Set the foreground color of text written after this call, downgraded to the
+    /// terminal's color depth.
+    pub fn set_fg(&mut self, color: Color) -> Result<(), Error> {
+        if self.caps.color_depth == ColorDepth::NoColor {
+            return Ok(());
+        }
+        let color = color.downgrade(self.caps.color_depth);
+        self.write(color.fg_sequence().as_bytes())
+    }
+
+    /// Set the background color of text written after this call, downgraded to the
+    /// terminal's color depth.
+    pub fn set_bg(&mut self, color: Color) -> Result<(), Error> {
+        if self.caps.color_depth == ColorDepth::NoColor {
+            return Ok(());
+        }
+        let color = color.downgrade(self.caps.color_depth);
+        self.write(color.bg_sequence().as_bytes())
+    }
+
+    /// Enable a text attribute such as bold or underline.
+    pub fn set_attr(&mut self, attr: Attribute) -> Result<(), Error> {
+        if self.caps.color_depth == ColorDepth::NoColor {
+            return Ok(());
+        }
+        self.write(attr.sequence().as_bytes())
+    }
+
+    /// Reset colors and attributes to the terminal default.
+    pub fn reset_style(&mut self) -> Result<(), Error> {
+        if self.caps.color_depth == ColorDepth::NoColor {
+            return Ok(());
+        }
+        self.write(RESET_STYLE.as_bytes())
+    }
+
+    /// Switch to the alternate screen buffer for full-screen output.
+    ///
+    /// The primary screen and its scrollback are restored by `leave_alternate_screen`, or
+    /// on drop. Does nothing if the terminal has no alternate screen.
+    pub fn enter_alternate_screen(&mut self) -> Result<(), Error> {
+        if !self.caps.alternate_screen {
+            return Ok(());
+        }
+        self.write(screen::ENTER_ALTERNATE.as_bytes())?;
+        self.alternate_screen = true;
+        self.flush()
+    }
+
+    /// Return to the primary screen buffer.
+    pub fn leave_alternate_screen(&mut self) -> Result<(), Error> {
+        self.write(screen::LEAVE_ALTERNATE.as_bytes())?;
+        self.alternate_screen = false;
+        self.flush()
+    }
+
+    /// Returns the terminal size as `(rows, cols)`.
+    pub fn size(&self) -> Result<(u16, u16), Error> {
+        let mut winsize = std::mem::MaybeUninit::<libc::winsize>::uninit();
+        let res = unsafe { libc::ioctl(self.fd, libc::TIOCGWINSZ, winsize.as_mut_ptr()) };
+        if res != 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+        let winsize = unsafe { winsize.assume_init() };
+        Ok((winsize.ws_row, winsize.ws_col))
+    }
+
+    /// Restrict scrolling to rows `top` through `bottom` inclusive, counted from 0.
+    ///
+    /// Rows outside the region stay in place, so a status bar or header can be pinned while
+    /// output scrolls. Note that setting the region moves the cursor to the top left.
+    pub fn set_scroll_region(&mut self, top: u16, bottom: u16) -> Result<(), Error> {
+        self.write(screen::scroll_region(top, bottom).as_bytes())
+    }
+
+    /// Reset the scroll region to the whole screen.
+    pub fn reset_scroll_region(&mut self) -> Result<(), Error> {
+        self.write(screen::RESET_SCROLL_REGION.as_bytes())
+    }
+
+    /// Set the terminal window and tab title. Does nothing if the terminal has no title.
+    pub fn set_title(&mut self, title: &str) -> Result<(), Error> {
+        if !self.caps.title {
+            return Ok(());
+        }
+        self.write(screen::title(title).as_bytes())?;
+        self.flush()
+    }
+
+    /// Clear the whole screen and move the cursor to the top left.
+    pub fn clear_screen(&mut self) -> Result<(), Error> {
+        self.write(clear::SCREEN.as_bytes())
+    }
+
+    /// Clear the line the cursor is on.
+    pub fn clear_line(&mut self) -> Result<(), Error> {
+        self.write(clear::LINE.as_bytes())
+    }
+
+    /// Clear from the cursor to the end of its line.
+    pub fn clear_to_end_of_line(&mut self) -> Result<(), Error> {
+        self.write(clear::TO_END_OF_LINE.as_bytes())
+    }
+
+    /// Clear from the cursor to the end of the screen.
+    pub fn clear_to_end_of_screen(&mut self) -> Result<(), Error> {
+        self.write(clear::TO_END_OF_SCREEN.as_bytes())
+    }
+
+    /// Move the cursor to `row` and `col`, counted from 0 at the top left of the screen.
+    pub fn move_to(&mut self, row: u16, col: u16) -> Result<(), Error> {
+        self.write(cursor::move_to(row, col).as_bytes())
+    }
+
+    /// Move the cursor up `n` rows.
+    pub fn move_up(&mut self, n: usize) -> Result<(), Error> {
+        self.write(cursor::up(n).as_bytes())
+    }
+
+    /// Move the cursor down `n` rows.
+    pub fn move_down(&mut self, n: usize) -> Result<(), Error> {
+        self.write(cursor::down(n).as_bytes())
+    }
+
+    /// Move the cursor left `n` columns.
+    pub fn move_left(&mut self, n: usize) -> Result<(), Error> {
+        self.write(cursor::left(n).as_bytes())
+    }
+
+    /// Move the cursor right `n` columns.
+    pub fn move_right(&mut self, n: usize) -> Result<(), Error> {
+        self.write(cursor::right(n).as_bytes())
+    }
+
+    /// Hide the cursor.
+    pub fn hide_cursor(&mut self) -> Result<(), Error> {
+        self.write(cursor::HIDE.as_bytes())
     }
 
-    /// Read byte from stdin. Return io::ErrorKind::WriteZero if no byte read.
-    pub fn read(&mut self, buf: &mut [u8; 1]) -> Result<usize, Error> {
-        match self.stdin.read(buf) {
-            Ok(0) => {
+    /// Show the cursor.
+    pub fn show_cursor(&mut self) -> Result<(), Error> {
+        self.write(cursor::SHOW.as_bytes())
+    }
+
+    /// Save the cursor position.
+    pub fn save_cursor(&mut self) -> Result<(), Error> {
+        self.write(cursor::SAVE.as_bytes())
+    }
+
+    /// Restore the cursor position saved with `save_cursor`.
+    pub fn restore_cursor(&mut self) -> Result<(), Error> {
+        self.write(cursor::RESTORE.as_bytes())
+    }
+
+    /// Copy `text` to the system clipboard with an OSC 52 escape sequence.
+    ///
+    /// Works over SSH in terminals that support it; others ignore the sequence.
+    pub fn set_clipboard(&mut self, text: &str) -> Result<(), Error> {
+        let sequence = format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()));
+        self.write(sequence.as_bytes())?;
+        self.flush()
+    }
+
+    /// Returns the cursor position as `(row, col)`, counted from 0 at the top left.
+    ///
+    /// Sends a device status report request (`ESC [ 6 n`) and waits up to
+    /// `DSR_TIMEOUT_MS` for the reply. Keys typed before the reply arrives are kept for
+    /// later calls to `read`.
+    pub fn cursor_position(&mut self) -> Result<(u16, u16), Error> {
+        self.write(b"\x1b[6n")?;
+        self.flush()?;
+
+        let mut received = Vec::new();
+        let position = loop {
+            if !wait_readable(self.fd, DSR_TIMEOUT_MS)? {
+                self.pending.extend(received);
                 return Err(Error::Io(io::Error::new(
-                    io::ErrorKind::WriteZero,
-                    "read 0 bytes from stdin",
+                    io::ErrorKind::TimedOut,
+                    "no reply to cursor position request",
                 )));
             }
-            Ok(bytes_read) => Ok(bytes_read),
+            let mut buf = [0u8; 1];
+            if self.read_fd(&mut buf)? == 0 {
+                self.pending.extend(received);
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "input closed before cursor position reply",
+                )));
+            }
+
+            received.push(buf[0]);
+            if buf[0] == b'R'
+                && let Some(start) = received.iter().rposition(|&b| b == 0x1B)
+                && let Some(position) = parse_position_report(&received[start..])
+            {
+                received.truncate(start);
+                break position;
+            }
+        };
+
+        self.pending.extend(received);
+        Ok(position)
+    }
+
+    /// Read the input that's available from stdin (or `/dev/tty`) into `buf`, blocking until
+    /// there's at least one byte. Returns the number of bytes read; any that don't fit are
+    /// returned by the next read. Return io::ErrorKind::WriteZero if no byte read.
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.pending.is_empty() {
+            self.fill_pending()?;
+        }
+        let n = buf.len().min(self.pending.len());
+        for (slot, byte) in buf.iter_mut().zip(self.pending.drain(..n)) {
+            *slot = byte;
+        }
+        Ok(n)
+    }
+
+    /// Read one byte of input, blocking until it arrives.
+    pub fn read_byte(&mut self) -> Result<u8, Error> {
+        let mut buf = [0u8; 1];
+        self.read(&mut buf)?;
+        Ok(buf[0])
+    }
+}
+
+impl TermManager {
+    /// Read one key: a single byte, a UTF-8 character, or a whole escape sequence.
+    ///
+    /// Blocks for the first byte, then waits briefly for each following byte, so sequences
+    /// split across reads (e.g. over slow links) still arrive whole. A UTF-8 character cut
+    /// short by a byte that can't continue it ends there, and the byte starts the next key.
+    pub fn read_sequence(&mut self) -> Result<Vec<u8>, Error> {
+        let first = self.read_byte()?;
+        let mut sequence = complete_sequence::<Error>(first, || {
+            if self.pending.is_empty() && !wait_readable(self.fd, ESCAPE_TIMEOUT_MS)? {
+                return Ok(None);
+            }
+            self.read_byte().map(Some)
+        })?;
+        if sequence[0] >= 0xC0
+            && let Some(end) = sequence[1..]
+                .iter()
+                .position(|b| !(0x80..=0xBF).contains(b))
+        {
+            for &b in sequence[end + 1..].iter().rev() {
+                self.pending.push_front(b);
+            }
+            sequence.truncate(end + 1);
+        }
+        Ok(sequence)
+    }
+
+    /// Returns `true` if input can be read without blocking, e.g. the rest of a string
+    /// committed by an input method.
+    pub fn input_pending(&self) -> Result<bool, Error> {
+        self.poll_read(Some(Duration::ZERO))
+    }
+
+    /// Wait up to `timeout` for input, or indefinitely if it's `None`. Returns `true` if input
+    /// can be read without blocking, `false` if the time ran out.
+    ///
+    /// Waiting resumes after a signal such as a window resize, up to the original deadline.
+    pub fn poll_read(&self, timeout: Option<Duration>) -> Result<bool, Error> {
+        if !self.pending.is_empty() {
+            return Ok(true);
+        }
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let timeout_ms = match deadline {
+                // Rounded up so a short wait isn't a busy loop.
+                Some(deadline) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_nanos()
+                    .div_ceil(1_000_000)
+                    .min(i32::MAX as u128) as i32,
+                None => -1,
+            };
+            match wait_readable(self.fd, timeout_ms) {
+                Err(Error::Io(e)) if e.kind() == io::ErrorKind::Interrupted => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// Read everything that's available into `pending` with one system call, blocking until
+    /// there's at least one byte, so a paste isn't read a byte at a time.
+    fn fill_pending(&mut self) -> Result<(), Error> {
+        let mut chunk = [0u8; READ_CHUNK];
+        match self.read_fd(&mut chunk) {
+            Ok(0) => Err(Error::Io(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "read 0 bytes from stdin",
+            ))),
+            Ok(bytes_read) => {
+                self.pending.extend(&chunk[..bytes_read]);
+                // Input may be a password; don't leave a copy on the stack.
+                for b in chunk[..bytes_read].iter_mut() {
+                    unsafe { std::ptr::write_volatile(b, 0) };
+                }
+                Ok(())
+            }
             Err(e) => Err(Error::Io(e)),
         }
     }
+
+    /// Returns where output is written: stdout, or the terminal passed to `from_fd`.
+    fn output(&mut self) -> &mut dyn Write {
+        match self.output.as_mut() {
+            Some(output) => output,
+            None => &mut self.stdout,
+        }
+    }
+
+    fn output_fd(&self) -> RawFd {
+        match self.output.as_ref() {
+            Some(output) => output.as_raw_fd(),
+            None => self.stdout.as_raw_fd(),
+        }
+    }
+
+    /// Read directly from the input file descriptor, bypassing `Stdin`'s buffer so that
+    /// `poll` sees every byte not yet read.
+    fn read_fd(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let res = unsafe { libc::read(self.fd, buf.as_mut_ptr().cast(), buf.len()) };
+        if res < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(res as usize)
+    }
 }
 
 impl Drop for TermManager {
     fn drop(&mut self) {
-        disable_raw_mode(self.fd, self.original_termios).unwrap();
+        // Best effort, since errors can't be reported here; `restore` returns them.
+        if self.mouse {
+            let _ = self.disable_mouse();
+        }
+        if self.kitty_keyboard {
+            let _ = self.disable_kitty_keyboard();
+        }
+        if self.focus_reporting {
+            let _ = self.disable_focus_reporting();
+        }
+        if self.alternate_screen {
+            let _ = self.leave_alternate_screen();
+        }
+        self.unwatch_resize();
     }
 }
 
-/// Enable raw mode by disabling canonical mode and echo.
-fn enable_raw_mode(fd: RawFd) -> Result<libc::termios, Error> {
-    let original_termios = get_termios(fd)?;
-    let mut raw = original_termios;
-    raw.c_lflag &= !(libc::ICANON | libc::ECHO);
-    raw.c_cc[libc::VMIN] = 1;
-    raw.c_cc[libc::VTIME] = 0;
-    set_termios(fd, &raw)?;
-    Ok(original_termios)
+/// Replace each `\n` in `data` that doesn't follow `\r` with `\r\n`.
+fn translate_newlines(data: &[u8]) -> Vec<u8> {
+    let mut translated = Vec::with_capacity(data.len() + data.len() / 8);
+    for (i, &b) in data.iter().enumerate() {
+        if b == b'\n' && (i == 0 || data[i - 1] != b'\r') {
+            translated.push(b'\r');
+        }
+        translated.push(b);
+    }
+    translated
 }
 
-/// Disable raw mode and reset terminal interface.
-fn disable_raw_mode(fd: RawFd, original_termios: libc::termios) -> Result<(), Error> {
-    set_termios(fd, &original_termios)?;
-    Ok(())
+/// Return `true` if the file descriptor refers to a terminal.
+pub fn is_tty(fd: RawFd) -> bool {
+    unsafe { libc::isatty(fd) == 1 }
+}
+
+/// Return `true` if stdin is connected to a terminal.
+pub fn stdin_is_tty() -> bool {
+    is_tty(io::stdin().as_raw_fd())
+}
+
+/// Return `true` if stdout is connected to a terminal.
+pub fn stdout_is_tty() -> bool {
+    is_tty(io::stdout().as_raw_fd())
+}
+
+/// Wait up to `timeout_ms` for `fd` to have input, or indefinitely if it's negative. Returns
+/// `false` on timeout.
+fn wait_readable(fd: RawFd, timeout_ms: i32) -> Result<bool, Error> {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let res = unsafe { libc::poll(&mut pollfd, 1, timeout_ms) };
+    if res < 0 {
+        return Err(Error::Io(io::Error::last_os_error()));
+    }
+    Ok(res > 0)
+}
+
+/// Collect the rest of the key starting with `first`: the continuation bytes of a UTF-8
+/// character, or the rest of an escape sequence.
+///
+/// `next` returns the following byte, or `None` if none arrives in time. An Escape with
+/// nothing after it is returned alone, and a sequence cut short is returned as far as it got.
+pub fn complete_sequence<E>(
+    first: u8,
+    mut next: impl FnMut() -> Result<Option<u8>, E>,
+) -> Result<Vec<u8>, E> {
+    let mut sequence = vec![first];
+    let lead = if first == 0x1B {
+        let Some(b) = next()? else {
+            return Ok(sequence);
+        };
+        sequence.push(b);
+        match b {
+            b'[' => {
+                // Parameter and intermediate bytes up to a final byte.
+                while let Some(b) = next()? {
+                    sequence.push(b);
+                    if (0x40..=0x7E).contains(&b) {
+                        break;
+                    }
+                }
+                return Ok(sequence);
+            }
+            b'O' => {
+                sequence.extend(next()?);
+                return Ok(sequence);
+            }
+            // Alt + key
+            b => b,
+        }
+    } else {
+        first
+    };
+
+    let continuation_bytes = match lead {
+        0xC0..=0xDF => 1,
+        0xE0..=0xEF => 2,
+        0xF0..=0xF7 => 3,
+        _ => 0,
+    };
+    for _ in 0..continuation_bytes {
+        match next()? {
+            Some(b) => sequence.push(b),
+            None => break,
+        }
+    }
+    Ok(sequence)
+}
+
+/// Parse a cursor position report (`ESC [ row ; col R`, 1-based) into 0-based `(row, col)`.
+fn parse_position_report(report: &[u8]) -> Option<(u16, u16)> {
+    let body = std::str::from_utf8(report.strip_prefix(b"\x1b[")?.strip_suffix(b"R")?).ok()?;
+    let (row, col) = body.split_once(';')?;
+    let row: u16 = row.parse().ok()?;
+    let col: u16 = col.parse().ok()?;
+    Some((row.checked_sub(1)?, col.checked_sub(1)?))
+}
+
+/// Encode bytes as standard base64 with padding.
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
 }
 
 /// Get termios from raw file descriptors.
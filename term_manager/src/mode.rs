@@ -0,0 +1,180 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::os::fd::RawFd;
+
+use crate::{Error, fatal, get_termios, set_termios};
+
+/// Terminal driver features, each either left to the terminal or turned off.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ModeFlags {
+    /// Line buffering and editing by the terminal (ICANON).
+    pub canonical: bool,
+    /// Echo typed characters (ECHO).
+    pub echo: bool,
+    /// Ctrl-C, Ctrl-Z and Ctrl-\ send SIGINT, SIGTSTP and SIGQUIT (ISIG).
+    pub signals: bool,
+    /// Ctrl-S and Ctrl-Q pause and resume output (IXON).
+    pub flow_control: bool,
+    /// Ctrl-V quotes the next character, among other extensions (IEXTEN).
+    pub extended: bool,
+    /// Carriage returns are read as `\n` (ICRNL), so Enter reads the same as Ctrl-J.
+    pub cr_to_newline: bool,
+    /// The terminal translates `\n` to `\r\n` on output (OPOST and ONLCR).
+    ///
+    /// When off, `TermManager::write` does the translation instead, but other writes to
+    /// stdout such as `println!` move down without returning to the first column.
+    pub output_processing: bool,
+}
+
+/// How the terminal processes input.
+///
+/// Every preset keeps output processing on, so `\n` starts a new line the same way in each
+/// mode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// The terminal's original settings, with line editing by the terminal.
+    Cooked,
+    /// Each key is read as it's pressed, without echo. Signal and flow control keys keep
+    /// working.
+    Cbreak,
+    /// Every key, including Ctrl-C, Ctrl-Z and Ctrl-S, is read as input.
+    Raw,
+    /// Individually chosen flags.
+    Custom(ModeFlags),
+}
+
+impl Mode {
+    /// Returns the flags this mode applies.
+    pub fn flags(&self) -> ModeFlags {
+        match *self {
+            Mode::Cooked => ModeFlags {
+                canonical: true,
+                echo: true,
+                signals: true,
+                flow_control: true,
+                extended: true,
+                cr_to_newline: true,
+                output_processing: true,
+            },
+            Mode::Cbreak => ModeFlags {
+                canonical: false,
+                echo: false,
+                ..Mode::Cooked.flags()
+            },
+            Mode::Raw => ModeFlags {
+                signals: false,
+                flow_control: false,
+                extended: false,
+                ..Mode::Cbreak.flags()
+            },
+            Mode::Custom(flags) => flags,
+        }
+    }
+
+    /// Returns `original` with this mode applied.
+    ///
+    /// Input flags left to the terminal keep their original setting, so `Cooked` restores
+    /// `original` unchanged. Output processing is turned on if enabled, whatever the original
+    /// setting.
+    pub(crate) fn apply(&self, original: &libc::termios) -> libc::termios {
+        if *self == Mode::Cooked {
+            return *original;
+        }
+
+        let flags = self.flags();
+        let mut termios = *original;
+        let lflags = [
+            (flags.canonical, libc::ICANON),
+            (flags.echo, libc::ECHO),
+            (flags.signals, libc::ISIG),
+            (flags.extended, libc::IEXTEN),
+        ];
+        for (_, flag) in lflags.iter().filter(|(enabled, _)| !enabled) {
+            termios.c_lflag &= !flag;
+        }
+        if !flags.flow_control {
+            termios.c_iflag &= !libc::IXON;
+        }
+        if !flags.cr_to_newline {
+            termios.c_iflag &= !libc::ICRNL;
+        }
+        if flags.output_processing {
+            termios.c_oflag |= libc::OPOST | libc::ONLCR;
+        } else {
+            termios.c_oflag &= !libc::OPOST;
+        }
+        if !flags.canonical {
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+        }
+        termios
+    }
+}
+
+/// Puts a terminal into a mode, restoring its original settings when dropped.
+///
+/// Use `suspend` and `resume` to drop back to the original settings temporarily, e.g.
+/// while a subprocess uses the terminal.
+pub struct RawModeGuard {
+    fd: RawFd,
+    original_termios: libc::termios,
+    mode: Mode,
+    suspended: bool,
+}
+
+impl RawModeGuard {
+    /// Put the terminal on `fd` into raw mode.
+    pub fn new(fd: RawFd) -> Result<Self, Error> {
+        Self::with_mode(fd, Mode::Raw)
+    }
+
+    /// Put the terminal on `fd` into `mode`.
+    pub fn with_mode(fd: RawFd, mode: Mode) -> Result<Self, Error> {
+        let original_termios = get_termios(fd)?;
+        set_termios(fd, &mode.apply(&original_termios))?;
+        fatal::save(fd, original_termios);
+        Ok(Self {
+            fd,
+            original_termios,
+            mode,
+            suspended: false,
+        })
+    }
+
+    /// Returns the mode applied while not suspended.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Switch to `mode`. If suspended, it takes effect on `resume`.
+    pub fn set_mode(&mut self, mode: Mode) -> Result<(), Error> {
+        if !self.suspended {
+            set_termios(self.fd, &mode.apply(&self.original_termios))?;
+        }
+        self.mode = mode;
+        Ok(())
+    }
+
+    /// Restore the original terminal settings until `resume` is called.
+    pub fn suspend(&mut self) -> Result<(), Error> {
+        set_termios(self.fd, &self.original_termios)?;
+        self.suspended = true;
+        Ok(())
+    }
+
+    /// Re-apply the mode after `suspend`.
+    pub fn resume(&mut self) -> Result<(), Error> {
+        set_termios(self.fd, &self.mode.apply(&self.original_termios))?;
+        self.suspended = false;
+        Ok(())
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = set_termios(self.fd, &self.original_termios);
+        fatal::forget(self.fd);
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+//! Escape sequences for switching screens and other whole-screen state.
+
+/// Switch to the alternate screen buffer, saving the cursor position.
+pub const ENTER_ALTERNATE: &str = "\x1b[?1049h";
+/// Return to the primary screen buffer and its scrollback, restoring the cursor position.
+pub const LEAVE_ALTERNATE: &str = "\x1b[?1049l";
+
+/// Ring the terminal bell.
+pub const BELL: &str = "\x07";
+/// Swap foreground and background colors across the whole screen (DECSCNM), for a visual bell.
+pub const REVERSE_VIDEO: &str = "\x1b[?5h";
+/// Undo `REVERSE_VIDEO`.
+pub const NORMAL_VIDEO: &str = "\x1b[?5l";
+
+/// Wrap pasted text in `ESC [ 200 ~` and `ESC [ 201 ~`, so it can be told apart from typing.
+pub const BRACKETED_PASTE_ON: &str = "\x1b[?2004h";
+/// Undo `BRACKETED_PASTE_ON`.
+pub const BRACKETED_PASTE_OFF: &str = "\x1b[?2004l";
+
+/// Reset the scroll region to the whole screen.
+pub const RESET_SCROLL_REGION: &str = "\x1b[r";
+
+/// Restrict scrolling to rows `top` through `bottom` inclusive, counted from 0 (DECSTBM).
+///
+/// Rows outside the region stay in place while output scrolls, e.g. for a status bar.
+pub fn scroll_region(top: u16, bottom: u16) -> String {
+    format!("\x1b[{};{}r", top as u32 + 1, bottom as u32 + 1)
+}
+
+/// Set the window and tab title (OSC 0). Control characters are removed from `title`.
+pub fn title(title: &str) -> String {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    format!("\x1b]0;{}\x07", title)
+}
+
+/// Returns `text` as a clickable link to `url` (OSC 8). Control characters are removed from
+/// `url`; terminals without hyperlinks show `text` alone.
+pub fn hyperlink(url: &str, text: &str) -> String {
+    let url: String = url.chars().filter(|c| !c.is_control()).collect();
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Display text in the order it's written, leaving bidirectional reordering to the
+/// application (explicit mode of BDSM, ECMA-48). Terminals without BiDi support ignore it.
+pub const BIDI_EXPLICIT: &str = "\x1b[8l";
+/// Let the terminal reorder right-to-left text for display (implicit mode of BDSM).
+pub const BIDI_IMPLICIT: &str = "\x1b[8h";
@@ -0,0 +1,261 @@
+// Copyright (c) 2026 Sebastian Ibanez
+// Author: Sebastian Ibanez
+// Created: 2026-10-16
+
+use std::env;
+
+use crate::stdout_is_tty;
+
+/// Number of colors a terminal can display.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorDepth {
+    /// Plain text, without colors or attributes.
+    NoColor,
+    /// The 16 standard colors.
+    Ansi16,
+    /// The 256-color palette.
+    Ansi256,
+    /// 24-bit color.
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Detect the color depth from the `COLORTERM` and `TERM` environment variables.
+    ///
+    /// Styling is disabled if `NO_COLOR` is set, `TERM` is `dumb`, or stdout is not a
+    /// terminal, unless `CLICOLOR_FORCE` is set to something other than `0`.
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let force = env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0");
+        if !force && (no_color || term == "dumb" || !stdout_is_tty()) {
+            ColorDepth::NoColor
+        } else if matches!(colorterm.as_str(), "truecolor" | "24bit")
+            || term.contains("truecolor")
+            || term.contains("direct")
+        {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// RGB values of the 16 standard colors, as xterm displays them.
+const ANSI16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// A terminal color.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// A color from the 256-color palette.
+    Ansi(u8),
+    /// A 24-bit color.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Returns the SGR sequence setting this as the foreground color.
+    pub fn fg_sequence(&self) -> String {
+        self.sequence(30)
+    }
+
+    /// Returns the SGR sequence setting this as the background color.
+    pub fn bg_sequence(&self) -> String {
+        self.sequence(40)
+    }
+
+    /// Returns the closest color a terminal with `depth` can display.
+    ///
+    /// Colors are returned unchanged for `NoColor`; callers skip styling entirely instead.
+    pub fn downgrade(self, depth: ColorDepth) -> Color {
+        match (self, depth) {
+            (_, ColorDepth::TrueColor) => self,
+            (Color::Rgb(r, g, b), ColorDepth::Ansi256) => Color::Ansi(rgb_to_ansi256(r, g, b)),
+            (Color::Rgb(r, g, b), ColorDepth::Ansi16) => nearest_ansi16(r, g, b),
+            (Color::Ansi(n), ColorDepth::Ansi16) => {
+                let (r, g, b) = ansi256_to_rgb(n);
+                nearest_ansi16(r, g, b)
+            }
+            _ => self,
+        }
+    }
+
+    /// Returns the color's RGB value, using xterm's values for the palette colors.
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Rgb(r, g, b) => (r, g, b),
+            other => ansi256_to_rgb(other.index()),
+        }
+    }
+
+    /// Build the SGR sequence, where `base` is 30 for foreground and 40 for background.
+    fn sequence(&self, base: u8) -> String {
+        match *self {
+            Color::Ansi(n) => format!("\x1b[{};5;{}m", base + 8, n),
+            Color::Rgb(r, g, b) => format!("\x1b[{};2;{};{};{}m", base + 8, r, g, b),
+            named => {
+                let index = named.index();
+                if index < 8 {
+                    format!("\x1b[{}m", base + index)
+                } else {
+                    // Bright colors use 90-97 and 100-107.
+                    format!("\x1b[{}m", base + 60 + index - 8)
+                }
+            }
+        }
+    }
+
+    /// Returns the palette index of a named color (0-15).
+    fn index(&self) -> u8 {
+        match self {
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::BrightBlack => 8,
+            Color::BrightRed => 9,
+            Color::BrightGreen => 10,
+            Color::BrightYellow => 11,
+            Color::BrightBlue => 12,
+            Color::BrightMagenta => 13,
+            Color::BrightCyan => 14,
+            Color::BrightWhite => 15,
+            Color::Ansi(n) => *n,
+            Color::Rgb(..) => 7,
+        }
+    }
+
+    /// Returns the named color for a palette index (0-15).
+    fn from_index(index: u8) -> Color {
+        match index {
+            0 => Color::Black,
+            1 => Color::Red,
+            2 => Color::Green,
+            3 => Color::Yellow,
+            4 => Color::Blue,
+            5 => Color::Magenta,
+            6 => Color::Cyan,
+            7 => Color::White,
+            8 => Color::BrightBlack,
+            9 => Color::BrightRed,
+            10 => Color::BrightGreen,
+            11 => Color::BrightYellow,
+            12 => Color::BrightBlue,
+            13 => Color::BrightMagenta,
+            14 => Color::BrightCyan,
+            _ => Color::BrightWhite,
+        }
+    }
+}
+
+/// Map a 24-bit color onto the 6x6x6 color cube or grayscale ramp of the 256-color palette.
+fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0..=7 => 16,
+            248..=255 => 231,
+            _ => 232 + (r - 8) / 10,
+        };
+    }
+
+    let level = |v: u8| match v {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (v - 35) / 40,
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// Returns the RGB value of a 256-color palette entry.
+fn ansi256_to_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => ANSI16_RGB[n as usize],
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let n = n - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let gray = 8 + (n - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// Returns the standard color closest to a 24-bit color.
+fn nearest_ansi16(r: u8, g: u8, b: u8) -> Color {
+    let distance = |&(cr, cg, cb): &(u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, cr) + d(g, cg) + d(b, cb)
+    };
+    let index = (0..16)
+        .min_by_key(|&i| distance(&ANSI16_RGB[i]))
+        .unwrap_or(7);
+    Color::from_index(index as u8)
+}
+
+/// A text attribute.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Attribute {
+    Bold,
+    Dim,
+    Italic,
+    Underline,
+    Reverse,
+}
+
+impl Attribute {
+    /// Returns the SGR sequence enabling this attribute.
+    pub fn sequence(&self) -> &'static str {
+        match self {
+            Attribute::Bold => "\x1b[1m",
+            Attribute::Dim => "\x1b[2m",
+            Attribute::Italic => "\x1b[3m",
+            Attribute::Underline => "\x1b[4m",
+            Attribute::Reverse => "\x1b[7m",
+        }
+    }
+}
+
+/// SGR sequence resetting colors and attributes to the terminal default.
+pub const RESET_STYLE: &str = "\x1b[0m";